@@ -0,0 +1,32 @@
+/* C declarations for the jfactor library, built with the `ffi` feature. */
+
+#ifndef JFACTOR_H
+#define JFACTOR_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Factors n into primes and exponents.
+ *
+ * Writes the distinct prime factors of n in ascending order to out_primes
+ * and their exponents to out_exps, up to cap of them.
+ * Returns the number of distinct prime factors, which may be larger than cap,
+ * in which case only the first cap are written.
+ * 15 is enough for all 64-bit integers.
+ * Returns -1 if n is 0.
+ */
+ptrdiff_t jfactor_factor_u64(uint64_t n, uint64_t *out_primes, uint32_t *out_exps, size_t cap);
+
+/* Returns the number of divisors of n, or 0 if n is 0. */
+uint64_t jfactor_num_divisors_u64(uint64_t n);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
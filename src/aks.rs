@@ -0,0 +1,183 @@
+
+extern crate num;
+
+use self::num::integer::{gcd, Roots};
+
+use arith::Word;
+use factor::factor;
+
+
+/// Tests whether an integer is prime using the AKS algorithm.
+///
+/// This is the deterministic polynomial time algorithm of
+/// Agrawal, Kayal and Saxena, which proves primality without relying
+/// on any conjecture.
+/// It is very slow in practice, a seven digit prime takes seconds,
+/// so it is meant as a reference to compare other tests against
+/// and for teaching, use `is_prime` otherwise.
+///
+/// This is only available with the `aks` feature.
+///
+/// ```
+/// use jfactor::aks_is_prime;
+///
+/// let primes: Vec<u64> = (0..30).filter(|&n| aks_is_prime(n)).collect();
+/// assert_eq!(vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29], primes);
+/// ```
+///
+pub fn aks_is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+
+    // step 1: perfect powers are composite
+    if is_perfect_power(n) {
+        return false;
+    }
+
+    // step 2: find the smallest r with ord_r(n) > log2(n)^2
+    let log_n = (n as f64).log2();
+    let max_k = (log_n * log_n).floor() as u64;
+    let mut r = 2;
+    while gcd(r, n) != 1 || multiplicative_order(n, r, max_k).is_some() {
+        r += 1;
+    }
+
+    // step 3: a common factor with some a <= r shows that n is composite
+    for a in 2..=r.min(n - 1) {
+        if gcd(a, n) != 1 {
+            return false;
+        }
+    }
+
+    // step 4
+    if n <= r {
+        return true;
+    }
+
+    // step 5: check (X + a)^n = X^n + a mod (X^r - 1, n)
+    let phi_r = factor(r)
+        .iter()
+        .map(|(&p, &e)| (p - 1) * p.pow(e - 1))
+        .product::<u64>();
+    let limit = ((phi_r as f64).sqrt() * log_n).floor() as u64;
+    let r = r as usize;
+    for a in 1..=limit {
+        let lhs = poly_pow(&[a % n, 1], n, r, n);
+        let mut rhs = vec![0; r];
+        rhs[0] = a % n;
+        let i = (n % r as u64) as usize;
+        rhs[i] = u64::add_mod(rhs[i], 1, n);
+        if lhs != rhs {
+            return false;
+        }
+    }
+
+    // step 6
+    true
+}
+
+
+/// Returns true if n = a^b for integers a and b > 1.
+///
+fn is_perfect_power(n: u64) -> bool {
+    (2..64).any(|b| {
+        let a = n.nth_root(b);
+        a > 1 && a.checked_pow(b) == Some(n)
+    })
+}
+
+
+/// Returns the multiplicative order of n modulo r if it is at most max_k.
+///
+/// n and r must be coprime.
+///
+fn multiplicative_order(n: u64, r: u64, max_k: u64) -> Option<u64> {
+    let n = n % r;
+    let mut x = 1 % r;
+    for k in 1..=max_k {
+        x = u64::mul_mod(x, n, r);
+        if x == 1 {
+            return Some(k);
+        }
+    }
+    None
+}
+
+
+/// Multiplies two polynomials modulo X^r - 1 with coefficients modulo m.
+///
+/// The coefficients must be less than m and the polynomials must have
+/// at most r coefficients.
+///
+fn poly_mul(a: &[u64], b: &[u64], r: usize, m: u64) -> Vec<u64> {
+    // for small m the products can be summed up without overflow
+    // and reduced at the end, otherwise each product is reduced
+    let small = m >> 32 == 0;
+    let mut sums = vec![0_u128; r];
+    for (i, &ai) in a.iter().enumerate() {
+        if ai == 0 {
+            continue;
+        }
+        for (j, &bj) in b.iter().enumerate() {
+            let k = if i + j >= r { i + j - r } else { i + j };
+            let product = ai as u128 * bj as u128;
+            sums[k] += if small { product } else { product % m as u128 };
+        }
+    }
+    sums.into_iter().map(|s| (s % m as u128) as u64).collect()
+}
+
+
+/// Raises a polynomial to the power e modulo X^r - 1
+/// with coefficients modulo m.
+///
+fn poly_pow(a: &[u64], e: u64, r: usize, m: u64) -> Vec<u64> {
+    let mut result = vec![0; r];
+    result[0] = 1 % m;
+    let mut base = poly_mul(a, &[1], r, m);
+    let mut e = e;
+    while e > 0 {
+        if e & 1 == 1 {
+            result = poly_mul(&result, &base, r, m);
+        }
+        e >>= 1;
+        if e > 0 {
+            base = poly_mul(&base, &base, r, m);
+        }
+    }
+    result
+}
+
+
+#[cfg(test)]
+mod tests {
+    use primality::is_prime;
+
+    use super::*;
+
+    #[test]
+    fn test_aks_is_prime() {
+        for n in 0..500 {
+            assert_eq!(is_prime(n), aks_is_prime(n), "{}", n);
+        }
+        assert!(aks_is_prime(1009));
+        assert!(!aks_is_prime(1009 * 1013));
+    }
+
+    #[test]
+    fn test_is_perfect_power() {
+        assert!(is_perfect_power(1 << 40));
+        assert!(is_perfect_power(3486784401));
+        assert!(is_perfect_power(4294967291 * 4294967291));
+        assert!(!is_perfect_power(4294967291 * 4294967279));
+        assert!(!is_perfect_power(u64::MAX));
+    }
+
+    #[test]
+    fn test_poly_pow() {
+        // (X + 1)^3 = X^3 + 3 X^2 + 3 X + 1 = 3 X^2 + 3 X + 2 mod X^3 - 1
+        assert_eq!(vec![2, 3, 3], poly_pow(&[1, 1], 3, 3, 10));
+        assert_eq!(vec![2, 0, 0], poly_pow(&[1, 1], 3, 3, 3));
+    }
+}
@@ -0,0 +1,483 @@
+
+extern crate num;
+
+use std::fmt::Debug;
+
+use self::num::integer::Roots;
+use self::num::{Integer, NumCast, PrimInt, Unsigned};
+
+
+/// Unsigned machine words with the modular arithmetic needed for factoring.
+///
+pub trait Word: PrimInt + Integer + Unsigned + Roots + Debug {
+    /// Arithmetic context for computations modulo a fixed odd modulus.
+    type Modulus: Modulus<Self>;
+
+    /// Computes a * b mod m without overflow.
+    ///
+    /// a and b must be less than m.
+    ///
+    fn mul_mod(a: Self, b: Self, m: Self) -> Self;
+
+    /// Computes a + b mod m without overflow.
+    ///
+    /// a and b must be less than m.
+    ///
+    fn add_mod(a: Self, b: Self, m: Self) -> Self {
+        if a >= m - b {
+            a - (m - b)
+        } else {
+            a + b
+        }
+    }
+
+    /// Computes a - b mod m without overflow.
+    ///
+    /// a and b must be less than m.
+    ///
+    fn sub_mod(a: Self, b: Self, m: Self) -> Self {
+        if a >= b {
+            a - b
+        } else {
+            m - b + a
+        }
+    }
+}
+
+impl Word for u32 {
+    type Modulus = PlainModulus<u32>;
+
+    fn mul_mod(a: u32, b: u32, m: u32) -> u32 {
+        (a as u64 * b as u64 % m as u64) as u32
+    }
+}
+
+impl Word for u64 {
+    type Modulus = MontgomeryModulus64;
+
+    fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+        (a as u128 * b as u128 % m as u128) as u64
+    }
+}
+
+impl Word for u128 {
+    type Modulus = MontgomeryModulus128;
+
+    /// There is no wider type to multiply in,
+    /// so this uses shift and add unless both factors fit in 64 bits.
+    /// It is slow, use `MontgomeryModulus128` in inner loops.
+    ///
+    fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+        if (a | b) >> 64 == 0 {
+            a * b % m
+        } else {
+            let mut result = 0;
+            let mut a = a;
+            let mut b = b;
+            while b != 0 {
+                if b & 1 != 0 {
+                    result = u128::add_mod(result, a, m);
+                }
+                a = u128::add_mod(a, a, m);
+                b >>= 1;
+            }
+            result
+        }
+    }
+}
+
+
+/// Bit i is set if i is a square modulo 64,
+/// for quickly rejecting most non-squares.
+pub(crate) const SQUARES_MOD_64: u64 = 0x0202_0212_0203_0213;
+
+
+/// Returns the square root of n if n is a perfect square.
+///
+pub(crate) fn exact_sqrt<T: Word>(n: T) -> Option<T> {
+    let low = (n & NumCast::from(63).unwrap()).to_u64().unwrap();
+    if (SQUARES_MOD_64 >> low) & 1 == 0 {
+        return None;
+    }
+    let s = n.sqrt();
+    if s * s == n {
+        Some(s)
+    } else {
+        None
+    }
+}
+
+
+/// Arithmetic modulo a fixed odd modulus.
+///
+/// Residues are kept in an internal representation which may differ
+/// from their ordinary value, use `to_residue` and `value`
+/// to convert.
+/// Addition, subtraction and multiplication of residues commute with
+/// these conversions.
+///
+pub trait Modulus<T: Word>: Copy {
+    /// Creates the context for the odd modulus m.
+    ///
+    fn new(m: T) -> Self;
+
+    /// Returns the modulus.
+    ///
+    fn modulus(&self) -> T;
+
+    /// Converts x in the range 0..m to its internal representation.
+    ///
+    fn to_residue(&self, x: T) -> T;
+
+    /// Returns the value in the range 0..m of a residue.
+    ///
+    #[allow(dead_code)]
+    fn value(&self, x: T) -> T;
+
+    /// Multiplies two residues.
+    ///
+    fn mul(&self, a: T, b: T) -> T;
+
+    /// Adds two residues.
+    ///
+    fn add(&self, a: T, b: T) -> T {
+        T::add_mod(a, b, self.modulus())
+    }
+
+    /// Subtracts two residues.
+    ///
+    fn sub(&self, a: T, b: T) -> T {
+        T::sub_mod(a, b, self.modulus())
+    }
+
+    /// Returns the residue of 1.
+    ///
+    fn one(&self) -> T {
+        self.to_residue(T::one())
+    }
+
+    /// Raises a residue to a power by repeated squaring.
+    ///
+    fn pow_u64(&self, a: T, e: u64) -> T {
+        let mut result = self.one();
+        let mut a = a;
+        let mut e = e;
+        while e != 0 {
+            if e & 1 != 0 {
+                result = self.mul(result, a);
+            }
+            a = self.mul(a, a);
+            e >>= 1;
+        }
+        result
+    }
+
+    /// Raises a residue to a power by repeated squaring.
+    ///
+    fn pow(&self, a: T, e: T) -> T {
+        let mut result = self.one();
+        let mut a = a;
+        let mut e = e;
+        while e != T::zero() {
+            if e & T::one() != T::zero() {
+                result = self.mul(result, a);
+            }
+            a = self.mul(a, a);
+            e = e >> 1;
+        }
+        result
+    }
+}
+
+
+/// Arithmetic modulo m where residues are represented by their values.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct PlainModulus<T> {
+    m: T,
+}
+
+impl<T: Word> Modulus<T> for PlainModulus<T> {
+    fn new(m: T) -> PlainModulus<T> {
+        PlainModulus { m }
+    }
+    fn modulus(&self) -> T {
+        self.m
+    }
+    fn to_residue(&self, x: T) -> T {
+        x
+    }
+    fn value(&self, x: T) -> T {
+        x
+    }
+    fn mul(&self, a: T, b: T) -> T {
+        T::mul_mod(a, b, self.m)
+    }
+}
+
+
+/// Arithmetic modulo an odd 64-bit modulus m in Montgomery form.
+///
+/// A residue x is represented by x * 2^64 mod m,
+/// which replaces the 128-bit division of `mul_mod`
+/// by two multiplications.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct MontgomeryModulus64 {
+    m: u64,
+    /// -m^-1 mod 2^64
+    m_neg_inv: u64,
+    /// 2^128 mod m
+    r2: u64,
+}
+
+impl MontgomeryModulus64 {
+    /// Computes x * 2^-64 mod m for x < m * 2^64.
+    ///
+    fn reduce(&self, x: u128) -> u64 {
+        let q = (x as u64).wrapping_mul(self.m_neg_inv);
+        let qm = q as u128 * self.m as u128;
+        // the low half of the sum is zero by construction of q,
+        // the high half is less than 2 m
+        let carry = (x as u64).overflowing_add(qm as u64).1 as u128;
+        let t = (x >> 64) + (qm >> 64) + carry;
+        if t >= self.m as u128 {
+            (t - self.m as u128) as u64
+        } else {
+            t as u64
+        }
+    }
+}
+
+impl Modulus<u64> for MontgomeryModulus64 {
+    fn new(m: u64) -> MontgomeryModulus64 {
+        assert!(m & 1 == 1);
+
+        // Newton iteration doubles the number of correct low bits,
+        // starting with 3 bits since m * m == 1 mod 8 for odd m
+        let mut inv = m;
+        for _ in 0..5 {
+            inv = inv.wrapping_mul(2_u64.wrapping_sub(m.wrapping_mul(inv)));
+        }
+        let r2 = ((u128::MAX % m as u128 + 1) % m as u128) as u64;
+        MontgomeryModulus64 {
+            m,
+            m_neg_inv: inv.wrapping_neg(),
+            r2,
+        }
+    }
+    fn modulus(&self) -> u64 {
+        self.m
+    }
+    fn to_residue(&self, x: u64) -> u64 {
+        self.reduce(x as u128 * self.r2 as u128)
+    }
+    fn value(&self, x: u64) -> u64 {
+        self.reduce(x as u128)
+    }
+    fn mul(&self, a: u64, b: u64) -> u64 {
+        self.reduce(a as u128 * b as u128)
+    }
+}
+
+
+/// Arithmetic modulo an odd 128-bit modulus m in Montgomery form.
+///
+/// A residue x is represented by x * 2^128 mod m,
+/// which allows multiplication without division.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct MontgomeryModulus128 {
+    m: u128,
+    /// -m^-1 mod 2^128
+    m_neg_inv: u128,
+    /// 2^256 mod m
+    r2: u128,
+}
+
+impl MontgomeryModulus128 {
+    /// Computes x * 2^-128 mod m for x = hi * 2^128 + lo < m * 2^128.
+    ///
+    fn reduce(&self, hi: u128, lo: u128) -> u128 {
+        let q = lo.wrapping_mul(self.m_neg_inv);
+        let (qm_hi, qm_lo) = mul_wide_u128(q, self.m);
+        // the low half of the sum is zero by construction of q
+        let carry = lo.overflowing_add(qm_lo).1 as u128;
+        let (t, overflow1) = hi.overflowing_add(qm_hi);
+        let (t, overflow2) = t.overflowing_add(carry);
+        if overflow1 || overflow2 || t >= self.m {
+            t.wrapping_sub(self.m)
+        } else {
+            t
+        }
+    }
+}
+
+impl Modulus<u128> for MontgomeryModulus128 {
+    fn new(m: u128) -> MontgomeryModulus128 {
+        assert!(m & 1 == 1);
+
+        // Newton iteration doubles the number of correct low bits,
+        // starting with 3 bits since m * m == 1 mod 8 for odd m
+        let mut inv = m;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2_u128.wrapping_sub(m.wrapping_mul(inv)));
+        }
+        let r = (u128::MAX % m + 1) % m;
+        let r2 = u128::mul_mod(r, r, m);
+        MontgomeryModulus128 {
+            m,
+            m_neg_inv: inv.wrapping_neg(),
+            r2,
+        }
+    }
+    fn modulus(&self) -> u128 {
+        self.m
+    }
+    fn to_residue(&self, x: u128) -> u128 {
+        let (hi, lo) = mul_wide_u128(x, self.r2);
+        self.reduce(hi, lo)
+    }
+    fn value(&self, x: u128) -> u128 {
+        self.reduce(0, x)
+    }
+    fn mul(&self, a: u128, b: u128) -> u128 {
+        let (hi, lo) = mul_wide_u128(a, b);
+        self.reduce(hi, lo)
+    }
+}
+
+
+/// Computes the full 256-bit product of a and b as (high, low) halves.
+///
+fn mul_wide_u128(a: u128, b: u128) -> (u128, u128) {
+    let mask = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & mask);
+    let (b_hi, b_lo) = (b >> 64, b & mask);
+
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+
+    let middle = (lo_lo >> 64) + (hi_lo & mask) + (lo_hi & mask);
+    let lo = (middle << 64) | (lo_lo & mask);
+    let hi = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (middle >> 64);
+    (hi, lo)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mul_mod_u32() {
+        assert_eq!(1, u32::mul_mod(4294967290, 4294967290, 4294967291));
+    }
+
+    #[test]
+    fn test_mul_mod_u64() {
+        let m = 18446744073709551557_u64;
+        assert_eq!(1, u64::mul_mod(m - 1, m - 1, m));
+        assert_eq!(m - 2, u64::mul_mod(m - 1, 2, m));
+    }
+
+    #[test]
+    fn test_mul_mod_u128() {
+        let m = (1_u128 << 127) - 1;
+        assert_eq!(1, u128::mul_mod(m - 1, m - 1, m));
+        assert_eq!(m - 2, u128::mul_mod(m - 1, 2, m));
+        assert_eq!(4, u128::mul_mod(1 << 64, 1 << 65, m));
+    }
+
+    #[test]
+    fn test_mul_wide_u128() {
+        assert_eq!((u128::MAX - 1, 1), mul_wide_u128(u128::MAX, u128::MAX));
+        assert_eq!((1, 0), mul_wide_u128(1 << 64, 1 << 64));
+    }
+
+    #[test]
+    fn test_montgomery_64_round_trip() {
+        let m = MontgomeryModulus64::new(u64::MAX - 58);
+        for &x in &[0, 1, 2, 12345, u64::MAX - 59] {
+            assert_eq!(x, m.value(m.to_residue(x)));
+        }
+    }
+
+    #[test]
+    fn test_montgomery_64_mul() {
+        for &n in &[u64::MAX, u64::MAX - 58, 1_000_000_007, 3] {
+            let m = MontgomeryModulus64::new(n);
+            for &(a, b) in &[(n - 1, n - 1), (n - 3, n / 3 + 1), (2, n / 2 + 1), (0, n - 1)] {
+                let product = m.mul(m.to_residue(a), m.to_residue(b));
+                assert_eq!(u64::mul_mod(a, b, n), m.value(product));
+                let sum = m.add(m.to_residue(a), m.to_residue(b));
+                assert_eq!(u64::add_mod(a, b, n), m.value(sum));
+            }
+        }
+    }
+
+    #[test]
+    fn test_montgomery_round_trip() {
+        let m = MontgomeryModulus128::new(u128::MAX - 158);
+        for &x in &[0, 1, 2, 12345, u128::MAX - 159] {
+            assert_eq!(x, m.value(m.to_residue(x)));
+        }
+    }
+
+    #[test]
+    fn test_montgomery_mul() {
+        for &n in &[(1_u128 << 127) - 1, u128::MAX - 158, 1_000_000_007] {
+            let m = MontgomeryModulus128::new(n);
+            let a = n - 3;
+            let b = n / 3 + 5;
+            let product = m.mul(m.to_residue(a), m.to_residue(b));
+            assert_eq!(u128::mul_mod(a, b, n), m.value(product));
+        }
+    }
+
+    #[test]
+    fn test_montgomery_pow() {
+        // Fermat's little theorem for the Mersenne prime 2^127 - 1
+        let n = (1_u128 << 127) - 1;
+        let m = MontgomeryModulus128::new(n);
+        assert_eq!(1, m.value(m.pow(m.to_residue(3), n - 1)));
+    }
+
+    #[test]
+    fn test_pow_u64() {
+        let m = PlainModulus::new(1_000_000_007_u64);
+        assert_eq!(1, m.pow_u64(2, 1_000_000_006));
+        assert_eq!(1024, m.pow_u64(2, 10));
+        assert_eq!(1, m.pow_u64(2, 0));
+    }
+
+    #[test]
+    fn test_exact_sqrt() {
+        for x in 0..1000_u64 {
+            assert_eq!(Some(x), exact_sqrt(x * x));
+            if x > 1 {
+                assert_eq!(None, exact_sqrt(x * x + 1));
+                assert_eq!(None, exact_sqrt(x * x - 1));
+            }
+        }
+        let x = u64::MAX as u128;
+        assert_eq!(Some(x), exact_sqrt(x * x));
+        assert_eq!(None, exact_sqrt(x * x - 1));
+    }
+
+    #[test]
+    fn test_add_mod() {
+        let m = 18446744073709551557_u64;
+        assert_eq!(1, u64::add_mod(m - 1, 2, m));
+        assert_eq!(5, u64::add_mod(2, 3, m));
+    }
+
+    #[test]
+    fn test_sub_mod() {
+        let m = 18446744073709551557_u64;
+        assert_eq!(m - 1, u64::sub_mod(2, 3, m));
+        assert_eq!(1, u64::sub_mod(3, 2, m));
+    }
+}
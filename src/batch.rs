@@ -0,0 +1,143 @@
+
+extern crate num;
+extern crate num_bigint;
+
+use self::num::integer::gcd;
+use self::num::{One, ToPrimitive};
+use self::num_bigint::BigUint;
+
+
+/// Returns the levels of the product tree of the given integers,
+/// from the leaves to the root.
+///
+/// Each node is the product of its two children,
+/// an odd node at the end of a level is carried over as it is.
+///
+fn product_tree(leaves: Vec<BigUint>) -> Vec<Vec<BigUint>> {
+    let mut levels = vec![leaves];
+    while levels.last().unwrap().len() > 1 {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| pair.iter().fold(BigUint::one(), |a, b| a * b))
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+
+/// Computes for each integer in ns the largest divisor which has
+/// only prime factors from the given primes.
+///
+/// This is Bernstein's batch algorithm: the product P of the primes
+/// is reduced modulo all n at once with a remainder tree
+/// over the product tree of ns, and then the part of n
+/// supported on the primes is gcd(n, P^(2^e) mod n)
+/// for 2^e at least the number of bits of n.
+/// With fast multiplication this takes quasi-linear time in the total
+/// size of the input.
+/// As the big integers of num use schoolbook multiplication and division,
+/// the trees are built on groups of ns with a product about the size
+/// of P, which bounds the size of the numbers involved.
+/// Then the runtime is about proportional to the number of integers
+/// times the size of P, somewhat faster than trial division.
+///
+/// All integers in ns must be positive.
+///
+/// ```
+/// use jfactor::smooth_parts;
+///
+/// let parts = smooth_parts(&[12, 35, 1000003 * 49], &[2, 3, 7]);
+/// assert_eq!(vec![12, 7, 49], parts);
+/// ```
+///
+pub fn smooth_parts(ns: &[u64], primes: &[u32]) -> Vec<u64> {
+    assert!(ns.iter().all(|&n| n > 0));
+    if ns.is_empty() {
+        return Vec::new();
+    }
+
+    let prime_tree = product_tree(primes.iter().map(|&p| BigUint::from(p)).collect());
+    let product = prime_tree
+        .last()
+        .unwrap()
+        .first()
+        .cloned()
+        .unwrap_or_else(BigUint::one);
+
+    // products of ns larger than P do not help as P is its own remainder,
+    // so the trees are built on groups of about the size of P,
+    // descending each tree with remainders modulo its nodes
+    let group_size = (product.bits() / 64).max(1);
+    let mut remainders = Vec::with_capacity(ns.len());
+    for group in ns.chunks(group_size) {
+        let tree = product_tree(group.iter().map(|&n| BigUint::from(n)).collect());
+        let mut group_remainders = vec![&product % &tree.last().unwrap()[0]];
+        for level in tree.iter().rev().skip(1) {
+            group_remainders = level
+                .iter()
+                .enumerate()
+                .map(|(i, node)| &group_remainders[i / 2] % node)
+                .collect();
+        }
+        remainders.extend(group_remainders);
+    }
+
+    ns.iter()
+        .zip(remainders)
+        .map(|(&n, r)| {
+            // square 6 times, as 2^6 = 64 bounds all exponents in n
+            let n128 = n as u128;
+            let mut z = r.to_u64().unwrap() as u128;
+            for _ in 0..6 {
+                z = z * z % n128;
+            }
+            gcd(n, z as u64)
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use sieve::primes_up_to;
+
+    use super::*;
+
+    fn smooth_part(n: u64, primes: &[u32]) -> u64 {
+        let mut part = 1;
+        let mut rest = n;
+        for &p in primes {
+            while rest.is_multiple_of(p as u64) {
+                rest /= p as u64;
+                part *= p as u64;
+            }
+        }
+        part
+    }
+
+    #[test]
+    fn test_smooth_parts() {
+        let primes = primes_up_to(1000);
+        let ns = (1..2000_u64)
+            .map(|i| i.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1 << 63)
+            .chain(1..1000)
+            .collect::<Vec<_>>();
+        let expected = ns.iter().map(|&n| smooth_part(n, &primes)).collect::<Vec<_>>();
+        assert_eq!(expected, smooth_parts(&ns, &primes));
+    }
+
+    #[test]
+    fn test_high_powers() {
+        assert_eq!(vec![1 << 63, 3_u64.pow(40)], smooth_parts(&[1 << 63, 3_u64.pow(40)], &[2, 3]));
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        assert!(smooth_parts(&[], &[2, 3]).is_empty());
+        assert_eq!(vec![1, 1], smooth_parts(&[6, 1], &[]));
+        assert_eq!(vec![1000003], smooth_parts(&[1000003], &[1000003]));
+    }
+}
@@ -0,0 +1,283 @@
+
+extern crate num;
+extern crate num_bigint;
+
+use std::cmp::min;
+
+use self::num::{Integer, One, ToPrimitive, Zero};
+use self::num_bigint::BigUint;
+
+use factor::{factor, RHO_BATCH_SIZE};
+use factorization::Factorization;
+use primality::bpsw_is_prime_big;
+use sieve::prime_table;
+
+
+const TRIAL_FACTOR_LIMIT: u32 = 100;
+
+
+/// Factors an arbitrary precision integer into its prime factors.
+///
+/// Whenever an unfactored part fits into 128 bits it is passed on
+/// to `factor`, otherwise Pollard's rho algorithm is used.
+/// This is practical as long as all prime factors except the largest
+/// have at most about 50 bits.
+///
+pub fn factor_big(n: &BigUint) -> Factorization<BigUint> {
+    assert!(!n.is_zero());
+
+    let mut factorization = Factorization::new();
+
+    // remove small factors
+    let mut rest = n.clone();
+    for &p in prime_table(TRIAL_FACTOR_LIMIT - 1).iter() {
+        let d = BigUint::from(p);
+        let mut exponent = 0;
+        loop {
+            let (q, r) = rest.div_rem(&d);
+            if !r.is_zero() {
+                break;
+            }
+            rest = q;
+            exponent += 1;
+        }
+        factorization.insert(d, exponent);
+    }
+
+    let mut unfactored = vec![rest];
+    while let Some(u) = unfactored.pop() {
+        if let Some(u) = to_u128(&u) {
+            if u > 1 {
+                for (p, e) in factor(u) {
+                    factorization.insert(from_u128(p), e);
+                }
+            }
+        } else if bpsw_is_prime_big(&u) {
+            factorization.insert(u, 1);
+        } else {
+            let f = find_large_factor_big(&u);
+            unfactored.push(&u / &f);
+            unfactored.push(f);
+        }
+    }
+    factorization
+}
+
+
+/// Finds a factor of a big integer using Pollard's rho algorithm
+/// with Brent's improvements.
+///
+/// This works the same as for machine words.
+///
+/// n must be composite.
+/// The returned factor may be composite.
+///
+fn find_large_factor_big(n: &BigUint) -> BigUint {
+
+    /// Generates a pseudo-random sequence of numbers below m.
+    ///
+    fn next_random(m: &BigUint, c: u32, x: &BigUint) -> BigUint {
+        (x * x + BigUint::from(c)) % m
+    }
+
+    fn abs_diff(x: &BigUint, y: &BigUint) -> BigUint {
+        if x >= y {
+            x - y
+        } else {
+            y - x
+        }
+    }
+
+    let mut c = 1_u32;
+    loop {
+        let mut f = BigUint::one();
+
+        let mut x = BigUint::one();
+        let mut y = x.clone();
+        let mut saved_y = y.clone();
+        let mut product = BigUint::one();
+        let mut r = 1_u64;
+        while f.is_one() {
+            x = y.clone();
+            for _ in 0..r {
+                y = next_random(n, c, &y);
+            }
+            let mut k = 0;
+            while k < r && f.is_one() {
+                saved_y = y.clone();
+                let batch_size = min(RHO_BATCH_SIZE, r - k);
+                for _ in 0..batch_size {
+                    y = next_random(n, c, &y);
+                    product = (product * abs_diff(&x, &y)) % n;
+                }
+                f = product.gcd(n);
+                k += batch_size;
+            }
+            r *= 2;
+        }
+
+        if &f == n {
+            // the batch overshot, repeat it step by step
+            loop {
+                saved_y = next_random(n, c, &saved_y);
+                f = abs_diff(&x, &saved_y).gcd(n);
+                if !f.is_one() {
+                    break;
+                }
+            }
+        }
+        if &f != n {
+            return f;
+        }
+
+        // if we find only the trivial factor f == n
+        // we retry with another random sequence
+        c += 1;
+    }
+}
+
+
+/// Converts a big integer to u128 if it fits.
+///
+pub(crate) fn to_u128(n: &BigUint) -> Option<u128> {
+    if n.bits() > 128 {
+        None
+    } else {
+        let lo = (n & BigUint::from(u64::MAX)).to_u64().unwrap();
+        let hi = (n >> 64).to_u64().unwrap();
+        Some(((hi as u128) << 64) | lo as u128)
+    }
+}
+
+
+/// Converts a u128 to a big integer.
+///
+pub(crate) fn from_u128(n: u128) -> BigUint {
+    (BigUint::from((n >> 64) as u64) << 64) | BigUint::from(n as u64)
+}
+
+
+/// Computes the integer square root of a big integer with Newton's method.
+///
+pub(crate) fn sqrt_big(n: &BigUint) -> BigUint {
+    if n.is_zero() {
+        return BigUint::zero();
+    }
+    // start above the square root, the iteration then decreases
+    let mut x = BigUint::one() << n.bits().div_ceil(2);
+    loop {
+        let y = (&x + n / &x) >> 1;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::num_bigint::BigUint;
+
+    use super::*;
+
+    fn big(n: u128) -> BigUint {
+        from_u128(n)
+    }
+
+    #[test]
+    fn test_conversion() {
+        for &n in &[0, 1, 1 << 64, u128::MAX, (1 << 127) - 1] {
+            assert_eq!(Some(n), to_u128(&from_u128(n)));
+        }
+        assert_eq!(None, to_u128(&(big(1) << 128)));
+    }
+
+    #[test]
+    fn test_sqrt_big() {
+        for n in 0..1000_u128 {
+            assert_eq!(big(n.isqrt()), sqrt_big(&big(n)));
+        }
+        assert_eq!(big(u64::MAX as u128), sqrt_big(&big(u128::MAX)));
+        let n = big(1) << 200;
+        assert_eq!(big(1) << 100, sqrt_big(&n));
+        assert_eq!((big(1) << 100) - big(1), sqrt_big(&(n - big(1))));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero() {
+        factor_big(&big(0));
+    }
+
+    #[test]
+    fn test_one() {
+        let expected = BTreeMap::new();
+        let actual = factor_big(&big(1)).into_map();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_small() {
+        let mut expected = BTreeMap::new();
+        expected.insert(big(2), 2);
+        expected.insert(big(3), 1);
+        expected.insert(big(101), 1);
+        let actual = factor_big(&big(1212)).into_map();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_power() {
+        let mut expected = BTreeMap::new();
+        expected.insert(big(3), 100);
+        let actual = factor_big(&num::pow(big(3), 100)).into_map();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_large_prime() {
+        let p = (big(1) << 521) - big(1);
+
+        let mut expected = BTreeMap::new();
+        expected.insert(p.clone(), 1);
+
+        let actual = factor_big(&p).into_map();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_128bit_cofactor() {
+        let p1 = big(97);
+        let p2 = big(4294967291);
+        let p3 = big(18446744073709551557);
+
+        let mut expected = BTreeMap::new();
+        expected.insert(p1.clone(), 2);
+        expected.insert(p2.clone(), 1);
+        expected.insert(p3.clone(), 1);
+
+        let actual = factor_big(&(p1.clone() * p1 * p2 * p3)).into_map();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_rho() {
+        let p1 = big(1000003);
+        let p2 = big(1000033);
+        let p3 = big((1 << 127) - 1);
+
+        let mut expected = BTreeMap::new();
+        expected.insert(p1.clone(), 1);
+        expected.insert(p2.clone(), 1);
+        expected.insert(p3.clone(), 1);
+
+        let actual = factor_big(&(p1 * p2 * p3)).into_map();
+
+        assert_eq!(expected, actual);
+    }
+}
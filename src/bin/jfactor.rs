@@ -0,0 +1,282 @@
+//! Factors integers given on the command line or on standard input.
+//!
+//! ```text
+//! $ jfactor 600851475143
+//! 600851475143: 71 839 1471 6857
+//! $ jfactor --format exp --phi --tau 360
+//! 360: 2^3 3^2 5 phi=96 tau=24
+//! ```
+
+extern crate jfactor;
+extern crate num_bigint;
+
+use std::env;
+use std::fmt::Write;
+use std::io;
+use std::io::BufRead;
+use std::process;
+
+use jfactor::{try_factor, Factorization};
+
+
+const USAGE: &str = "\
+Usage: jfactor [OPTIONS] [N...]
+
+Factors the positive integers N, or those read from standard input
+if none are given.
+
+Options:
+  -f, --format FORMAT  output format: plain (default), exp or json
+      --phi            also print Euler's totient φ(n)
+      --sigma          also print the sum of divisors σ(n)
+      --tau            also print the number of divisors τ(n)
+  -h, --help           print this help
+";
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    /// the prime factors repeated according to their multiplicity
+    Plain,
+    /// the prime factors with their exponents in the form `p^e`
+    Exp,
+    /// a JSON object per line
+    Json,
+}
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Options {
+    format: Format,
+    phi: bool,
+    sigma: bool,
+    tau: bool,
+    help: bool,
+    numbers: Vec<String>,
+}
+
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        format: Format::Plain,
+        phi: false,
+        sigma: false,
+        tau: false,
+        help: false,
+        numbers: Vec::new(),
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-f" | "--format" => {
+                let value = args.next().ok_or_else(|| format!("missing value for {}", arg))?;
+                options.format = parse_format(&value)?;
+            }
+            "--phi" => options.phi = true,
+            "--sigma" => options.sigma = true,
+            "--tau" => options.tau = true,
+            "-h" | "--help" => options.help = true,
+            _ if arg.starts_with("--format=") => {
+                options.format = parse_format(&arg["--format=".len()..])?;
+            }
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(format!("unknown option {}", arg));
+            }
+            _ => options.numbers.push(arg),
+        }
+    }
+    Ok(options)
+}
+
+
+fn parse_format(s: &str) -> Result<Format, String> {
+    match s {
+        "plain" => Ok(Format::Plain),
+        "exp" => Ok(Format::Exp),
+        "json" => Ok(Format::Json),
+        _ => Err(format!("unknown format {}", s)),
+    }
+}
+
+
+fn format_line(n: u128, factorization: &Factorization<u128>, options: &Options) -> String {
+    let mut line = String::new();
+    let phi = if options.phi { Some(factorization.euler_phi()) } else { None };
+    let sigma = if options.sigma { Some(factorization.sigma_k_big(1)) } else { None };
+    let tau = if options.tau { factorization.num_divisors() } else { None };
+    match options.format {
+        Format::Plain | Format::Exp => {
+            write!(line, "{}:", n).unwrap();
+            for (&p, &e) in factorization.iter() {
+                if options.format == Format::Plain {
+                    for _ in 0..e {
+                        write!(line, " {}", p).unwrap();
+                    }
+                } else if e == 1 {
+                    write!(line, " {}", p).unwrap();
+                } else {
+                    write!(line, " {}^{}", p, e).unwrap();
+                }
+            }
+            if let Some(phi) = phi {
+                write!(line, " phi={}", phi).unwrap();
+            }
+            if let Some(sigma) = sigma {
+                write!(line, " sigma={}", sigma).unwrap();
+            }
+            if let Some(tau) = tau {
+                write!(line, " tau={}", tau).unwrap();
+            }
+        }
+        Format::Json => {
+            write!(line, "{{\"n\":{},\"factors\":{{", n).unwrap();
+            let mut first = true;
+            for (&p, &e) in factorization.iter() {
+                if !first {
+                    line.push(',');
+                }
+                first = false;
+                write!(line, "\"{}\":{}", p, e).unwrap();
+            }
+            line.push('}');
+            if let Some(phi) = phi {
+                write!(line, ",\"phi\":{}", phi).unwrap();
+            }
+            if let Some(sigma) = sigma {
+                write!(line, ",\"sigma\":{}", sigma).unwrap();
+            }
+            if let Some(tau) = tau {
+                write!(line, ",\"tau\":{}", tau).unwrap();
+            }
+            line.push('}');
+        }
+    }
+    line
+}
+
+
+/// Factors the integer in s and prints it, returns false on failure.
+///
+fn process_line(s: &str, options: &Options) -> bool {
+    let n = match s.parse::<u128>() {
+        Ok(n) => n,
+        Err(_) => {
+            eprintln!("jfactor: {}: not a valid positive integer", s);
+            return false;
+        }
+    };
+    match try_factor(n) {
+        Ok(factorization) => {
+            println!("{}", format_line(n, &factorization, options));
+            true
+        }
+        Err(e) => {
+            eprintln!("jfactor: {}: {}", s, e);
+            false
+        }
+    }
+}
+
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprint!("jfactor: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    if options.help {
+        print!("{}", USAGE);
+        return;
+    }
+
+    let mut ok = true;
+    if options.numbers.is_empty() {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    eprintln!("jfactor: {}", e);
+                    process::exit(1);
+                }
+            };
+            for s in line.split_whitespace() {
+                ok &= process_line(s, &options);
+            }
+        }
+    } else {
+        for s in &options.numbers {
+            ok &= process_line(s, &options);
+        }
+    }
+    if !ok {
+        process::exit(1);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    use jfactor::factor;
+
+    fn args(s: &str) -> impl Iterator<Item = String> + '_ {
+        s.split_whitespace().map(String::from)
+    }
+
+    fn options(s: &str) -> Options {
+        parse_args(args(s)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let o = options("-f exp --tau 12 34");
+        assert_eq!(Format::Exp, o.format);
+        assert!(o.tau && !o.phi && !o.sigma);
+        assert_eq!(vec!["12", "34"], o.numbers);
+        assert_eq!(Format::Json, options("--format=json").format);
+        assert!(parse_args(args("--format")).is_err());
+        assert!(parse_args(args("-f xml")).is_err());
+        assert!(parse_args(args("--foo")).is_err());
+    }
+
+    #[test]
+    fn test_format_plain() {
+        let o = options("");
+        assert_eq!("360: 2 2 2 3 3 5", format_line(360, &factor(360), &o));
+        assert_eq!("1:", format_line(1, &factor(1), &o));
+    }
+
+    #[test]
+    fn test_format_exp() {
+        let o = options("-f exp --phi --sigma --tau");
+        assert_eq!(
+            "360: 2^3 3^2 5 phi=96 sigma=1170 tau=24",
+            format_line(360, &factor(360), &o)
+        );
+    }
+
+    #[test]
+    fn test_format_json() {
+        let o = options("-f json --phi --sigma --tau");
+        assert_eq!(
+            r#"{"n":360,"factors":{"2":3,"3":2,"5":1},"phi":96,"sigma":1170,"tau":24}"#,
+            format_line(360, &factor(360), &o)
+        );
+        assert_eq!(r#"{"n":1,"factors":{}}"#, format_line(1, &factor(1), &options("-f json")));
+    }
+
+    #[test]
+    fn test_format_sigma_large() {
+        // σ(2^126 · 3) does not fit into 128 bits
+        let n = (1_u128 << 126) * 3;
+        let one = BigUint::from(1_u32);
+        let expected = ((&one << 127) - &one) * BigUint::from(4_u32);
+        let line = format_line(n, &factor(n), &options("-f exp --sigma"));
+        assert_eq!(format!("{}: 2^126 3 sigma={}", n, expected), line);
+    }
+}
@@ -0,0 +1,173 @@
+
+extern crate num;
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+use self::num::{Integer, PrimInt};
+
+use factor::{FactorConfig, FactorError};
+use factorization::Factorization;
+
+
+/// A factorer which remembers the factorizations it computed.
+///
+/// This is useful for applications which factor the same integers
+/// again and again. At most `capacity` factorizations are kept,
+/// when it is full the least recently used one is dropped.
+///
+/// ```
+/// use jfactor::CachedFactorer;
+///
+/// let mut factorer = CachedFactorer::new(100);
+/// let n = 1000003_u64 * 4294967291;
+/// assert_eq!(factorer.factor(n), factorer.factor(n));
+/// assert_eq!(1, factorer.len());
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct CachedFactorer<T: Ord> {
+    config: FactorConfig,
+    capacity: usize,
+    /// the factorizations with the time of their last use
+    cache: HashMap<T, (Factorization<T>, u64)>,
+    /// the cached integers by the time of their last use
+    uses: BTreeMap<u64, T>,
+    time: u64,
+}
+
+impl<T: PrimInt + Integer + Hash> CachedFactorer<T> {
+    /// Creates a factorer with the default configuration which keeps
+    /// up to capacity factorizations.
+    ///
+    pub fn new(capacity: usize) -> CachedFactorer<T> {
+        CachedFactorer::with_config(FactorConfig::default(), capacity)
+    }
+
+    /// Creates a factorer with the given configuration which keeps
+    /// up to capacity factorizations.
+    ///
+    pub fn with_config(config: FactorConfig, capacity: usize) -> CachedFactorer<T> {
+        CachedFactorer {
+            config,
+            capacity,
+            cache: HashMap::new(),
+            uses: BTreeMap::new(),
+            time: 0,
+        }
+    }
+
+    /// Returns the maximum number of factorizations kept.
+    ///
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of factorizations currently kept.
+    ///
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Returns true if no factorizations are kept.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Drops all kept factorizations.
+    ///
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.uses.clear();
+    }
+
+    /// Factors an integer into its prime factors,
+    /// or returns the kept factorization.
+    ///
+    /// n must be positive, see `try_factor` for a version which does not panic.
+    ///
+    pub fn factor(&mut self, n: T) -> Factorization<T> {
+        match self.try_factor(n) {
+            Ok(factorization) => factorization,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Factors an integer into its prime factors,
+    /// or returns the kept factorization, returning an error on failure.
+    ///
+    /// Failures are not kept.
+    ///
+    pub fn try_factor(&mut self, n: T) -> Result<Factorization<T>, FactorError> {
+        self.time += 1;
+        let time = self.time;
+        if let Some(&mut (ref factorization, ref mut last_use)) = self.cache.get_mut(&n) {
+            self.uses.remove(last_use);
+            self.uses.insert(time, n);
+            *last_use = time;
+            return Ok(factorization.clone());
+        }
+
+        let factorization = self.config.try_factor(n)?;
+        if self.capacity > 0 {
+            if self.cache.len() >= self.capacity {
+                let oldest = *self.uses.keys().next().unwrap();
+                let m = self.uses.remove(&oldest).unwrap();
+                self.cache.remove(&m);
+            }
+            self.cache.insert(n, (factorization.clone(), time));
+            self.uses.insert(time, n);
+        }
+        Ok(factorization)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use factor::factor;
+
+    use super::*;
+
+    #[test]
+    fn test_factor() {
+        let mut factorer = CachedFactorer::new(10);
+        for n in 1..100_u32 {
+            assert_eq!(factor(n), factorer.factor(n));
+            assert_eq!(factor(n), factorer.factor(n));
+        }
+        assert_eq!(10, factorer.len());
+        factorer.clear();
+        assert!(factorer.is_empty());
+    }
+
+    #[test]
+    fn test_least_recently_used() {
+        let mut factorer = CachedFactorer::new(2);
+        factorer.factor(6_u64);
+        factorer.factor(10);
+        factorer.factor(6);
+        factorer.factor(15);
+        // 10 was used least recently
+        assert!(factorer.cache.contains_key(&6));
+        assert!(!factorer.cache.contains_key(&10));
+        assert!(factorer.cache.contains_key(&15));
+        assert_eq!(2, factorer.uses.len());
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut factorer = CachedFactorer::new(2);
+        assert_eq!(Err(FactorError::Zero), factorer.try_factor(0_i32));
+        assert_eq!(Err(FactorError::Negative), factorer.try_factor(-4));
+        assert!(factorer.is_empty());
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut factorer = CachedFactorer::new(0);
+        assert_eq!(factor(12_u32), factorer.factor(12));
+        assert!(factorer.is_empty());
+    }
+}
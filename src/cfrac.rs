@@ -0,0 +1,183 @@
+
+use arith::{Modulus, Word};
+use relations::{factor_base, find_factor, Relation, EXTRA_RELATIONS};
+
+
+/// The size of the factor base for numbers up to the given number of bits.
+const PARAMETERS: [(u32, usize); 9] = [
+    (40, 40),
+    (50, 60),
+    (60, 100),
+    (70, 150),
+    (80, 250),
+    (90, 400),
+    (100, 600),
+    (110, 900),
+    (120, 1300),
+];
+
+
+/// Finds a factor using the continued fraction method CFRAC
+/// of Morrison and Brillhart.
+///
+/// The convergents A / B of the continued fraction of √n satisfy
+/// A^2 - n B^2 = ±Q with Q < 2 √n, so A^2 = ±Q mod n for small Q.
+/// The Q which factor over a base of small primes are combined
+/// into a congruence of squares X^2 = Y^2 mod n, in the same way
+/// as for the quadratic sieve.
+/// The values of Q are about as large as those of the quadratic sieve,
+/// but they are factored by trial division instead of sieving,
+/// so this is slower than the quadratic sieve for large n.
+/// It takes about a second for a balanced semiprime of 110 bits.
+///
+/// n should be an odd composite which is not a prime power.
+/// Returns None if no nontrivial factor was found,
+/// in particular if n is less than 4 or prime, or at least 2^120.
+/// This may also happen if the period of the continued fraction
+/// is too short, as for n = a^2 + 1.
+/// The returned factor may be composite.
+///
+/// ```
+/// use jfactor::cfrac;
+///
+/// let n = 1000003 * 1000033;
+/// let f = cfrac(n).unwrap();
+/// assert!(f == 1000003 || f == 1000033);
+/// ```
+///
+pub fn cfrac(n: u128) -> Option<u128> {
+    if n < 4 || n >> 120 != 0 {
+        return None;
+    }
+    if n & 1 == 0 {
+        return Some(2);
+    }
+    let s = n.isqrt();
+    if s * s == n {
+        return Some(s);
+    }
+
+    let bits = 128 - n.leading_zeros();
+    let &(_, fb_size) = PARAMETERS
+        .iter()
+        .find(|&&(max_bits, _)| bits <= max_bits)
+        .unwrap();
+    let fb = match factor_base(n, fb_size) {
+        Ok(fb) => fb,
+        Err(p) => return if n != p as u128 { Some(p as u128) } else { None },
+    };
+
+    // the columns are the sign, the prime 2 and the factor base primes
+    let mut column_primes = vec![0, 2];
+    column_primes.extend(&fb);
+    let columns = column_primes.len();
+    let mut relations = Vec::new();
+
+    // the complete quotients are (√n + m) / q with partial quotient a,
+    // the numerators of the convergents are kept modulo n
+    let modulus = <u128 as Word>::Modulus::new(n);
+    let mut m = 0;
+    let mut q = 1;
+    let mut a = s;
+    let mut numerator0 = modulus.one();
+    let mut numerator1 = modulus.to_residue(s);
+    let mut k = 0;
+    while relations.len() < columns + EXTRA_RELATIONS {
+        m = a * q - m;
+        q = (n - m * m) / q;
+        a = (s + m) / q;
+        k += 1;
+
+        // the previous numerator A satisfies A^2 = (-1)^k q mod n
+        if let Some(mut factors) = factor_over_base(q as u64, &fb) {
+            if k % 2 == 1 {
+                factors.insert(0, (0, 1));
+            }
+            relations.push(Relation {
+                square_root: modulus.value(numerator1),
+                q: 1,
+                factors,
+            });
+        }
+        if q == 1 {
+            // the end of the period, after which the relations repeat
+            break;
+        }
+
+        let numerator2 = modulus.add(modulus.mul(modulus.to_residue(a), numerator1), numerator0);
+        numerator0 = numerator1;
+        numerator1 = numerator2;
+    }
+
+    find_factor(n, &relations, &column_primes)
+}
+
+
+/// Factors q over 2 and the factor base by trial division,
+/// returns the columns of the prime factors with their exponents
+/// or None if q does not factor completely.
+///
+fn factor_over_base(q: u64, fb: &[u64]) -> Option<Vec<(usize, u32)>> {
+    let mut factors = Vec::new();
+    let twos = q.trailing_zeros();
+    if twos > 0 {
+        factors.push((1, twos));
+    }
+    let mut rest = q >> twos;
+    for (j, &p) in fb.iter().enumerate() {
+        if rest == 1 {
+            break;
+        }
+        if rest.is_multiple_of(p) {
+            let mut exponent = 0;
+            while rest.is_multiple_of(p) {
+                rest /= p;
+                exponent += 1;
+            }
+            factors.push((j + 2, exponent));
+        }
+    }
+    if rest == 1 {
+        Some(factors)
+    } else {
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_factor(n: u128) {
+        let f = cfrac(n).unwrap();
+        assert!(f != 1 && f != n);
+        assert_eq!(0, n % f);
+    }
+
+    #[test]
+    fn test_factor_over_base() {
+        let fb = [7, 11, 13];
+        assert_eq!(Some(vec![(1, 3), (2, 1), (4, 2)]), factor_over_base(8 * 7 * 169, &fb));
+        assert_eq!(Some(vec![]), factor_over_base(1, &fb));
+        assert_eq!(None, factor_over_base(2 * 17, &fb));
+    }
+
+    #[test]
+    fn test_small() {
+        assert_eq!(None, cfrac(3));
+        assert_eq!(None, cfrac(1000003));
+        assert_eq!(Some(2), cfrac(1000));
+        assert_eq!(Some(1000003), cfrac(1000003 * 1000003));
+        assert_eq!(Some(3), cfrac(3 * 1000003));
+        check_factor(1000003 * 1000033);
+        check_factor(101 * 103 * 107);
+    }
+
+    #[test]
+    fn test_semiprimes() {
+        // 60 and 80 bits
+        check_factor(1073741827 * 1073741831);
+        check_factor(1099511627791 * 1099511627803);
+    }
+}
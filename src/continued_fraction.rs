@@ -0,0 +1,241 @@
+
+extern crate num;
+extern crate num_bigint;
+
+use std::iter;
+
+use self::num::{One, Zero};
+use self::num_bigint::BigUint;
+
+
+/// Returns the continued fraction expansion of √n as (a0, period),
+/// where a0 is the integer part and the partial quotients after it
+/// repeat the period forever.
+///
+/// The period of an integer which is not a square is a palindrome
+/// followed by 2 a0, its length is O(√n log n).
+/// For a perfect square the period is empty.
+///
+/// ```
+/// use jfactor::sqrt_continued_fraction;
+///
+/// // √14 = [3; 1, 2, 1, 6, 1, 2, 1, 6, ...]
+/// assert_eq!((3, vec![1, 2, 1, 6]), sqrt_continued_fraction(14));
+/// assert_eq!((7, vec![]), sqrt_continued_fraction(49));
+/// ```
+///
+pub fn sqrt_continued_fraction(n: u64) -> (u64, Vec<u64>) {
+    let a0 = n.isqrt();
+    if a0 * a0 == n {
+        return (a0, Vec::new());
+    }
+    let mut period = Vec::new();
+    for a in PartialQuotients::new(n) {
+        period.push(a);
+        if a == 2 * a0 {
+            break;
+        }
+    }
+    (a0, period)
+}
+
+
+/// Iterates over the partial quotients of √d after the integer part,
+/// for d not a perfect square.
+///
+/// The complete quotients are (√d + m) / q with integers m and q,
+/// which stay below 2 √d, and a is their integer part.
+///
+struct PartialQuotients {
+    d: u64,
+    a0: u64,
+    m: u64,
+    q: u64,
+    a: u64,
+}
+
+impl PartialQuotients {
+    fn new(d: u64) -> PartialQuotients {
+        let a0 = d.isqrt();
+        PartialQuotients { d, a0, m: 0, q: 1, a: a0 }
+    }
+}
+
+impl Iterator for PartialQuotients {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.m = self.a * self.q - self.m;
+        self.q = (self.d - self.m * self.m) / self.q;
+        self.a = (self.a0 + self.m) / self.q;
+        Some(self.a)
+    }
+}
+
+
+/// Returns the fundamental solution (x, y) of Pell's equation
+/// x^2 - d y^2 = 1, the solution in positive integers with the smallest x,
+/// or None if d is a perfect square.
+///
+/// The solution is a convergent x / y of the continued fraction of √d,
+/// the last one in the first period if the period length is even
+/// and in the second period otherwise.
+/// It can have hundreds of digits even for small d,
+/// which is why it is returned as big integers.
+///
+/// ```
+/// extern crate jfactor;
+/// extern crate num_bigint;
+///
+/// use num_bigint::BigUint;
+///
+/// use jfactor::solve_pell;
+///
+/// # fn main() {
+/// let big = |n: u64| BigUint::from(n);
+/// assert_eq!(Some((big(3), big(2))), solve_pell(2));
+/// assert_eq!(Some((big(1766319049), big(226153980))), solve_pell(61));
+/// assert_eq!(None, solve_pell(49));
+/// # }
+/// ```
+///
+pub fn solve_pell(d: u64) -> Option<(BigUint, BigUint)> {
+    let a0 = d.isqrt();
+    if a0 * a0 == d {
+        return None;
+    }
+
+    let (mut p0, mut p1) = (BigUint::one(), BigUint::from(a0));
+    let (mut q0, mut q1) = (BigUint::zero(), BigUint::one());
+    for (period, a) in (1..).zip(PartialQuotients::new(d)) {
+        // the period ends with the first a = 2 a0
+        if a == 2 * a0 && period % 2 == 0 {
+            return Some((p1, q1));
+        }
+        let p2 = BigUint::from(a) * &p1 + p0;
+        let q2 = BigUint::from(a) * &q1 + q0;
+        p0 = p1;
+        p1 = p2;
+        q0 = q1;
+        q1 = q2;
+    }
+    unreachable!()
+}
+
+
+/// Returns an iterator over the solutions (x, y) of Pell's equation
+/// x^2 - d y^2 = 1 in positive integers, in increasing order.
+///
+/// These are given by x + y √d = (x1 + y1 √d)^k for k = 1, 2, ...,
+/// where (x1, y1) is the fundamental solution from `solve_pell`.
+/// The iterator is empty if d is a perfect square.
+///
+/// ```
+/// extern crate jfactor;
+/// extern crate num_bigint;
+///
+/// use num_bigint::BigUint;
+///
+/// use jfactor::pell_solutions;
+///
+/// # fn main() {
+/// let big = |n: u64| BigUint::from(n);
+/// let solutions = pell_solutions(2).take(3).collect::<Vec<_>>();
+/// assert_eq!(vec![(big(3), big(2)), (big(17), big(12)), (big(99), big(70))], solutions);
+/// # }
+/// ```
+///
+pub fn pell_solutions(d: u64) -> impl Iterator<Item = (BigUint, BigUint)> {
+    let fundamental = solve_pell(d);
+    let d = BigUint::from(d);
+    iter::successors(fundamental.clone(), move |(x, y)| {
+        let (x1, y1) = fundamental.as_ref().unwrap();
+        Some((x1 * x + &d * y1 * y, x1 * y + y1 * x))
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(n: u64) -> BigUint {
+        BigUint::from(n)
+    }
+
+    fn is_solution(d: u64, x: &BigUint, y: &BigUint) -> bool {
+        x * x == big(d) * y * y + big(1)
+    }
+
+    #[test]
+    fn test_sqrt_continued_fraction() {
+        assert_eq!((0, vec![]), sqrt_continued_fraction(0));
+        assert_eq!((1, vec![2]), sqrt_continued_fraction(2));
+        assert_eq!((7, vec![1, 4, 3, 1, 2, 2, 1, 3, 4, 1, 14]), sqrt_continued_fraction(61));
+        for n in 0..2000_u64 {
+            let (a0, period) = sqrt_continued_fraction(n);
+            assert_eq!(n.isqrt(), a0);
+            if let Some((&last, rest)) = period.split_last() {
+                assert_eq!(2 * a0, last);
+                assert!(rest.iter().eq(rest.iter().rev()));
+                assert!(rest.iter().all(|&a| a < 2 * a0));
+            } else {
+                assert_eq!(a0 * a0, n);
+            }
+        }
+        // (2^32 - 1)^2 + 1 has period length 1
+        assert_eq!((4294967295, vec![8589934590]), sqrt_continued_fraction(u64::MAX - 8589934589));
+        assert_eq!((4294967295, vec![1, 4294967294, 1, 8589934590]), sqrt_continued_fraction(u64::MAX - 1));
+    }
+
+    #[test]
+    fn test_solve_pell() {
+        for d in 0..1000_u64 {
+            let s = d.isqrt();
+            match solve_pell(d) {
+                Some((x, y)) => {
+                    assert!(s * s != d);
+                    assert!(is_solution(d, &x, &y));
+                    // no smaller solution
+                    if let Ok(y) = y.to_string().parse::<u64>() {
+                        for y2 in 1..y.min(10000) {
+                            let x2 = (d as u128 * y2 as u128 * y2 as u128 + 1).isqrt();
+                            assert!(x2 * x2 != d as u128 * y2 as u128 * y2 as u128 + 1);
+                        }
+                    }
+                }
+                None => assert_eq!(s * s, d),
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_pell_large() {
+        // the fundamental solution for d = 991 has 30 digits
+        let (x, y) = solve_pell(991).unwrap();
+        assert_eq!("379516400906811930638014896080", x.to_string());
+        assert_eq!("12055735790331359447442538767", y.to_string());
+        let d = u64::MAX;
+        let (x, y) = solve_pell(d).unwrap();
+        assert!(is_solution(d, &x, &y));
+        let d = u64::MAX - 1;
+        let (x, y) = solve_pell(d).unwrap();
+        assert!(is_solution(d, &x, &y));
+    }
+
+    #[test]
+    fn test_pell_solutions() {
+        for d in 2..100_u64 {
+            let solutions = pell_solutions(d).take(5).collect::<Vec<_>>();
+            if d.isqrt() * d.isqrt() == d {
+                assert!(solutions.is_empty());
+            } else {
+                assert_eq!(5, solutions.len());
+                for (x, y) in &solutions {
+                    assert!(is_solution(d, x, y));
+                }
+                assert!(solutions.windows(2).all(|w| w[0].0 < w[1].0));
+            }
+        }
+    }
+}
@@ -1 +1,885 @@
 
+extern crate num;
+extern crate num_bigint;
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeSet, BinaryHeap};
+
+use self::num::{Integer, NumCast, PrimInt};
+use self::num_bigint::BigUint;
+
+use big::from_u128;
+use factor::factor;
+use factorization::Factorization;
+
+
+/// Returns the divisors of n in ascending order.
+///
+/// The divisors are generated from the factorization of n,
+/// which is much faster than trial division up to the square root
+/// for large n.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::divisors;
+///
+/// assert_eq!(vec![1, 2, 3, 4, 6, 12], divisors(12_u64));
+/// assert_eq!(vec![1], divisors(1_u64));
+/// ```
+///
+pub fn divisors<T: PrimInt + Integer>(n: T) -> Vec<T> {
+    divisors_of(&factor(n))
+}
+
+
+/// Returns the number of divisors τ(n) of n.
+///
+/// This is the product of e + 1 over the prime powers p^e in n,
+/// without generating the divisors.
+/// It fits into u64 for all 128-bit integers.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::num_divisors;
+///
+/// assert_eq!(24, num_divisors(360_u32));
+/// assert_eq!(1, num_divisors(1_u32));
+/// ```
+///
+pub fn num_divisors<T: PrimInt + Integer>(n: T) -> u64 {
+    factor(n).num_divisors().unwrap()
+}
+
+
+/// Returns the divisor summatory function D(x),
+/// the sum of τ(n) for n from 1 to x.
+///
+/// This counts the lattice points under the hyperbola d e = x
+/// with Dirichlet's hyperbola method,
+/// D(x) = 2 Σ x / d - s^2 for d from 1 to s = √x,
+/// so it needs time O(√x) and no memory.
+///
+/// ```
+/// use jfactor::divisor_count_sum;
+///
+/// assert_eq!(27, divisor_count_sum(10));
+/// assert_eq!(20877697634, divisor_count_sum(1_000_000_000));
+/// ```
+///
+pub fn divisor_count_sum(x: u64) -> u128 {
+    let s = x.isqrt();
+    let sum = (1..=s).map(|d| (x / d) as u128).sum::<u128>();
+    2 * sum - s as u128 * s as u128
+}
+
+
+/// Returns the sum of divisors σ(n) of n,
+/// or None if it does not fit into u128.
+///
+/// The sum is computed as the product of 1 + p + ... + p^e
+/// over the prime powers p^e in n, in u128 with overflow checks,
+/// so it is exact for all n up to 64 bits
+/// and for most larger ones.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::sum_of_divisors;
+///
+/// assert_eq!(Some(1170), sum_of_divisors(360_u32));
+/// assert_eq!(None, sum_of_divisors(u128::MAX - 1));
+/// ```
+///
+pub fn sum_of_divisors<T: PrimInt + Integer>(n: T) -> Option<u128> {
+    sigma_k(n, 1)
+}
+
+
+/// Returns the aliquot sum s(n) = σ(n) - n of n,
+/// the sum of its proper divisors,
+/// or None if σ(n) does not fit into u128.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::aliquot_sum;
+///
+/// assert_eq!(Some(16), aliquot_sum(12_u32));
+/// assert_eq!(Some(28), aliquot_sum(28_u32));
+/// assert_eq!(Some(0), aliquot_sum(1_u32));
+/// ```
+///
+pub fn aliquot_sum<T: PrimInt + Integer>(n: T) -> Option<u128> {
+    Some(sum_of_divisors(n)? - n.to_u128().unwrap())
+}
+
+
+/// The classification of positive integers by comparing
+/// the sum of their proper divisors with the integer itself.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Abundance {
+    /// The proper divisors sum to less than n.
+    Deficient,
+    /// The proper divisors sum to n.
+    Perfect,
+    /// The proper divisors sum to more than n.
+    Abundant,
+}
+
+
+/// Classifies n as deficient, perfect or abundant.
+///
+/// This compares σ(n) with 2 n, falling back to big integers
+/// if σ(n) does not fit into u128.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::{classify, Abundance};
+///
+/// assert_eq!(Abundance::Deficient, classify(8_u32));
+/// assert_eq!(Abundance::Perfect, classify(28_u32));
+/// assert_eq!(Abundance::Abundant, classify(12_u32));
+/// ```
+///
+pub fn classify<T: PrimInt + Integer>(n: T) -> Abundance {
+    let n_u128 = n.to_u128().unwrap();
+    let ordering = match sum_of_divisors(n) {
+        Some(sigma) if n_u128 >> 127 == 0 => sigma.cmp(&(2 * n_u128)),
+        _ => sigma_k_big(n, 1).cmp(&(from_u128(n_u128) << 1)),
+    };
+    match ordering {
+        Ordering::Less => Abundance::Deficient,
+        Ordering::Equal => Abundance::Perfect,
+        Ordering::Greater => Abundance::Abundant,
+    }
+}
+
+
+/// Returns true if n is perfect, that is the sum of its proper divisors.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::is_perfect;
+///
+/// let perfect = (1..10000_u32).filter(|&n| is_perfect(n)).collect::<Vec<_>>();
+/// assert_eq!(vec![6, 28, 496, 8128], perfect);
+/// ```
+///
+pub fn is_perfect<T: PrimInt + Integer>(n: T) -> bool {
+    classify(n) == Abundance::Perfect
+}
+
+
+/// Returns the amicable partner m of n, if there is one.
+///
+/// n and m are an amicable pair if they are different
+/// and each is the sum of the proper divisors of the other.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::amicable_partner;
+///
+/// assert_eq!(Some(284), amicable_partner(220_u64));
+/// assert_eq!(Some(220), amicable_partner(284_u64));
+/// assert_eq!(None, amicable_partner(28_u64));
+/// ```
+///
+pub fn amicable_partner<T: PrimInt + Integer>(n: T) -> Option<T> {
+    let m: T = NumCast::from(aliquot_sum(n)?)?;
+    if m != n && m > T::zero() && aliquot_sum(m)? == n.to_u128().unwrap() {
+        Some(m)
+    } else {
+        None
+    }
+}
+
+
+/// Returns true if a and b are an amicable pair.
+///
+/// a and b must be positive.
+///
+/// ```
+/// use jfactor::is_amicable_pair;
+///
+/// assert!(is_amicable_pair(1184_u32, 1210));
+/// assert!(!is_amicable_pair(6_u32, 6));
+/// ```
+///
+pub fn is_amicable_pair<T: PrimInt + Integer>(a: T, b: T) -> bool {
+    a != b && aliquot_sum(a) == b.to_u128() && aliquot_sum(b) == a.to_u128()
+}
+
+
+/// Returns the sociable cycle of the aliquot map s(n) = σ(n) - n
+/// through n, if it has at most max_len elements.
+///
+/// The cycle starts with n, perfect numbers give a cycle of length 1
+/// and amicable numbers one of length 2.
+/// Returns None if the sequence does not return to n within max_len steps,
+/// as soon as it reaches 1 or repeats a value other than n,
+/// or if it leaves the range of T.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::sociable_cycle;
+///
+/// assert_eq!(Some(vec![12496, 14288, 15472, 14536, 14264]), sociable_cycle(12496_u64, 10));
+/// assert_eq!(None, sociable_cycle(12496_u64, 4));
+/// assert_eq!(None, sociable_cycle(12_u64, 10));
+/// ```
+///
+pub fn sociable_cycle<T: PrimInt + Integer>(n: T, max_len: usize) -> Option<Vec<T>> {
+    let mut cycle = vec![n];
+    let mut seen = BTreeSet::new();
+    seen.insert(n);
+    let mut x = n;
+    while cycle.len() <= max_len {
+        x = NumCast::from(aliquot_sum(x)?)?;
+        if x == n {
+            return Some(cycle);
+        }
+        // a repeated value other than n starts a cycle without n
+        if x == T::zero() || !seen.insert(x) {
+            return None;
+        }
+        cycle.push(x);
+    }
+    None
+}
+
+
+/// Returns the sum of the k-th powers of the divisors σ_k(n) of n,
+/// or None if it does not fit into u128.
+///
+/// σ_0 is the number of divisors and σ_1 the sum of divisors.
+/// See `sigma_k_big` for a version which does not overflow.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::sigma_k;
+///
+/// assert_eq!(Some(6), sigma_k(12_u32, 0));
+/// assert_eq!(Some(28), sigma_k(12_u32, 1));
+/// assert_eq!(Some(210), sigma_k(12_u32, 2));
+/// ```
+///
+pub fn sigma_k<T: PrimInt + Integer>(n: T, k: u32) -> Option<u128> {
+    let mut sum = 1_u128;
+    for (p, &e) in factor(n).iter() {
+        let pk = p.to_u128().unwrap().checked_pow(k)?;
+        let mut power = 1_u128;
+        let mut term = 1_u128;
+        for _ in 0..e {
+            power = power.checked_mul(pk)?;
+            term = term.checked_add(power)?;
+        }
+        sum = sum.checked_mul(term)?;
+    }
+    Some(sum)
+}
+
+
+/// Returns the sum of the k-th powers of the divisors σ_k(n) of n
+/// as a big integer.
+///
+/// See `Factorization::sigma_k_big` for an already factored n.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::sigma_k_big;
+///
+/// // 1 + 2^128 does not fit into u128
+/// assert_eq!("340282366920938463463374607431768211457", sigma_k_big(2_u32, 128).to_string());
+/// ```
+///
+pub fn sigma_k_big<T: PrimInt + Integer>(n: T, k: u32) -> BigUint {
+    factor(n).sigma_k_big(k)
+}
+
+
+/// Returns the unitary divisors of n in ascending order,
+/// the divisors d with d and n / d coprime.
+///
+/// These are the products of subsets of the prime powers p^e in n.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::unitary_divisors;
+///
+/// assert_eq!(vec![1, 3, 4, 12], unitary_divisors(12_u32));
+/// ```
+///
+pub fn unitary_divisors<T: PrimInt + Integer>(n: T) -> Vec<T> {
+    let mut divisors = vec![T::one()];
+    for (&p, &e) in factor(n).iter() {
+        let power = p.pow(e);
+        for i in 0..divisors.len() {
+            let d = divisors[i] * power;
+            divisors.push(d);
+        }
+    }
+    divisors.sort_unstable();
+    divisors
+}
+
+
+/// Returns the sum of the unitary divisors σ*(n) of n,
+/// or None if it does not fit into u128.
+///
+/// This is the product of 1 + p^e over the prime powers p^e in n.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::unitary_sigma;
+///
+/// assert_eq!(Some(20), unitary_sigma(12_u32));
+/// ```
+///
+pub fn unitary_sigma<T: PrimInt + Integer>(n: T) -> Option<u128> {
+    let mut sum = 1_u128;
+    for (&p, &e) in factor(n).iter() {
+        let power = p.pow(e).to_u128().unwrap();
+        sum = sum.checked_mul(power.checked_add(1)?)?;
+    }
+    Some(sum)
+}
+
+
+/// Returns the bi-unitary divisors of n in ascending order,
+/// the divisors d where 1 is the only common unitary divisor
+/// of d and n / d.
+///
+/// For a prime power p^e these are the p^i with i from 0 to e,
+/// except p^(e / 2) if e is even.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::bi_unitary_divisors;
+///
+/// assert_eq!(vec![1, 3, 4, 12], bi_unitary_divisors(12_u32));
+/// assert_eq!(vec![1, 2, 4, 8], bi_unitary_divisors(8_u32));
+/// ```
+///
+pub fn bi_unitary_divisors<T: PrimInt + Integer>(n: T) -> Vec<T> {
+    let mut divisors = vec![T::one()];
+    for (&p, &e) in factor(n).iter() {
+        let len = divisors.len();
+        for i in bi_unitary_exponents(e).skip(1) {
+            let power = p.pow(i);
+            for j in 0..len {
+                let d = divisors[j] * power;
+                divisors.push(d);
+            }
+        }
+    }
+    divisors.sort_unstable();
+    divisors
+}
+
+
+/// Returns the number of bi-unitary divisors τ**(n) of n.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::bi_unitary_num_divisors;
+///
+/// assert_eq!(4, bi_unitary_num_divisors(12_u32));
+/// ```
+///
+pub fn bi_unitary_num_divisors<T: PrimInt + Integer>(n: T) -> u64 {
+    factor(n)
+        .iter()
+        .map(|(_, &e)| if e % 2 == 0 { e as u64 } else { e as u64 + 1 })
+        .product()
+}
+
+
+/// Returns the sum of the bi-unitary divisors σ**(n) of n,
+/// or None if it does not fit into u128.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::bi_unitary_sigma;
+///
+/// assert_eq!(Some(20), bi_unitary_sigma(12_u32));
+/// assert_eq!(Some(15), bi_unitary_sigma(8_u32));
+/// ```
+///
+pub fn bi_unitary_sigma<T: PrimInt + Integer>(n: T) -> Option<u128> {
+    let mut sum = 1_u128;
+    for (p, &e) in factor(n).iter() {
+        let p = p.to_u128().unwrap();
+        let mut term = 0_u128;
+        for i in bi_unitary_exponents(e) {
+            term = term.checked_add(p.checked_pow(i)?)?;
+        }
+        sum = sum.checked_mul(term)?;
+    }
+    Some(sum)
+}
+
+
+/// Returns the exponents i of the bi-unitary divisors p^i of p^e.
+///
+fn bi_unitary_exponents(e: u32) -> impl Iterator<Item = u32> {
+    (0..=e).filter(move |&i| e % 2 == 1 || i != e / 2)
+}
+
+
+/// Returns the pairs (d, n / d) of complementary divisors of n
+/// with d <= n / d, each exactly once.
+///
+/// The pairs are in no particular order,
+/// as the divisors are not sorted.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::divisor_pairs;
+///
+/// let mut pairs = divisor_pairs(36_u32).collect::<Vec<_>>();
+/// pairs.sort();
+/// assert_eq!(vec![(1, 36), (2, 18), (3, 12), (4, 9), (6, 6)], pairs);
+/// ```
+///
+pub fn divisor_pairs<T: PrimInt + Integer>(n: T) -> impl Iterator<Item = (T, T)> {
+    unsorted_divisors_of(&factor(n))
+        .into_iter()
+        .filter(move |&d| d <= n / d)
+        .map(move |d| (d, n / d))
+}
+
+
+/// Returns the divisors d of n with lo <= d <= hi in ascending order.
+///
+/// This searches the products of prime powers depth first,
+/// cutting off branches where the product exceeds hi
+/// or cannot reach lo anymore with the remaining primes,
+/// so only a fraction of the divisors is generated for narrow ranges.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::divisors_in_range;
+///
+/// assert_eq!(vec![8, 9, 10, 12], divisors_in_range(360_u32, 7, 12));
+/// ```
+///
+pub fn divisors_in_range<T: PrimInt + Integer>(n: T, lo: T, hi: T) -> Vec<T> {
+    fn search<T: PrimInt>(
+        prime_powers: &[(T, u32)],
+        rest: &[T],
+        d: T,
+        lo: T,
+        hi: T,
+        result: &mut Vec<T>,
+    ) {
+        // the largest divisor reachable from here is d * rest[0]
+        if lo > T::zero() && d <= (lo - T::one()) / rest[0] {
+            return;
+        }
+        if prime_powers.is_empty() {
+            if d <= hi {
+                result.push(d);
+            }
+            return;
+        }
+        let (p, e) = prime_powers[0];
+        let mut d = d;
+        for k in 0..=e {
+            search(&prime_powers[1..], &rest[1..], d, lo, hi, result);
+            if k == e || d > hi / p {
+                break;
+            }
+            d = d * p;
+        }
+    }
+
+    let prime_powers = factor(n).into_iter().collect::<Vec<_>>();
+    // rest[i] is the part of n made up of the primes from index i on
+    let mut rest = vec![T::one(); prime_powers.len() + 1];
+    for i in (0..prime_powers.len()).rev() {
+        let (p, e) = prime_powers[i];
+        rest[i] = rest[i + 1] * p.pow(e);
+    }
+    let mut result = Vec::new();
+    if lo <= hi {
+        search(&prime_powers, &rest, T::one(), lo, hi, &mut result);
+    }
+    result.sort_unstable();
+    result
+}
+
+
+/// Returns the k-th smallest divisor of n, counting from 1 for 1,
+/// or None if n has fewer than k divisors.
+///
+/// The divisors are generated lazily in ascending order with a heap,
+/// each from the divisor without its largest prime factor,
+/// and for k beyond half the number of divisors the divisors
+/// are counted from the top instead, as n / d is then the k-th one.
+/// So only about min(k, τ(n) - k) divisors are generated.
+///
+/// n and k must be positive.
+///
+/// ```
+/// use jfactor::kth_divisor;
+///
+/// assert_eq!(Some(1), kth_divisor(360_u32, 1));
+/// assert_eq!(Some(8), kth_divisor(360_u32, 7));
+/// assert_eq!(Some(360), kth_divisor(360_u32, 24));
+/// assert_eq!(None, kth_divisor(360_u32, 25));
+/// ```
+///
+pub fn kth_divisor<T: PrimInt + Integer>(n: T, k: u64) -> Option<T> {
+    assert!(k > 0);
+
+    let factorization = factor(n);
+    let count = factorization.num_divisors().unwrap();
+    if k > count {
+        return None;
+    }
+    if k > count / 2 {
+        return kth_divisor_ascending(&factorization, count + 1 - k).map(|d| n / d);
+    }
+    kth_divisor_ascending(&factorization, k)
+}
+
+/// Returns the k-th smallest divisor of the factored integer.
+///
+fn kth_divisor_ascending<T: PrimInt>(factorization: &Factorization<T>, k: u64) -> Option<T> {
+    let prime_powers = factorization.iter().map(|(&p, &e)| (p, e)).collect::<Vec<_>>();
+
+    // the entries are divisors d with the index i of the largest prime
+    // factor and its exponent, or the number of primes for 1
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((T::one(), prime_powers.len(), 0)));
+    let mut count = 0;
+    while let Some(Reverse((d, i, e))) = heap.pop() {
+        count += 1;
+        if count == k {
+            return Some(d);
+        }
+        if i < prime_powers.len() && e < prime_powers[i].1 {
+            heap.push(Reverse((d * prime_powers[i].0, i, e + 1)));
+        }
+        let first = if i < prime_powers.len() { i + 1 } else { 0 };
+        for (j, &(p, _)) in prime_powers.iter().enumerate().skip(first) {
+            heap.push(Reverse((d * p, j, 1)));
+        }
+    }
+    None
+}
+
+
+/// Returns the divisors of the factored integer in ascending order.
+///
+pub(crate) fn divisors_of<T: PrimInt>(factorization: &Factorization<T>) -> Vec<T> {
+    let mut divisors = unsorted_divisors_of(factorization);
+    divisors.sort_unstable();
+    divisors
+}
+
+
+/// Returns the divisors of the factored integer in no particular order.
+///
+fn unsorted_divisors_of<T: PrimInt>(factorization: &Factorization<T>) -> Vec<T> {
+    let mut divisors = vec![T::one()];
+    for (&p, &e) in factorization.iter() {
+        let len = divisors.len();
+        let mut power = T::one();
+        for _ in 0..e {
+            power = power * p;
+            for i in 0..len {
+                let d = divisors[i] * power;
+                divisors.push(d);
+            }
+        }
+    }
+    divisors
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::num::One;
+
+    use primality::is_prime;
+
+    use super::*;
+
+    #[test]
+    fn test_divisors() {
+        for n in 1..1000_u32 {
+            let expected = (1..=n).filter(|d| n % d == 0).collect::<Vec<_>>();
+            assert_eq!(expected, divisors(n));
+        }
+    }
+
+    #[test]
+    fn test_divisors_large() {
+        let p = 4294967291_u64;
+        assert_eq!(vec![1, p, p * p], divisors(p * p));
+        assert_eq!(64, divisors(1_u64 << 63).len());
+        let d = divisors(18446744073709551557_u128 * 6);
+        assert_eq!(vec![1, 2, 3, 6], d[..4].to_vec());
+        assert_eq!(8, d.len());
+    }
+
+    #[test]
+    fn test_num_divisors() {
+        for n in 1..1000_u32 {
+            assert_eq!(divisors(n).len() as u64, num_divisors(n));
+        }
+        assert_eq!(128, num_divisors(1_u128 << 127));
+        // the product of the first 25 primes has 2^25 divisors
+        let primorial = (1..98_u128).filter(|&p| is_prime(p)).product::<u128>();
+        assert_eq!(1 << 25, num_divisors(primorial));
+    }
+
+    #[test]
+    fn test_divisor_count_sum() {
+        let mut sum = 0;
+        for x in 1..1000_u32 {
+            sum += num_divisors(x) as u128;
+            assert_eq!(sum, divisor_count_sum(x as u64));
+        }
+        assert_eq!(0, divisor_count_sum(0));
+        let expected = [
+            27,
+            482,
+            7069,
+            93668,
+            1166750,
+            13970034,
+            162725364,
+            1857511568,
+            20877697634,
+            231802823220,
+            2548286736297,
+            27785452449086,
+        ];
+        for (k, &sum) in expected.iter().enumerate() {
+            assert_eq!(sum, divisor_count_sum(10_u64.pow(k as u32 + 1)));
+        }
+    }
+
+    #[test]
+    fn test_sum_of_divisors() {
+        for n in 1..1000_u32 {
+            let expected = divisors(n).iter().map(|&d| d as u128).sum::<u128>();
+            assert_eq!(Some(expected), sum_of_divisors(n));
+        }
+        let n = u64::MAX;
+        let expected = divisors(n).iter().map(|&d| d as u128).sum::<u128>();
+        assert_eq!(Some(expected), sum_of_divisors(n));
+        assert_eq!(Some(u128::MAX), sum_of_divisors(1_u128 << 127));
+        assert_eq!(None, sum_of_divisors(3_u128 << 126));
+    }
+
+    #[test]
+    fn test_aliquot_sum() {
+        for n in 1..1000_u32 {
+            let proper = divisors(n).into_iter().filter(|&d| d < n);
+            assert_eq!(Some(proper.map(|d| d as u128).sum()), aliquot_sum(n));
+        }
+        assert_eq!(Some(1), aliquot_sum(18446744073709551557_u64));
+        assert_eq!(Some((1 << 127) - 1), aliquot_sum(1_u128 << 127));
+        assert_eq!(None, aliquot_sum(3_u128 << 126));
+    }
+
+    #[test]
+    fn test_classify() {
+        for n in 1..1000_u32 {
+            let s = aliquot_sum(n).unwrap();
+            let expected = match s.cmp(&(n as u128)) {
+                Ordering::Less => Abundance::Deficient,
+                Ordering::Equal => Abundance::Perfect,
+                Ordering::Greater => Abundance::Abundant,
+            };
+            assert_eq!(expected, classify(n));
+        }
+        assert!(is_perfect(2305843008139952128_u64));
+        assert!(is_perfect(2658455991569831744654692615953842176_u128));
+        assert_eq!(Abundance::Deficient, classify(u64::MAX));
+        assert_eq!(Abundance::Deficient, classify(1_u128 << 127));
+        assert_eq!(Abundance::Abundant, classify(3_u128 << 126));
+        assert_eq!(Abundance::Deficient, classify(u128::MAX));
+    }
+
+    #[test]
+    fn test_amicable() {
+        let pairs = (1..20000_u32)
+            .filter_map(|n| amicable_partner(n).map(|m| (n, m)))
+            .filter(|&(n, m)| n < m)
+            .collect::<Vec<_>>();
+        let expected = vec![
+            (220, 284),
+            (1184, 1210),
+            (2620, 2924),
+            (5020, 5564),
+            (6232, 6368),
+            (10744, 10856),
+            (12285, 14595),
+            (17296, 18416),
+        ];
+        assert_eq!(expected, pairs);
+        for &(a, b) in &expected {
+            assert!(is_amicable_pair(a, b) && is_amicable_pair(b, a));
+        }
+        assert!(!is_amicable_pair(220_u32, 285));
+        assert_eq!(None, amicable_partner(1_u32));
+        // the partner of a large abundant number does not fit
+        assert_eq!(None, amicable_partner(u32::MAX - 15));
+        assert!(is_amicable_pair(111448537712_u64, 118853793424));
+    }
+
+    #[test]
+    fn test_sociable_cycle() {
+        assert_eq!(Some(vec![6]), sociable_cycle(6_u32, 1));
+        assert_eq!(None, sociable_cycle(6_u32, 0));
+        assert_eq!(Some(vec![284, 220]), sociable_cycle(284_u32, 2));
+        assert_eq!(None, sociable_cycle(284_u32, 1));
+        assert_eq!(None, sociable_cycle(1_u32, 10));
+        // 95 -> 25 -> 6 ends in a cycle without 95
+        assert_eq!(None, sociable_cycle(95_u32, 10));
+        assert_eq!(None, sociable_cycle(95_u32, usize::MAX));
+        // 562 -> 284 -> 220 -> 284 ends in an amicable pair without 562
+        assert_eq!(None, sociable_cycle(562_u32, usize::MAX));
+        let cycle = sociable_cycle(14316_u32, 28).unwrap();
+        assert_eq!(28, cycle.len());
+        assert_eq!(Some(&629072), cycle.iter().max());
+        assert_eq!(None, sociable_cycle(14316_u32, 27));
+    }
+
+    #[test]
+    fn test_sigma_k() {
+        for n in 1..300_u32 {
+            let d = divisors(n);
+            assert_eq!(Some(d.len() as u128), sigma_k(n, 0));
+            for k in 1..4 {
+                let expected = d.iter().map(|&d| (d as u128).pow(k)).sum::<u128>();
+                assert_eq!(Some(expected), sigma_k(n, k));
+                assert_eq!(from_u128(expected), sigma_k_big(n, k));
+            }
+        }
+        assert_eq!(Some(1), sigma_k(1_u32, 100));
+        assert_eq!(None, sigma_k(2_u32, 128));
+        assert_eq!((BigUint::one() << 128) + BigUint::one(), sigma_k_big(2_u32, 128));
+    }
+
+    #[test]
+    fn test_unitary_divisors() {
+        for n in 1..1000_u32 {
+            let expected = divisors(n)
+                .into_iter()
+                .filter(|&d| d.gcd(&(n / d)) == 1)
+                .collect::<Vec<_>>();
+            let sum = expected.iter().map(|&d| d as u128).sum::<u128>();
+            assert_eq!(expected, unitary_divisors(n));
+            assert_eq!(Some(sum), unitary_sigma(n));
+        }
+        assert_eq!(vec![1, 1 << 127], unitary_divisors(1_u128 << 127));
+        assert_eq!(None, unitary_sigma(u128::MAX));
+    }
+
+    #[test]
+    fn test_bi_unitary_divisors() {
+        // the greatest common unitary divisor of a and b
+        fn gcud(a: u32, b: u32) -> u32 {
+            let b_divisors = unitary_divisors(b);
+            unitary_divisors(a)
+                .into_iter()
+                .filter(|d| b_divisors.contains(d))
+                .max()
+                .unwrap()
+        }
+
+        for n in 1..1000_u32 {
+            let expected = divisors(n)
+                .into_iter()
+                .filter(|&d| gcud(d, n / d) == 1)
+                .collect::<Vec<_>>();
+            let sum = expected.iter().map(|&d| d as u128).sum::<u128>();
+            assert_eq!(expected, bi_unitary_divisors(n));
+            assert_eq!(expected.len() as u64, bi_unitary_num_divisors(n));
+            assert_eq!(Some(sum), bi_unitary_sigma(n));
+        }
+        assert_eq!(128, bi_unitary_num_divisors(1_u128 << 127));
+        assert_eq!(Some(u128::MAX), bi_unitary_sigma(1_u128 << 127));
+        assert_eq!(None, bi_unitary_sigma(3_u128 << 126));
+    }
+
+    #[test]
+    fn test_divisor_pairs() {
+        for n in 1..1000_u32 {
+            let d = divisors(n);
+            let half = d.len().div_ceil(2);
+            let expected = d[..half].iter().map(|&d| (d, n / d)).collect::<Vec<_>>();
+            let mut pairs = divisor_pairs(n).collect::<Vec<_>>();
+            pairs.sort();
+            assert_eq!(expected, pairs);
+        }
+        assert_eq!(vec![(1, u128::MAX)], divisor_pairs(u128::MAX).take(1).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_divisors_in_range() {
+        for n in 1..300_u32 {
+            let d = divisors(n);
+            for lo in 0..20 {
+                for hi in lo..40 {
+                    let expected = d.iter().cloned().filter(|&d| lo <= d && d <= hi);
+                    assert_eq!(expected.collect::<Vec<_>>(), divisors_in_range(n, lo, hi));
+                }
+            }
+            assert_eq!(d, divisors_in_range(n, 0, u32::MAX));
+        }
+        assert!(divisors_in_range(360_u32, 12, 7).is_empty());
+        let n = 1_u128 << 127;
+        assert_eq!(vec![1 << 126, 1 << 127], divisors_in_range(n, 1 << 126, u128::MAX));
+        // the product of the first 15 primes
+        let primorial = (1..48_u64).filter(|&p| is_prime(p)).product::<u64>();
+        let lo = 1 << 30;
+        let expected = divisors(primorial).into_iter().filter(|&d| lo <= d && d <= 2 * lo);
+        assert_eq!(expected.collect::<Vec<_>>(), divisors_in_range(primorial, lo, 2 * lo));
+    }
+
+    #[test]
+    fn test_kth_divisor() {
+        for n in 1..1000_u32 {
+            let d = divisors(n);
+            for k in 1..=d.len() as u64 + 1 {
+                assert_eq!(d.get(k as usize - 1).cloned(), kth_divisor(n, k));
+            }
+        }
+        assert_eq!(Some(1 << 64), kth_divisor(1_u128 << 127, 65));
+        assert_eq!(Some(u128::MAX), kth_divisor(u128::MAX, 512));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_kth_divisor_zero() {
+        kth_divisor(12_u32, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_divisors_zero() {
+        divisors(0_u32);
+    }
+}
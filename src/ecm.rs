@@ -0,0 +1,312 @@
+
+extern crate num;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use self::num::integer::gcd;
+use self::num::{Integer, NumCast, PrimInt};
+
+use arith::{Modulus, Word};
+use p_minus_1::max_power;
+use sieve::prime_table;
+
+
+/// The first Suyama parameter, later curves use the following integers.
+const FIRST_SIGMA: u64 = 6;
+
+/// The giant step width for stage 2, the product of the smallest primes.
+const STAGE_2_WIDTH: u32 = 210;
+
+
+/// Finds a factor using Lenstra's elliptic curve method.
+///
+/// This tries up to the given number of curves.
+/// For each curve the group order behaves like a random number near p
+/// for a prime factor p of n, and the factor is found if all prime powers
+/// dividing it are at most b1, except possibly a single prime up to b2.
+/// So unlike p-1 the method can be repeated with different curves
+/// until it succeeds.
+///
+/// The expected runtime depends mainly on the size of the smallest
+/// factor, which makes it the method of choice for finding factors
+/// of 40 bits and more in numbers too large for SQUFOF.
+///
+/// Returns None if no nontrivial factor was found,
+/// in particular if n is less than 4 or prime.
+/// The returned factor may be composite.
+///
+pub fn lenstra_ecm<T: PrimInt + Integer>(n: T, b1: u32, b2: u32, curves: u32) -> Option<T> {
+    if n < NumCast::from(4).unwrap() {
+        return None;
+    }
+    let primes = prime_table(b1.max(b2));
+    let cancel = AtomicBool::new(false);
+    if let Some(n) = n.to_u32() {
+        ecm_with_primes(n, b1, &primes, curves, &cancel).map(|f| NumCast::from(f).unwrap())
+    } else if let Some(n) = n.to_u64() {
+        ecm_with_primes(n, b1, &primes, curves, &cancel).map(|f| NumCast::from(f).unwrap())
+    } else {
+        ecm_with_primes(n.to_u128().unwrap(), b1, &primes, curves, &cancel)
+            .map(|f| NumCast::from(f).unwrap())
+    }
+}
+
+
+/// Finds a factor of a machine word using Lenstra's elliptic curve method,
+/// with the primes up to b2 given.
+///
+/// Gives up before the next curve when the flag is set.
+///
+/// n must be at least 4.
+///
+pub(crate) fn ecm_with_primes<T: Word>(
+    n: T,
+    b1: u32,
+    primes: &[u32],
+    curves: u32,
+    cancel: &AtomicBool,
+) -> Option<T> {
+    if n & T::one() == T::zero() {
+        return Some(NumCast::from(2).unwrap());
+    }
+    let m = T::Modulus::new(n);
+    (0..curves as u64)
+        .take_while(|_| !cancel.load(Ordering::Relaxed))
+        .filter_map(|i| ecm_curve(m, FIRST_SIGMA + i, b1, primes))
+        .next()
+}
+
+
+/// A point on a Montgomery curve in projective coordinates X:Z.
+///
+/// The Y coordinate is not needed for computing multiples.
+///
+#[derive(Clone, Copy, Debug)]
+struct Point<T> {
+    x: T,
+    z: T,
+}
+
+
+/// A Montgomery curve B y^2 = x^3 + A x^2 + x modulo n.
+///
+/// The curve is given by (A + 2) / 4 as a fraction,
+/// which avoids computing a modular inverse.
+///
+struct Curve<T: Word> {
+    m: T::Modulus,
+    a24_num: T,
+    a24_den: T,
+}
+
+impl<T: Word> Curve<T> {
+    /// Returns 2 p.
+    ///
+    fn double(&self, p: Point<T>) -> Point<T> {
+        let m = &self.m;
+        let sum = m.add(p.x, p.z);
+        let sum2 = m.mul(sum, sum);
+        let diff = m.sub(p.x, p.z);
+        let diff2 = m.mul(diff, diff);
+        // 4 x z
+        let t = m.sub(sum2, diff2);
+        let diff2_den = m.mul(diff2, self.a24_den);
+        Point {
+            x: m.mul(sum2, diff2_den),
+            z: m.mul(t, m.add(diff2_den, m.mul(t, self.a24_num))),
+        }
+    }
+
+    /// Returns p + q, given p - q.
+    ///
+    fn add(&self, p: Point<T>, q: Point<T>, p_minus_q: Point<T>) -> Point<T> {
+        let m = &self.m;
+        let u = m.mul(m.sub(p.x, p.z), m.add(q.x, q.z));
+        let v = m.mul(m.add(p.x, p.z), m.sub(q.x, q.z));
+        let sum = m.add(u, v);
+        let diff = m.sub(u, v);
+        Point {
+            x: m.mul(p_minus_q.z, m.mul(sum, sum)),
+            z: m.mul(p_minus_q.x, m.mul(diff, diff)),
+        }
+    }
+
+    /// Returns k p for k >= 1, using the Montgomery ladder.
+    ///
+    fn mul(&self, p: Point<T>, k: u64) -> Point<T> {
+        let mut r0 = p;
+        let mut r1 = self.double(p);
+        let bits = 64 - k.leading_zeros();
+        for i in (0..bits - 1).rev() {
+            if (k >> i) & 1 == 1 {
+                r0 = self.add(r1, r0, p);
+                r1 = self.double(r1);
+            } else {
+                r1 = self.add(r1, r0, p);
+                r0 = self.double(r0);
+            }
+        }
+        r0
+    }
+}
+
+
+/// Runs both stages of ECM on the curve with Suyama parameter sigma.
+///
+fn ecm_curve<T: Word>(m: T::Modulus, sigma: u64, b1: u32, primes: &[u32]) -> Option<T> {
+    let n = m.modulus();
+    let residue = |x: u64| {
+        let x: T = NumCast::from(x).unwrap();
+        m.to_residue(x % n)
+    };
+    let nontrivial = |g: T| if g != T::one() && g != n { Some(g) } else { None };
+
+    // Suyama's parametrization gives a group order divisible by 12:
+    // u = sigma^2 - 5, v = 4 sigma, x0 = u^3, z0 = v^3,
+    // (A + 2) / 4 = (v - u)^3 (3 u + v) / (16 u^3 v)
+    let s = residue(sigma);
+    let u = m.sub(m.mul(s, s), residue(5));
+    let v = m.mul(residue(4), s);
+    let u3 = m.mul(m.mul(u, u), u);
+    let v3 = m.mul(m.mul(v, v), v);
+    let v_minus_u = m.sub(v, u);
+    let curve = Curve {
+        m,
+        a24_num: m.mul(
+            m.mul(m.mul(v_minus_u, v_minus_u), v_minus_u),
+            m.add(m.mul(residue(3), u), v),
+        ),
+        a24_den: m.mul(m.mul(residue(16), u3), v),
+    };
+    let g = gcd(curve.a24_den, n);
+    if g != T::one() {
+        // the parameter is degenerate modulo some factor
+        return nontrivial(g);
+    }
+
+    // stage 1: multiply by all prime powers up to b1
+    let stage_1_len = primes.iter().take_while(|&&p| p <= b1).count();
+    let mut q = Point { x: u3, z: v3 };
+    for &p in &primes[..stage_1_len] {
+        q = curve.mul(q, max_power(p, b1));
+    }
+    let g = gcd(q.z, n);
+    if g != T::one() {
+        return nontrivial(g);
+    }
+
+    // stage 2: a prime r = k w + j or k w - j divides the order of q
+    // if and only if k w q = +-j q, so multiply together all the
+    // cross differences X_kw Z_j - X_j Z_kw
+    let stage_2_primes = &primes[stage_1_len..];
+    if stage_2_primes.is_empty() {
+        return None;
+    }
+    let w = STAGE_2_WIDTH;
+
+    // baby steps j q for odd j <= w / 2
+    let q2 = curve.double(q);
+    let mut baby = vec![q, curve.add(q2, q, q)];
+    while baby.len() <= w as usize / 4 {
+        let len = baby.len();
+        baby.push(curve.add(baby[len - 1], q2, baby[len - 2]));
+    }
+
+    // giant steps k w q, starting with k >= 1;
+    // for k = 1 the previous giant step is the point at infinity,
+    // which does not work for additions, so the next step is a doubling
+    let wq = curve.mul(q, w as u64);
+    let mut k = (stage_2_primes[0] / w).max(1);
+    let mut giant_prev = if k == 1 { wq } else { curve.mul(q, (k - 1) as u64 * w as u64) };
+    let mut giant = curve.mul(q, k as u64 * w as u64);
+
+    let mut product = m.one();
+    for &r in stage_2_primes {
+        if r <= w / 2 {
+            // below the first giant step, only for small b1
+            product = m.mul(product, curve.mul(q, r as u64).z);
+            continue;
+        }
+        while r > k * w + w / 2 {
+            let next = if k == 1 {
+                curve.double(giant)
+            } else {
+                curve.add(giant, wq, giant_prev)
+            };
+            giant_prev = giant;
+            giant = next;
+            k += 1;
+        }
+        let j = r.abs_diff(k * w);
+        let b = baby[j as usize / 2];
+        product = m.mul(product, m.sub(m.mul(giant.x, b.z), m.mul(b.x, giant.z)));
+    }
+    nontrivial(gcd(product, n))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use arith::PlainModulus;
+
+    use super::*;
+
+    #[test]
+    fn test_curve_arithmetic() {
+        // compare the ladder with repeated differential additions
+        let m = PlainModulus::new(1_000_000_007_u32);
+        let curve = Curve {
+            m,
+            a24_num: 5,
+            a24_den: 7,
+        };
+        let p = Point { x: 2, z: 3 };
+        let mut multiples = vec![p, curve.double(p)];
+        for i in 2..20 {
+            let next = curve.add(multiples[i - 1], p, multiples[i - 2]);
+            multiples.push(next);
+        }
+        for (i, q) in multiples.iter().enumerate() {
+            let r = curve.mul(p, i as u64 + 1);
+            assert_eq!(m.mul(q.x, r.z), m.mul(r.x, q.z));
+        }
+    }
+
+    #[test]
+    fn test_stage_2() {
+        // the first curve modulo 100043 has order 2^4 * 3 * 2089
+        let p = 100043_u64;
+        let n = p * 1000003;
+        assert_eq!(None, lenstra_ecm(n, 20, 2088, 1));
+        assert_eq!(Some(p), lenstra_ecm(n, 20, 2089, 1));
+    }
+
+    #[test]
+    fn test_u64() {
+        let p = 1000003_u64;
+        let q = 4294967291_u64;
+        let f = lenstra_ecm(p * q, 200, 10000, 20);
+        assert!(f == Some(p) || f == Some(q));
+    }
+
+    #[test]
+    fn test_u128() {
+        // a 40-bit factor of a 104-bit number
+        let p = 1099511627791_u128;
+        let q = 18446744073709551557_u128;
+        assert_eq!(Some(p), lenstra_ecm(p * q, 1000, 50000, 100));
+    }
+
+    #[test]
+    fn test_prime() {
+        assert_eq!(None, lenstra_ecm(1000003_u32, 100, 1000, 5));
+    }
+
+    #[test]
+    fn test_small() {
+        assert_eq!(None, lenstra_ecm(3_u32, 100, 1000, 5));
+        assert_eq!(Some(2), lenstra_ecm(6_u32, 100, 1000, 5));
+        let f = lenstra_ecm(143_u32, 10, 100, 20);
+        assert!(f == Some(11) || f == Some(13));
+    }
+}
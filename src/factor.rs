@@ -1,171 +1,870 @@
+
 extern crate num;
-extern crate primal;
 
+use std::borrow::Cow;
 use std::cmp::min;
 use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use self::num::integer::gcd;
+use self::num::{Integer, NumCast, PrimInt, Signed};
+
+use arith::{exact_sqrt, Modulus, Word};
+use ecm::ecm_with_primes;
+use fermat::fermat_with_limit;
+use hart::hart_factor;
+use lehman::lehman_factor;
+use factorization::{Factorization, SignedFactorization};
+use p_minus_1::p_minus_1_with_primes;
+use p_plus_1::p_plus_1_with_primes;
+use sieve::{prime_table, wheel_candidates, PRIME_TABLE_LIMIT};
+use primality::is_prime_word;
+#[cfg(feature = "qs")]
+use qs::qs_with_cancel;
+use squfof::squfof;
+
+
+/// The default limit for trial division.
+///
+/// Below this trial division by primes from a table is faster
+/// than the other algorithms for typical inputs.
+///
+const TRIAL_FACTOR_LIMIT: u32 = 2000;
+
+/// The number of steps of Fermat's method tried before other algorithms,
+/// which finds factors p and q if (p - q)^2 is less than about 128 sqrt(n).
+const FERMAT_ITERATIONS: u64 = 16;
+
+/// Numbers up to this size are tried with Hart's method before rho,
+/// where it is usually faster.
+const HART_MAX_BITS: u32 = 32;
+
+/// Numbers in this size range are tried with SQUFOF before rho,
+/// where it is usually faster.
+const SQUFOF_MIN_BITS: u32 = 40;
+const SQUFOF_MAX_BITS: u32 = 62;
+
+/// Numbers from this size on are tried with p-1 and p+1 before rho.
+///
+/// Below this rho is usually faster than p-1 with the default bounds.
+///
+const P_MINUS_1_MIN_BITS: u32 = 56;
+
+/// The default bounds for p-1 in the factoring pipeline.
+const P_MINUS_1_B1: u32 = 1000;
+const P_MINUS_1_B2: u32 = 20000;
+
+/// The default bounds for p+1 in the factoring pipeline.
+const P_PLUS_1_B1: u32 = 1000;
+const P_PLUS_1_B2: u32 = 20000;
+
+/// Numbers from this size on are tried with ECM before rho.
+///
+/// Smaller numbers are handled well by SQUFOF and rho.
+///
+const ECM_MIN_BITS: u32 = 65;
 
-use self::primal::is_prime;
+/// The default bounds and number of curves for ECM in the factoring pipeline,
+/// suitable for factors up to about 50 bits.
+const ECM_B1: u32 = 2000;
+const ECM_B2: u32 = 200000;
+const ECM_CURVES: u32 = 200;
 
+/// Numbers in this size range are factored with the quadratic sieve
+/// if it is enabled, after a few curves of ECM for smaller factors.
+#[cfg(feature = "qs")]
+const QS_MIN_BITS: u32 = 63;
+#[cfg(feature = "qs")]
+const QS_MAX_BITS: u32 = 110;
+const QS_ECM_CURVES: u32 = 2;
 
-const TRIAL_FACTOR_LIMIT: u32 = 100;
+/// The number of rho steps between gcd computations.
+pub(crate) const RHO_BATCH_SIZE: u64 = 128;
 
 
 /// Factors an integer into its prime factors.
 ///
-pub fn factor(n: u32) -> BTreeMap<u32, u32> {
-    assert!(n != 0);
+/// This works for all primitive integer types up to 128 bits.
+/// The computation is done in the smallest unsigned type
+/// which can hold n, as smaller types are faster.
+///
+/// ECM and rho find factors up to about 50 bits in reasonable time,
+/// ECM being much faster for large numbers, so for 128-bit integers
+/// this works well up to about 100 bits
+/// and for numbers with at most one large prime factor.
+///
+/// n must be positive, see `try_factor` for a version which does not panic.
+///
+/// This uses the default `FactorConfig`.
+///
+pub fn factor<T: PrimInt + Integer>(n: T) -> Factorization<T> {
+    FactorConfig::default().factor(n)
+}
+
+
+/// Factors an integer into its prime factors, returning an error on failure.
+///
+/// This is like `factor` but returns an error for zero and negative inputs.
+///
+pub fn try_factor<T: PrimInt + Integer>(n: T) -> Result<Factorization<T>, FactorError> {
+    FactorConfig::default().try_factor(n)
+}
+
+
+/// The configuration of the factoring algorithms.
+///
+/// Large numbers are first tried with a few steps of Fermat's method,
+/// which quickly finds two factors close to the square root.
+/// Numbers up to 32 bits are then tried with Hart's one line factoring
+/// method and numbers from 40 to 62 bits with SQUFOF.
+/// Before falling back to Pollard's rho algorithm, large numbers are
+/// tried with the special purpose algorithms p-1 and p+1 if enabled.
+/// They are fast for factors p where p - 1 or p + 1 respectively
+/// has only small prime factors, up to the bounds given.
+/// Numbers above 64 bits are then tried with the elliptic curve method,
+/// which finds factors up to about 50 bits much faster than rho.
+///
+/// With the `qs` feature numbers from 63 to 110 bits are factored with
+/// the quadratic sieve, which is much faster for balanced semiprimes.
+///
+/// By default p-1, ECM and the quadratic sieve are enabled and p+1 is
+/// disabled, as p+1 rarely finds factors which p-1 does not and is slower.
+///
+/// Numbers up to 64 bits for which the other algorithms fail can be
+/// factored with Lehman's method instead of rho, which has a bounded
+/// worst case runtime, but is usually slower.
+///
+/// Small factors are removed by trial division first.
+/// Rho can be disabled or limited to a number of steps,
+/// in which case factoring may fail with `FactorError::Incomplete`.
+/// By default rho tries the same sequences for every input,
+/// which can be changed by giving a seed.
+///
+/// ```
+/// use jfactor::FactorConfig;
+///
+/// let config = FactorConfig::new().p_plus_1(true);
+/// let n = 1000159_u64 * 1000003;
+/// assert_eq!(vec![1000003, 1000159], config.factor(n).primes().cloned().collect::<Vec<_>>());
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FactorConfig {
+    trial_division_limit: u32,
+    fermat: bool,
+    hart: bool,
+    p_minus_1: bool,
+    p_minus_1_b1: u32,
+    p_minus_1_b2: u32,
+    p_plus_1: bool,
+    p_plus_1_b1: u32,
+    p_plus_1_b2: u32,
+    ecm: bool,
+    ecm_b1: u32,
+    ecm_b2: u32,
+    ecm_curves: u32,
+    lehman: bool,
+    rho: bool,
+    rho_iterations: u64,
+    rho_seed: Option<u64>,
+    #[cfg(feature = "qs")]
+    qs: bool,
+}
+
+impl Default for FactorConfig {
+    fn default() -> FactorConfig {
+        FactorConfig {
+            trial_division_limit: TRIAL_FACTOR_LIMIT,
+            fermat: true,
+            hart: true,
+            p_minus_1: true,
+            p_minus_1_b1: P_MINUS_1_B1,
+            p_minus_1_b2: P_MINUS_1_B2,
+            p_plus_1: false,
+            p_plus_1_b1: P_PLUS_1_B1,
+            p_plus_1_b2: P_PLUS_1_B2,
+            ecm: true,
+            ecm_b1: ECM_B1,
+            ecm_b2: ECM_B2,
+            ecm_curves: ECM_CURVES,
+            lehman: false,
+            rho: true,
+            rho_iterations: u64::MAX,
+            rho_seed: None,
+            #[cfg(feature = "qs")]
+            qs: true,
+        }
+    }
+}
+
+impl FactorConfig {
+    /// Creates the default configuration.
+    ///
+    pub fn new() -> FactorConfig {
+        FactorConfig::default()
+    }
+
+    /// Sets the limit below which factors are found by trial division.
+    ///
+    /// The default is 2000.
+    /// Trial division uses the primes from `prime_table` up to 200000,
+    /// and above that the numbers not divisible by 2, 3, 5 or 7.
+    ///
+    pub fn trial_division_limit(mut self, limit: u32) -> FactorConfig {
+        self.trial_division_limit = limit;
+        self
+    }
+
+    /// Enables or disables the pre-check with Fermat's method.
+    ///
+    pub fn fermat(mut self, enabled: bool) -> FactorConfig {
+        self.fermat = enabled;
+        self
+    }
+
+    /// Enables or disables Hart's one line factoring method.
+    ///
+    pub fn hart(mut self, enabled: bool) -> FactorConfig {
+        self.hart = enabled;
+        self
+    }
+
+    /// Enables or disables Pollard's p-1 algorithm.
+    ///
+    pub fn p_minus_1(mut self, enabled: bool) -> FactorConfig {
+        self.p_minus_1 = enabled;
+        self
+    }
+
+    /// Sets the stage 1 and stage 2 bounds for Pollard's p-1 algorithm.
+    ///
+    pub fn p_minus_1_bounds(mut self, b1: u32, b2: u32) -> FactorConfig {
+        self.p_minus_1_b1 = b1;
+        self.p_minus_1_b2 = b2;
+        self
+    }
+
+    /// Enables or disables Williams' p+1 algorithm.
+    ///
+    pub fn p_plus_1(mut self, enabled: bool) -> FactorConfig {
+        self.p_plus_1 = enabled;
+        self
+    }
+
+    /// Sets the stage 1 and stage 2 bounds for Williams' p+1 algorithm.
+    ///
+    pub fn p_plus_1_bounds(mut self, b1: u32, b2: u32) -> FactorConfig {
+        self.p_plus_1_b1 = b1;
+        self.p_plus_1_b2 = b2;
+        self
+    }
+
+    /// Enables or disables Lenstra's elliptic curve method.
+    ///
+    pub fn ecm(mut self, enabled: bool) -> FactorConfig {
+        self.ecm = enabled;
+        self
+    }
+
+    /// Sets the stage 1 and stage 2 bounds for the elliptic curve method.
+    ///
+    pub fn ecm_bounds(mut self, b1: u32, b2: u32) -> FactorConfig {
+        self.ecm_b1 = b1;
+        self.ecm_b2 = b2;
+        self
+    }
+
+    /// Sets the number of curves tried by the elliptic curve method.
+    ///
+    pub fn ecm_curves(mut self, curves: u32) -> FactorConfig {
+        self.ecm_curves = curves;
+        self
+    }
+
+    /// Uses Lehman's method instead of rho for numbers up to 64 bits.
+    ///
+    /// This bounds the runtime for these by O(n^(1/3)).
+    ///
+    pub fn lehman(mut self, enabled: bool) -> FactorConfig {
+        self.lehman = enabled;
+        self
+    }
+
+    /// Enables or disables Pollard's rho algorithm.
+    ///
+    /// Without it factoring fails if no other algorithm finds a factor.
+    ///
+    pub fn rho(mut self, enabled: bool) -> FactorConfig {
+        self.rho = enabled;
+        self
+    }
+
+    /// Sets the maximum number of steps of Pollard's rho algorithm
+    /// for each factor, after which factoring fails.
+    ///
+    /// The limit is checked once per batch of steps sharing a gcd,
+    /// so it can be exceeded by less than a batch.
+    /// The default is unlimited.
+    ///
+    pub fn rho_iterations(mut self, limit: u64) -> FactorConfig {
+        self.rho_iterations = limit;
+        self
+    }
+
+    /// Sets a seed for choosing the pseudo-random sequences of rho.
+    ///
+    /// Without a seed rho uses the polynomials x^2 + 1, x^2 + 2, ...
+    /// starting from 1, so that inputs which are hard for them are
+    /// always hard. With a seed the polynomials and starting values
+    /// are chosen pseudo-randomly, and the same seed gives the same
+    /// computation, for example a seed taken from `rand::random()`.
+    ///
+    pub fn rho_seed(mut self, seed: u64) -> FactorConfig {
+        self.rho_seed = Some(seed);
+        self
+    }
+
+    /// Enables or disables the quadratic sieve.
+    ///
+    /// This is only available with the `qs` feature.
+    ///
+    #[cfg(feature = "qs")]
+    pub fn qs(mut self, enabled: bool) -> FactorConfig {
+        self.qs = enabled;
+        self
+    }
+
+    /// Factors an integer into its prime factors with this configuration.
+    ///
+    /// n must be positive, see `try_factor` for a version which does not panic.
+    ///
+    pub fn factor<T: PrimInt + Integer>(&self, n: T) -> Factorization<T> {
+        match self.try_factor(n) {
+            Ok(factorization) => factorization,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Factors an integer into its prime factors with this configuration,
+    /// returning an error on failure.
+    ///
+    /// This fails for zero and negative inputs, and if the enabled
+    /// algorithms give up before factoring completely.
+    ///
+    pub fn try_factor<T: PrimInt + Integer>(&self, n: T) -> Result<Factorization<T>, FactorError> {
+        self.try_factor_cancellable(n, &AtomicBool::new(false))
+    }
+
+    /// Factors an integer into its prime factors with this configuration,
+    /// giving up when the flag is set.
+    ///
+    /// The flag is checked regularly by the long running algorithms,
+    /// so another thread can set it to abort the factorization,
+    /// which then fails with `FactorError::Cancelled`.
+    ///
+    /// ```
+    /// use std::sync::atomic::AtomicBool;
+    ///
+    /// use jfactor::{FactorConfig, FactorError};
+    ///
+    /// let cancel = AtomicBool::new(true);
+    /// let n = 1000003_u64 * 4294967291;
+    /// let result = FactorConfig::new().try_factor_cancellable(n, &cancel);
+    /// assert_eq!(Err(FactorError::Cancelled), result);
+    /// ```
+    ///
+    pub fn try_factor_cancellable<T: PrimInt + Integer>(
+        &self,
+        n: T,
+        cancel: &AtomicBool,
+    ) -> Result<Factorization<T>, FactorError> {
+        if n == T::zero() {
+            return Err(FactorError::Zero);
+        }
+        if n < T::zero() {
+            return Err(FactorError::Negative);
+        }
+
+        fn convert<T: PrimInt, W: Word>(factorization: BTreeMap<W, u32>) -> Factorization<T> {
+            factorization
+                .into_iter()
+                .map(|(p, e)| (NumCast::from(p).unwrap(), e))
+                .collect::<BTreeMap<_, _>>()
+                .into()
+        }
+
+        Ok(if let Some(n) = n.to_u32() {
+            convert(factor_word(n, self, cancel)?)
+        } else if let Some(n) = n.to_u64() {
+            convert(factor_word(n, self, cancel)?)
+        } else {
+            convert(factor_word(n.to_u128().unwrap(), self, cancel)?)
+        })
+    }
+
+    /// Factors a signed integer into a unit and its prime factors
+    /// with this configuration, returning an error on failure.
+    ///
+    /// See `try_factor_signed`.
+    ///
+    pub fn try_factor_signed<T: PrimInt + Integer + Signed>(
+        &self,
+        n: T,
+    ) -> Result<SignedFactorization<T>, FactorError> {
+        self.try_factor_signed_cancellable(n, &AtomicBool::new(false))
+    }
+
+    /// Factors a signed integer into a unit and its prime factors
+    /// with this configuration, giving up when the flag is set.
+    ///
+    /// See `try_factor_cancellable`.
+    ///
+    pub fn try_factor_signed_cancellable<T: PrimInt + Integer + Signed>(
+        &self,
+        n: T,
+        cancel: &AtomicBool,
+    ) -> Result<SignedFactorization<T>, FactorError> {
+        if n == T::zero() {
+            return Err(FactorError::Zero);
+        }
+        let unit = if n < T::zero() { -T::one() } else { T::one() };
+
+        // the absolute value of the most negative integer does not fit into T,
+        // but all its prime factors do
+        let abs = n.to_i128().unwrap().unsigned_abs();
+        let factorization = self
+            .try_factor_cancellable(abs, cancel)?
+            .into_iter()
+            .map(|(p, e)| (NumCast::from(p).unwrap(), e))
+            .collect::<BTreeMap<_, _>>();
+        Ok(SignedFactorization::new(unit, factorization.into()))
+    }
+
+    /// Returns whether numbers with this many bits are factored
+    /// with the quadratic sieve.
+    ///
+    #[cfg(feature = "qs")]
+    fn uses_qs(&self, bits: u32) -> bool {
+        self.qs && (QS_MIN_BITS..=QS_MAX_BITS).contains(&bits)
+    }
+    #[cfg(not(feature = "qs"))]
+    fn uses_qs(&self, _bits: u32) -> bool {
+        false
+    }
+
+    /// Returns the primes up to the largest enabled stage 2 bound.
+    ///
+    fn primes(&self) -> Cow<'static, [u32]> {
+        let mut limit = 0;
+        if self.p_minus_1 {
+            limit = limit.max(self.p_minus_1_b1).max(self.p_minus_1_b2);
+        }
+        if self.p_plus_1 {
+            limit = limit.max(self.p_plus_1_b1).max(self.p_plus_1_b2);
+        }
+        if self.ecm {
+            limit = limit.max(self.ecm_b1).max(self.ecm_b2);
+        }
+        prime_table(limit)
+    }
+}
+
+
+/// Factors a nonzero signed integer into a unit and its prime factors.
+///
+/// The unit is -1 for negative integers and 1 otherwise.
+///
+/// n must not be zero, see `try_factor_signed` for a version which
+/// does not panic.
+///
+pub fn factor_signed<T: PrimInt + Integer + Signed>(n: T) -> SignedFactorization<T> {
+    match try_factor_signed(n) {
+        Ok(factorization) => factorization,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+
+/// Factors a signed integer into a unit and its prime factors,
+/// returning an error on failure.
+///
+pub fn try_factor_signed<T: PrimInt + Integer + Signed>(
+    n: T,
+) -> Result<SignedFactorization<T>, FactorError> {
+    FactorConfig::default().try_factor_signed(n)
+}
+
+
+/// The reasons why an integer could not be factored.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FactorError {
+    /// Zero has no factorization.
+    Zero,
+    /// Negative integers have no factorization into primes alone,
+    /// use `factor_signed` for them.
+    Negative,
+    /// The factoring algorithm gave up before factoring completely.
+    Incomplete,
+    /// The factorization was cancelled.
+    Cancelled,
+}
+
+impl fmt::Display for FactorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FactorError::Zero => write!(f, "zero has no factorization"),
+            FactorError::Negative => write!(f, "negative integers are not supported"),
+            FactorError::Incomplete => write!(f, "factorization is incomplete"),
+            FactorError::Cancelled => write!(f, "factorization was cancelled"),
+        }
+    }
+}
+
+impl Error for FactorError {}
+
+
+/// Factors a machine word into its prime factors.
+///
+/// Fails if no enabled algorithm finds a factor of a composite
+/// or if the flag is set.
+///
+fn factor_word<T: Word>(
+    n: T,
+    config: &FactorConfig,
+    cancel: &AtomicBool,
+) -> Result<BTreeMap<T, u32>, FactorError> {
+    assert!(n != T::zero());
 
     let mut factorization = BTreeMap::new();
 
-    // remove small factors
+    // remove small factors, afterwards rest has no prime factors
+    // below bound; comparing with the quotient as the square may overflow
     let mut rest = n;
-    let mut trial_factor = 2_u32;
-    while trial_factor < TRIAL_FACTOR_LIMIT && rest >= trial_factor * trial_factor {
+    let limit = config.trial_division_limit.max(2);
+    let mut bound: T = NumCast::from(limit).unwrap();
+    let primes = prime_table(min(limit - 1, PRIME_TABLE_LIMIT));
+    let large_candidates = wheel_candidates(PRIME_TABLE_LIMIT as u64 + 1)
+        .take_while(|&d| d < limit as u64);
+    for p in primes.iter().map(|&p| p as u64).chain(large_candidates) {
+        let p: T = NumCast::from(p).unwrap();
+        if rest / p < p {
+            bound = p;
+            break;
+        }
         let mut exponent = 0;
-        while rest % trial_factor == 0 {
-            rest /= trial_factor;
+        while rest % p == T::zero() {
+            rest = rest / p;
             exponent += 1;
         }
         if exponent != 0 {
-            factorization.insert(trial_factor, exponent);
-        }
-        if trial_factor == 2 {
-            trial_factor = 3;
-        } else {
-            trial_factor += 2;
+            factorization.insert(p, exponent);
         }
     }
 
-    if rest < trial_factor * trial_factor {
+    if rest / bound < bound {
         // rest is 1 or prime
-        if rest > 1 {
+        if rest > T::one() {
             factorization.insert(rest, 1);
         }
     } else {
         // use Pollard's rho algorithm to find large factors
+        let primes = config.primes();
         let mut unfactored = vec![rest];
-        while !unfactored.is_empty() {
-            let u = unfactored.pop().unwrap();
-
-            if is_prime(u as u64) {
+        while let Some(u) = unfactored.pop() {
+            if is_prime_word(u) {
                 *factorization.entry(u).or_insert(0) += 1;
             } else {
-                let f = find_large_factor(u);
+                let f = find_large_factor_word(u, config, &primes, cancel);
+                if cancel.load(Ordering::Relaxed) {
+                    return Err(FactorError::Cancelled);
+                }
+                let f = f.ok_or(FactorError::Incomplete)?;
                 unfactored.push(f);
                 unfactored.push(u / f);
             }
         }
     }
-    factorization
+    Ok(factorization)
+}
+
+
+/// Finds a factor of a composite machine word.
+///
+/// A few steps of Fermat's method find factors close to the square root.
+/// Numbers up to 32 bits are then tried with Hart's method
+/// and numbers from 40 to 62 bits with SQUFOF.
+/// Large numbers are first tried with Pollard's p-1 algorithm,
+/// which quickly finds factors p where p - 1 has only small factors,
+/// and then with Williams' p+1 algorithm, as far as they are enabled.
+/// Numbers above 64 bits are then tried with the elliptic curve method,
+/// and up to 110 bits with the quadratic sieve if the `qs` feature is enabled.
+/// Otherwise Pollard's rho algorithm is used,
+/// or Lehman's method for numbers up to 64 bits if it is enabled.
+///
+/// Uses 64-bit arithmetic whenever n fits, as it is much faster.
+///
+/// primes must contain the primes up to the enabled stage 2 bounds.
+///
+/// Returns None if no enabled algorithm found a factor,
+/// or if the long running algorithms gave up because the flag was set.
+///
+fn find_large_factor_word<T: Word>(
+    n: T,
+    config: &FactorConfig,
+    primes: &[u32],
+    cancel: &AtomicBool,
+) -> Option<T> {
+    if T::zero().count_zeros() > 64 {
+        if let Some(n64) = n.to_u64() {
+            return find_large_factor_word(n64, config, primes, cancel)
+                .map(|f| NumCast::from(f).unwrap());
+        }
+    }
+    // even numbers and squares of primes can only get here
+    // with a low trial division limit, and rho does not find the latter
+    if n & T::one() == T::zero() {
+        return Some(NumCast::from(2).unwrap());
+    }
+    if let Some(s) = exact_sqrt(n) {
+        return Some(s);
+    }
+    if config.fermat {
+        if let Some(f) = fermat_with_limit(n, FERMAT_ITERATIONS) {
+            return Some(f);
+        }
+    }
+    let bits = T::zero().count_zeros() - n.leading_zeros();
+    if config.hart && bits <= HART_MAX_BITS {
+        if let Some(f) = hart_factor(n.to_u64().unwrap()) {
+            return Some(NumCast::from(f).unwrap());
+        }
+    }
+    if (SQUFOF_MIN_BITS..=SQUFOF_MAX_BITS).contains(&bits) {
+        if let Some(f) = squfof(n.to_u64().unwrap()) {
+            return Some(NumCast::from(f).unwrap());
+        }
+    }
+    fn primes_up_to_bounds(primes: &[u32], b1: u32, b2: u32) -> &[u32] {
+        let limit = b1.max(b2);
+        &primes[..primes.partition_point(|&p| p <= limit)]
+    }
+
+    if bits >= P_MINUS_1_MIN_BITS {
+
+        if config.p_minus_1 {
+            let primes = primes_up_to_bounds(primes, config.p_minus_1_b1, config.p_minus_1_b2);
+            if let Some(f) = p_minus_1_with_primes(n, config.p_minus_1_b1, primes) {
+                return Some(f);
+            }
+        }
+        if config.p_plus_1 {
+            let primes = primes_up_to_bounds(primes, config.p_plus_1_b1, config.p_plus_1_b2);
+            if let Some(f) = p_plus_1_with_primes(n, config.p_plus_1_b1, primes) {
+                return Some(f);
+            }
+        }
+    }
+    if bits >= ECM_MIN_BITS && config.ecm {
+        let primes = primes_up_to_bounds(primes, config.ecm_b1, config.ecm_b2);
+        let curves = if config.uses_qs(bits) {
+            // only look for small factors, QS is faster for large ones
+            config.ecm_curves.min(QS_ECM_CURVES)
+        } else {
+            config.ecm_curves
+        };
+        if let Some(f) = ecm_with_primes(n, config.ecm_b1, primes, curves, cancel) {
+            return Some(f);
+        }
+    }
+    #[cfg(feature = "qs")]
+    {
+        if config.uses_qs(bits) {
+            if let Some(f) = qs_with_cancel(n.to_u128().unwrap(), cancel) {
+                return Some(NumCast::from(f).unwrap());
+            }
+        }
+    }
+    if config.lehman {
+        if let Some(n64) = n.to_u64() {
+            return lehman_factor(n64).map(|f| NumCast::from(f).unwrap());
+        }
+    }
+    if config.rho {
+        find_large_factor(n, config.rho_iterations, config.rho_seed, cancel)
+    } else {
+        None
+    }
 }
 
 
-/// Finds a factor using Pollard's rho algorithm.
+/// Finds a factor using Pollard's rho algorithm with Brent's improvements.
 ///
 /// The expected runtime is O(sqrt(f)) where f is the smallest factor of n.
 /// This implies an expected runtime on the order of the fourth root of n.
 /// It is the fastest algorithm currently known for up to about 100 bits.
 ///
+/// Brent's cycle detection compares with a saved value which is updated
+/// at powers of two, and skips the comparisons which cannot succeed.
+/// Differences are multiplied together to share one gcd computation
+/// between several steps. If that finds the trivial factor n,
+/// the steps since the last gcd are repeated one by one.
+///
 /// n must be composite.
 /// The returned factor may be composite.
 ///
-fn find_large_factor(n: u32) -> u32 {
+/// Without a seed the sequences are x^2 + c for c = 1, 2, 3, ...
+/// starting from 1, with a seed c and the starting value are
+/// pseudo-random.
+///
+/// Returns None if no factor was found in the given number of steps,
+/// or when the flag is set.
+///
+fn find_large_factor<T: Word>(
+    n: T,
+    max_iterations: u64,
+    seed: Option<u64>,
+    cancel: &AtomicBool,
+) -> Option<T> {
 
-    /// Generates a pseudo-random sequence of numbers below m.
+    /// Generates a pseudo-random sequence of residues modulo m.
     ///
-    /// c determines the sequence, should not be 0 or -2
+    /// a determines the sequence, should not be 0 or -2
     /// x is the previous value
     ///
-    /// All values must fit in 32 bits to avoid overflow.
-    ///
-    fn next_random(m: u32, c: u32, x: u32) -> u32 {
-        ((x as u64 * x as u64 + c as u64) % m as u64) as u32
-    }
-
-    let mut f: u32 = n;
-    let mut c: u32 = 1;
-    while f == n {
-        let mut limit_power = 0;
-
-        let mut k = 0;
-        let mut y = 1;
-
-        'search: loop {
-            limit_power += 1;
-            let limit = 1_u32 << limit_power;
-            let product_limit = 1_u32 << (limit_power / 2);
-
-            let x = y;
-            while k < limit {
-                let saved_y = y;
-
-                // multiply together differences in the random sequence
-                // doing gcd once for several numbers improves efficiency
-                let mut product = 1;
-                let mut j = 0;
-                let j_limit = min(product_limit, limit - k);
-                while j < j_limit {
-                    y = next_random(n, c, y);
-                    product = {
-                        let n = n as u64;
-                        let x = x as u64;
-                        let y = y as u64;
-                        let product = product as u64;
-                        let x_minus_y = if x >= y {
-                            x - y
-                        } else {
-                            n - y + x
-                        };
-
-                        ((product * x_minus_y) % n) as u32
-                    };
-                    j += 1;
+    fn next_random<T: Word>(m: &T::Modulus, a: T, x: T) -> T {
+        m.add(m.mul(x, x), a)
+    }
+
+    // the sequence and the differences are computed on residues,
+    // which does not change their common factors with n
+    let m = T::Modulus::new(n);
+
+    let mut rng = seed.map(SplitMix64::new);
+    let mut iterations = 0_u64;
+    let mut c: T = T::one();
+    loop {
+        let (a, start) = match rng {
+            Some(ref mut rng) => {
+                // c in 1..n-2 avoids the bad polynomials x^2 and x^2 - 2
+                let three: T = NumCast::from(3).unwrap();
+                (rng.next_below(n - three) + T::one(), rng.next_below(n))
+            }
+            None => (c % n, T::one()),
+        };
+        let a = m.to_residue(a);
+        let start = m.to_residue(start);
+        let mut f = T::one();
+
+        let mut x = start;
+        let mut y = x;
+        let mut saved_y = y;
+        let mut product = T::one();
+        let mut r = 1_u64;
+        // the limit and the flag are checked once per batch
+        let stop = |iterations| iterations >= max_iterations || cancel.load(Ordering::Relaxed);
+        while f == T::one() {
+            x = y;
+            let mut k = 0;
+            while k < r {
+                if stop(iterations) {
+                    return None;
                 }
-                f = gcd::<u32>(product, n);
-
-                if f == 1 {
-                    // no common factor found, move on
-                    k += j_limit;
-                } else {
-                    // restart and find the factor
-                    y = saved_y;
-                    loop {
-                        k == 1;
-                        y = next_random(n, c, y);
-                        let x_minus_y = if x >= y {
-                            x - y
-                        } else {
-                            n - y + x
-                        };
-                        f = gcd::<u32>(x_minus_y, n);
-                        if f != 1 {
-                            break 'search;
-                        }
-                    }
+                let batch_size = min(RHO_BATCH_SIZE, r - k);
+                for _ in 0..batch_size {
+                    y = next_random(&m, a, y);
                 }
+                iterations += batch_size;
+                k += batch_size;
             }
+            let mut k = 0;
+            while k < r && f == T::one() {
+                if stop(iterations) {
+                    return None;
+                }
+                saved_y = y;
+                let batch_size = min(RHO_BATCH_SIZE, r - k);
+                for _ in 0..batch_size {
+                    y = next_random(&m, a, y);
+                    product = m.mul(product, m.sub(x, y));
+                }
+                f = gcd::<T>(product, n);
+                iterations += batch_size;
+                k += batch_size;
+            }
+            r *= 2;
+        }
+
+        if f == n {
+            // the batch overshot, repeat it step by step
+            loop {
+                saved_y = next_random(&m, a, saved_y);
+                f = gcd::<T>(m.sub(x, saved_y), n);
+                if f != T::one() {
+                    break;
+                }
+            }
+        }
+        if f != n {
+            return Some(f);
         }
 
         // if we find only the trivial factor f == n
         // we retry with another random sequence
-        c += 1;
+        c = c + T::one();
+    }
+}
+
+
+/// A small and fast pseudo-random number generator.
+///
+/// This is Steele, Lea and Flood's SplitMix64, which is good enough
+/// for choosing polynomials and starting values.
+///
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a pseudo-random number below the positive bound.
+    ///
+    /// The slight bias of the modulo reduction does not matter here.
+    ///
+    fn next_below<T: Word>(&mut self, bound: T) -> T {
+        let r = ((self.next_u64() as u128) << 64) | self.next_u64() as u128;
+        NumCast::from(r % bound.to_u128().unwrap()).unwrap()
     }
-    f
 }
 
 
 /// Finds the largest prime factor of an integer.
 ///
-pub fn largest_prime_factor(n: u32) -> Option<u32> {
-    factor(n).keys().last().cloned()
+pub fn largest_prime_factor<T: PrimInt + Integer>(n: T) -> Option<T> {
+    factor(n).primes().next_back().cloned()
 }
 
 
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use primality::is_prime;
 
     use super::*;
 
@@ -178,7 +877,7 @@ mod tests {
     #[test]
     fn test_one() {
         let expected = BTreeMap::new();
-        let actual = factor(1);
+        let actual = factor(1).into_map();
         assert_eq!(expected, actual);
     }
 
@@ -186,7 +885,7 @@ mod tests {
     fn test_two() {
         let mut expected = BTreeMap::new();
         expected.insert(2, 1);
-        let actual = factor(2);
+        let actual = factor(2).into_map();
         assert_eq!(expected, actual);
     }
 
@@ -194,7 +893,7 @@ mod tests {
     fn test_four() {
         let mut expected = BTreeMap::new();
         expected.insert(2, 2);
-        let actual = factor(4);
+        let actual = factor(4).into_map();
         assert_eq!(expected, actual);
     }
 
@@ -203,7 +902,7 @@ mod tests {
         let mut expected = BTreeMap::new();
         expected.insert(2, 1);
         expected.insert(3, 1);
-        let actual = factor(6);
+        let actual = factor(6).into_map();
         assert_eq!(expected, actual);
     }
 
@@ -211,7 +910,7 @@ mod tests {
     fn test_power() {
         let mut expected = BTreeMap::new();
         expected.insert(2, 31);
-        let actual = factor(1 << 31);
+        let actual = factor(1_u32 << 31).into_map();
         assert_eq!(expected, actual);
     }
 
@@ -224,10 +923,476 @@ mod tests {
         expected.insert(p1, 1);
         expected.insert(p2, 1);
 
-        let actual = factor(p1 * p2);
+        let actual = factor(p1 * p2).into_map();
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    #[should_panic]
+    fn test_u64_zero() {
+        factor(0_u64);
+    }
+
+    #[test]
+    fn test_u64_one() {
+        let expected = BTreeMap::new();
+        let actual = factor(1_u64).into_map();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_u64_power() {
+        let mut expected = BTreeMap::new();
+        expected.insert(2, 63);
+        let actual = factor(1_u64 << 63).into_map();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_u64_prime() {
+        let p = 18446744073709551557_u64;
+
+        let mut expected = BTreeMap::new();
+        expected.insert(p, 1);
+
+        let actual = factor(p).into_map();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_64bit_overflow() {
+        let p1 = 4294967279_u64;
+        let p2 = 4294967291_u64;
+
+        let mut expected = BTreeMap::new();
+        expected.insert(p1, 1);
+        expected.insert(p2, 1);
+
+        let actual = factor(p1 * p2).into_map();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_u64_mixed() {
+        let mut expected = BTreeMap::new();
+        expected.insert(3, 2);
+        expected.insert(65521, 1);
+        expected.insert(4294967291, 1);
+
+        let actual = factor(9 * 65521 * 4294967291_u64).into_map();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_u64_squfof() {
+        // balanced semiprimes of 48 and 61 bits
+        let mut expected = BTreeMap::new();
+        expected.insert(12716569, 1);
+        expected.insert(13665961, 1);
+        assert_eq!(expected, factor(12716569 * 13665961_u64).into_map());
+
+        let mut expected = BTreeMap::new();
+        expected.insert(1446901903, 1);
+        expected.insert(1495291943, 1);
+        assert_eq!(expected, factor(1446901903 * 1495291943_u64).into_map());
+    }
+
+    #[test]
+    fn test_u128_small() {
+        let mut expected = BTreeMap::new();
+        expected.insert(2, 1);
+        expected.insert(3, 1);
+        let actual = factor(6_u128).into_map();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_u128_power() {
+        let mut expected = BTreeMap::new();
+        expected.insert(2, 127);
+        let actual = factor(1_u128 << 127).into_map();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_u128_prime() {
+        let p = (1_u128 << 127) - 1;
+
+        let mut expected = BTreeMap::new();
+        expected.insert(p, 1);
+
+        let actual = factor(p).into_map();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_u128_large_cofactor() {
+        let p1 = 4294967291_u128;
+        let p2 = (1_u128 << 31) - 1;
+        let p3 = 18446744073709551557_u128;
+
+        let mut expected = BTreeMap::new();
+        expected.insert(p1, 1);
+        expected.insert(p2, 1);
+        expected.insert(p3, 1);
+
+        let actual = factor(p1 * p2 * p3).into_map();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_128bit_overflow() {
+        let p1 = 68719476767_u128;
+        let p2 = (1_u128 << 89) - 1;
+
+        let mut expected = BTreeMap::new();
+        expected.insert(p1, 1);
+        expected.insert(p2, 1);
+
+        let actual = factor(p1 * p2).into_map();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_largest_prime_factor() {
+        assert_eq!(None, largest_prime_factor(1));
+        assert_eq!(Some(6857), largest_prime_factor(600851475143_u64));
+    }
+
+    #[test]
+    fn test_find_large_factor() {
+        let cancel = AtomicBool::new(false);
+        // composites without factors below 100
+        let composites = (10201..40000_u32)
+            .filter(|&n| !is_prime(n as u64))
+            .filter(|&n| (2..100).all(|d| n % d != 0));
+        for n in composites {
+            let f = find_large_factor(n, u64::MAX, None, &cancel).unwrap();
+            assert!(f > 1 && f < n && n % f == 0, "{} for {}", f, n);
+        }
+    }
+
+    #[test]
+    fn test_find_large_factor_u128() {
+        let cancel = AtomicBool::new(false);
+        let n = 1000003_u128 * 1000033;
+        let f = find_large_factor(n, u64::MAX, None, &cancel).unwrap();
+        assert!(f == 1000003 || f == 1000033);
+    }
+
+    #[test]
+    fn test_p_plus_1_factor() {
+        // 281474976714779 + 1 = 2^2 * 3 * 5 * 23 * 29 * 53 * 211 * 677 * 929
+        // is found by p+1, but 281474976714779 - 1 has the factor 2772437
+        let p1 = 281474976714779_u128;
+        let p2 = 18446744073709551557_u128;
+
+        let mut expected = BTreeMap::new();
+        expected.insert(p1, 1);
+        expected.insert(p2, 1);
+
+        let config = FactorConfig::new().p_minus_1(false).p_plus_1(true).ecm(false);
+        let actual = config.factor(p1 * p2).into_map();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_ecm_factor() {
+        // 1099511627791 - 1 = 2 * 3 * 5 * 36650387593 is not smooth,
+        // so this is found by ECM
+        let p1 = 1099511627791_u128;
+        let p2 = 18446744073709551557_u128;
+
+        let mut expected = BTreeMap::new();
+        expected.insert(p1, 1);
+        expected.insert(p2, 1);
+
+        let actual = factor(p1 * p2).into_map();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[cfg(feature = "qs")]
+    #[test]
+    fn test_qs_factor() {
+        // a balanced 90-bit semiprime
+        let p1 = 28358983095227_u128;
+        let p2 = 29750830075391_u128;
+
+        let mut expected = BTreeMap::new();
+        expected.insert(p1, 1);
+        expected.insert(p2, 1);
+
+        let actual = factor(p1 * p2).into_map();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_fermat_factor() {
+        // two 64-bit primes close together
+        let p1 = 18446744073709551533_u128;
+        let p2 = 18446744073709551557_u128;
+
+        let mut expected = BTreeMap::new();
+        expected.insert(p1, 1);
+        expected.insert(p2, 1);
+
+        let actual = factor(p1 * p2).into_map();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_hart() {
+        let config = FactorConfig::new().hart(false);
+        for n in 1_000_000_000..1_000_002_000_u64 {
+            assert_eq!(config.factor(n), factor(n));
+        }
+    }
+
+    #[test]
+    fn test_lehman() {
+        let mut expected = BTreeMap::new();
+        expected.insert(3, 2);
+        expected.insert(1000003, 1);
+        expected.insert(4294967291, 1);
+
+        let config = FactorConfig::new().lehman(true);
+        let actual = config.factor(9 * 1000003 * 4294967291_u64).into_map();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_trial_division_limit() {
+        let config = FactorConfig::new().trial_division_limit(2).fermat(false).hart(false);
+        for n in 1..2000_u32 {
+            assert_eq!(config.factor(n), factor(n));
+        }
+        let config = FactorConfig::new().trial_division_limit(1000);
+        assert_eq!(factor(997 * 991 * 100003_u64), config.factor(997 * 991 * 100003_u64));
+        // beyond the shared prime table
+        let config = FactorConfig::new().trial_division_limit(300000);
+        assert_eq!(factor(299993 * 1000003_u64), config.factor(299993 * 1000003_u64));
+    }
+
+    #[test]
+    fn test_rho_disabled() {
+        // 37 bits and distant factors, which only rho finds
+        let n = 100003 * 1000033_u64;
+        assert_eq!(Err(FactorError::Incomplete), FactorConfig::new().rho(false).try_factor(n));
+        assert_eq!(factor(n), FactorConfig::new().lehman(true).rho(false).factor(n));
+    }
+
+    #[test]
+    fn test_rho_iterations() {
+        let n = 100003 * 1000033_u64;
+        let config = FactorConfig::new().rho_iterations(10);
+        assert_eq!(Err(FactorError::Incomplete), config.try_factor(n));
+        let config = FactorConfig::new().rho_iterations(100000);
+        assert_eq!(Ok(factor(n)), config.try_factor(n));
+    }
+
+    #[test]
+    fn test_find_large_factor_seeded() {
+        let cancel = AtomicBool::new(false);
+        let n = 100003 * 1000033_u64;
+        for seed in 0..20 {
+            let f = find_large_factor(n, u64::MAX, Some(seed), &cancel).unwrap();
+            assert!(f == 100003 || f == 1000033);
+        }
+        let composites = (10201..20000_u32)
+            .filter(|&n| !is_prime(n as u64))
+            .filter(|&n| (2..100).all(|d| n % d != 0));
+        for n in composites {
+            let f = find_large_factor(n, u64::MAX, Some(n as u64), &cancel).unwrap();
+            assert!(f > 1 && f < n && n % f == 0, "{} for {}", f, n);
+        }
+    }
+
+    #[test]
+    fn test_rho_seed() {
+        let n = 1000003 * 1000033 * 4294967291_u128;
+        let config = FactorConfig::new().rho_seed(12345);
+        assert_eq!(factor(n), config.factor(n));
+        assert_eq!(config.factor(n), config.factor(n));
+    }
+
+    #[test]
+    fn test_cancelled() {
+        let cancel = AtomicBool::new(true);
+        let config = FactorConfig::new();
+        assert_eq!(Ok(factor(1000_u32)), config.try_factor_cancellable(1000_u32, &cancel));
+        let n = 1099511627791_u128 * 18446744073709551557;
+        assert_eq!(Err(FactorError::Cancelled), config.try_factor_cancellable(n, &cancel));
+    }
+
+    #[test]
+    fn test_cancel_from_thread() {
+        // two 64-bit factors, which rho alone would take very long for
+        let n = 18446744073709551533_u128 * 18446744073709551557;
+        let config = FactorConfig::new().fermat(false).ecm(false);
+        let cancel = AtomicBool::new(false);
+        thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(10));
+                cancel.store(true, Ordering::Relaxed);
+            });
+            assert_eq!(Err(FactorError::Cancelled), config.try_factor_cancellable(n, &cancel));
+        });
+    }
+
+    #[test]
+    fn test_find_large_factor_cancel() {
+        // rho alone would take very long for two 64-bit factors
+        let n = 18446744073709551533_u128 * 18446744073709551557;
+        let cancel = AtomicBool::new(false);
+        thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(300));
+                cancel.store(true, Ordering::Relaxed);
+            });
+            let start = Instant::now();
+            assert_eq!(None, find_large_factor(n, u64::MAX, None, &cancel));
+            // the flag is noticed within a batch, not after as many steps again
+            assert!(start.elapsed() < Duration::from_millis(500));
+        });
+    }
+
+    #[test]
+    fn test_config_bounds() {
+        let config = FactorConfig::new()
+            .p_minus_1_bounds(100, 30000)
+            .p_plus_1(true)
+            .p_plus_1_bounds(2000, 40000)
+            .ecm(false);
+        assert_eq!(Some(&39989), config.primes().last());
+        assert_eq!(Some(&199999), FactorConfig::new().primes().last());
+        assert_eq!(Some(&19997), FactorConfig::new().ecm(false).primes().last());
+        assert!(FactorConfig::new().p_minus_1(false).ecm(false).primes().is_empty());
+    }
+
+    #[test]
+    fn test_p_minus_1_factor() {
+        // 1000081 - 1 = 2^4 * 3^3 * 5 * 463 is found by p-1,
+        // 2^64 - 59 is prime
+        let p1 = 1000081_u128;
+        let p2 = 18446744073709551557_u128;
+
+        let mut expected = BTreeMap::new();
+        expected.insert(p1, 1);
+        expected.insert(p2, 1);
+
+        let actual = factor(p1 * p2).into_map();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_try_factor() {
+        assert_eq!(Err(FactorError::Zero), try_factor(0_u64));
+        assert_eq!(Err(FactorError::Negative), try_factor(-6_i32));
+        assert_eq!(Ok(factor(600851475143_u64)), try_factor(600851475143_u64));
+    }
+
+    #[test]
+    fn test_signed_positive() {
+        let f = factor_signed(12_i32);
+        assert_eq!(1, *f.unit());
+        assert_eq!(factor(12_i32), *f.factorization());
+    }
+
+    #[test]
+    fn test_signed_negative() {
+        let mut expected = BTreeMap::new();
+        expected.insert(2, 2);
+        expected.insert(3, 1);
+
+        let f = factor_signed(-12_i32);
+
+        assert_eq!(-1, *f.unit());
+        assert_eq!(expected, f.factorization().clone().into_map());
+    }
+
+    #[test]
+    fn test_signed_minus_one() {
+        let f = factor_signed(-1_i64);
+        assert!(f.is_negative());
+        assert!(f.factorization().is_one());
+    }
+
+    #[test]
+    fn test_signed_min() {
+        let mut expected = BTreeMap::new();
+        expected.insert(2, 63);
+
+        let f = factor_signed(i64::MIN);
+
+        assert!(f.is_negative());
+        assert_eq!(expected, f.factorization().clone().into_map());
+        assert_eq!(Some(i64::MIN), f.value());
+    }
+
+    #[test]
+    fn test_signed_zero() {
+        assert_eq!(Err(FactorError::Zero), try_factor_signed(0_i64));
+    }
+
+    #[test]
+    fn test_signed_config() {
+        // 37 bits and distant factors, which only rho finds
+        let n = -(100003 * 1000033_i64);
+        let config = FactorConfig::new().rho(false);
+        assert_eq!(Err(FactorError::Incomplete), config.try_factor_signed(n));
+        let f = FactorConfig::new().try_factor_signed(n).unwrap();
+        assert_eq!(factor_signed(n), f);
+        let cancel = AtomicBool::new(true);
+        let n = -1099511627791_i128 * 18446744073709551557;
+        let result = FactorConfig::new().try_factor_signed_cancellable(n, &cancel);
+        assert_eq!(Err(FactorError::Cancelled), result);
+        assert_eq!(Err(FactorError::Zero), config.try_factor_signed(0_i32));
+    }
+
+    #[test]
+    fn test_small_types() {
+        let mut expected = BTreeMap::new();
+        expected.insert(2_u8, 2);
+        expected.insert(7, 1);
+        assert_eq!(expected, factor(28_u8).into_map());
+
+        let mut expected = BTreeMap::new();
+        expected.insert(2_u16, 2);
+        expected.insert(7, 1);
+        assert_eq!(expected, factor(28_u16).into_map());
+
+        let mut expected = BTreeMap::new();
+        expected.insert(2_usize, 2);
+        expected.insert(7, 1);
+        assert_eq!(expected, factor(28_usize).into_map());
+    }
+
+    #[test]
+    fn test_signed() {
+        let mut expected = BTreeMap::new();
+        expected.insert(3, 1);
+        expected.insert(2147483647, 1);
+        assert_eq!(expected, factor(3 * 2147483647_i64).into_map());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_negative() {
+        factor(-6_i32);
+    }
 }
 
 
@@ -265,6 +1430,16 @@ mod benchmarks {
         b.iter(|| factor(65521 * 65551));
     }
 
+    #[bench]
+    fn bench_factor_u64_high_composite(b: &mut Bencher) {
+        b.iter(|| factor(4294967279 * 4294967291_u64));
+    }
+
+    #[bench]
+    fn bench_factor_u128_high_composite(b: &mut Bencher) {
+        b.iter(|| factor(68719476767 * ((1_u128 << 89) - 1)));
+    }
+
     #[bench]
     fn bench_factor_low_prime(b: &mut Bencher) {
         b.iter(|| factor(997));
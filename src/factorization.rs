@@ -0,0 +1,735 @@
+
+extern crate num;
+extern crate num_bigint;
+
+use std::collections::btree_map;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Div, Mul, Sub};
+use std::str::FromStr;
+
+use self::num::{CheckedMul, One, Signed, ToPrimitive};
+use self::num_bigint::BigUint;
+
+use big::from_u128;
+
+
+/// The factorization of a positive integer into powers of primes.
+///
+/// The primes are kept in ascending order and all exponents are positive.
+/// The factorization of 1 is empty.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Factorization<T: Ord> {
+    factors: BTreeMap<T, u32>,
+}
+
+impl<T: Ord> Factorization<T> {
+    /// Returns the empty factorization, that of 1.
+    ///
+    pub fn new() -> Factorization<T> {
+        Factorization {
+            factors: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the distinct prime factors in ascending order.
+    ///
+    pub fn primes(&self) -> btree_map::Keys<'_, T, u32> {
+        self.factors.keys()
+    }
+
+    /// Returns the exponent of p in the factorization.
+    ///
+    /// This is zero if p is not a factor.
+    ///
+    pub fn exponent(&self, p: &T) -> u32 {
+        self.factors.get(p).cloned().unwrap_or(0)
+    }
+
+    /// Returns an iterator over the primes and their exponents.
+    ///
+    pub fn iter(&self) -> btree_map::Iter<'_, T, u32> {
+        self.factors.iter()
+    }
+
+    /// Returns true if this is the factorization of 1.
+    ///
+    pub fn is_one(&self) -> bool {
+        self.factors.is_empty()
+    }
+
+    /// Returns the number of distinct prime factors ω(n).
+    ///
+    pub fn omega(&self) -> u32 {
+        self.factors.len() as u32
+    }
+
+    /// Returns the number of prime factors Ω(n) counted with multiplicity.
+    ///
+    pub fn big_omega(&self) -> u32 {
+        self.factors.values().sum()
+    }
+
+    /// Returns the number of divisors, or None if it overflows u64.
+    ///
+    /// It fits if the factored integer fits into 128 bits.
+    ///
+    pub fn num_divisors(&self) -> Option<u64> {
+        self.factors
+            .values()
+            .try_fold(1_u64, |acc, &e| acc.checked_mul(e as u64 + 1))
+    }
+
+    /// Returns the radical, the product of the distinct prime factors.
+    ///
+    /// As it divides the factored integer it does not overflow
+    /// if the factored integer fits into T.
+    ///
+    pub fn radical(&self) -> T
+    where
+        T: Clone + One + Mul<Output = T>,
+    {
+        self.factors.keys().cloned().fold(T::one(), |r, p| r * p)
+    }
+
+    /// Returns Euler's totient φ(n) of the factored integer n,
+    /// the number of integers from 1 to n which are coprime to n.
+    ///
+    /// This is the product of (p - 1) p^(e - 1) over the prime powers p^e,
+    /// as it is at most n it does not overflow
+    /// if the factored integer fits into T.
+    ///
+    pub fn euler_phi(&self) -> T
+    where
+        T: Clone + One + Sub<Output = T> + Mul<Output = T>,
+    {
+        let mut phi = T::one();
+        for (p, &e) in &self.factors {
+            phi = phi * (p.clone() - T::one());
+            for _ in 1..e {
+                phi = phi * p.clone();
+            }
+        }
+        phi
+    }
+
+    /// Returns the sum of the k-th powers of the divisors σ_k(n)
+    /// of the factored integer n as a big integer.
+    ///
+    /// This is the product of 1 + p^k + ... + p^(e k) over the prime powers p^e.
+    /// The primes must fit into u128.
+    ///
+    pub fn sigma_k_big(&self, k: u32) -> BigUint
+    where
+        T: ToPrimitive,
+    {
+        let mut sum = BigUint::one();
+        for (p, &e) in &self.factors {
+            let pk = num::pow(from_u128(p.to_u128().unwrap()), k as usize);
+            let mut power = BigUint::one();
+            let mut term = BigUint::one();
+            for _ in 0..e {
+                power *= &pk;
+                term += &power;
+            }
+            sum *= term;
+        }
+        sum
+    }
+
+    /// Evaluates the multiplicative function given by its values
+    /// f(p, e) on the prime powers p^e at the factored integer.
+    ///
+    /// This is the product of f(p, e) over the prime powers in the
+    /// factorization, so 1 gives the neutral element.
+    ///
+    pub fn evaluate_multiplicative<U, F>(&self, mut f: F) -> U
+    where
+        T: Clone,
+        U: One + Mul<Output = U>,
+        F: FnMut(T, u32) -> U,
+    {
+        self.factors
+            .iter()
+            .fold(U::one(), |product, (p, &e)| product * f(p.clone(), e))
+    }
+
+    /// Returns the map from primes to exponents.
+    ///
+    pub fn as_map(&self) -> &BTreeMap<T, u32> {
+        &self.factors
+    }
+
+    /// Converts into the map from primes to exponents.
+    ///
+    pub fn into_map(self) -> BTreeMap<T, u32> {
+        self.factors
+    }
+
+    /// Multiplies by p^e, where p must be prime.
+    ///
+    /// Panics if the exponent overflows.
+    ///
+    pub(crate) fn insert(&mut self, p: T, e: u32) {
+        if e != 0 {
+            let exponent = self.factors.entry(p).or_insert(0);
+            *exponent = exponent.checked_add(e).expect("exponent overflow");
+        }
+    }
+
+    /// Returns the factorization of the greatest common divisor,
+    /// taking the smaller exponent of each prime.
+    ///
+    pub fn gcd(&self, other: &Factorization<T>) -> Factorization<T>
+    where
+        T: Clone,
+    {
+        Factorization {
+            factors: self
+                .factors
+                .iter()
+                .filter_map(|(p, &e)| match other.exponent(p).min(e) {
+                    0 => None,
+                    e => Some((p.clone(), e)),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the factorization of the least common multiple,
+    /// taking the larger exponent of each prime.
+    ///
+    pub fn lcm(&self, other: &Factorization<T>) -> Factorization<T>
+    where
+        T: Clone,
+    {
+        let mut result = self.clone();
+        for (p, &e) in &other.factors {
+            let re = result.factors.entry(p.clone()).or_insert(0);
+            *re = (*re).max(e);
+        }
+        result
+    }
+
+    /// Returns the factorization of the k-th power.
+    ///
+    /// Panics if an exponent overflows.
+    ///
+    pub fn pow(&self, k: u32) -> Factorization<T>
+    where
+        T: Clone,
+    {
+        if k == 0 {
+            return Factorization::new();
+        }
+        Factorization {
+            factors: self.factors
+                .iter()
+                .map(|(p, &e)| (p.clone(), e.checked_mul(k).expect("exponent overflow")))
+                .collect(),
+        }
+    }
+}
+
+impl<T: Ord + Clone + One + CheckedMul> Factorization<T> {
+    /// Returns the factored integer, or None if it overflows T.
+    ///
+    pub fn value(&self) -> Option<T> {
+        let mut result = T::one();
+        for (p, &e) in &self.factors {
+            for _ in 0..e {
+                result = result.checked_mul(p)?;
+            }
+        }
+        Some(result)
+    }
+}
+
+/// Creates a factorization from a map from primes to exponents.
+///
+/// The keys must be prime, zero exponents are dropped.
+///
+impl<T: Ord> From<BTreeMap<T, u32>> for Factorization<T> {
+    fn from(map: BTreeMap<T, u32>) -> Factorization<T> {
+        Factorization {
+            factors: map.into_iter().filter(|&(_, e)| e != 0).collect(),
+        }
+    }
+}
+
+impl<T: Ord> IntoIterator for Factorization<T> {
+    type Item = (T, u32);
+    type IntoIter = btree_map::IntoIter<T, u32>;
+
+    fn into_iter(self) -> btree_map::IntoIter<T, u32> {
+        self.factors.into_iter()
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a Factorization<T> {
+    type Item = (&'a T, &'a u32);
+    type IntoIter = btree_map::Iter<'a, T, u32>;
+
+    fn into_iter(self) -> btree_map::Iter<'a, T, u32> {
+        self.factors.iter()
+    }
+}
+
+/// Multiplies factorizations by adding exponents.
+///
+/// Panics if an exponent overflows.
+///
+impl<T: Ord> Mul for Factorization<T> {
+    type Output = Factorization<T>;
+
+    fn mul(self, other: Factorization<T>) -> Factorization<T> {
+        let mut result = self;
+        for (p, e) in other.factors {
+            result.insert(p, e);
+        }
+        result
+    }
+}
+
+impl<'b, T: Ord + Clone> Mul<&'b Factorization<T>> for &Factorization<T> {
+    type Output = Factorization<T>;
+
+    fn mul(self, other: &'b Factorization<T>) -> Factorization<T> {
+        let mut result = self.clone();
+        for (p, &e) in &other.factors {
+            result.insert(p.clone(), e);
+        }
+        result
+    }
+}
+
+/// Divides factorizations by subtracting exponents.
+///
+/// The division is exact, if the divisor does not divide the dividend
+/// the result is an error.
+///
+impl<T: Ord> Div for Factorization<T> {
+    type Output = Result<Factorization<T>, NotDivisibleError>;
+
+    fn div(self, other: Factorization<T>) -> Result<Factorization<T>, NotDivisibleError> {
+        let mut result = self;
+        for (p, e) in other.factors {
+            match result.factors.get_mut(&p) {
+                Some(ref mut re) if **re >= e => **re -= e,
+                _ => return Err(NotDivisibleError),
+            }
+        }
+        result.factors.retain(|_, &mut e| e != 0);
+        Ok(result)
+    }
+}
+
+impl<'b, T: Ord + Clone> Div<&'b Factorization<T>> for &Factorization<T> {
+    type Output = Result<Factorization<T>, NotDivisibleError>;
+
+    fn div(self, other: &'b Factorization<T>) -> Result<Factorization<T>, NotDivisibleError> {
+        self.clone() / other.clone()
+    }
+}
+
+
+/// The error returned when dividing factorizations which are not divisible.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NotDivisibleError;
+
+impl fmt::Display for NotDivisibleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "divisor does not divide dividend")
+    }
+}
+
+impl Error for NotDivisibleError {}
+
+
+/// Formats a factorization like `2^3 · 5 · 7`.
+///
+/// The factorization of 1 is formatted as `1`.
+///
+impl<T: Ord + fmt::Display> fmt::Display for Factorization<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.factors.is_empty() {
+            return write!(f, "1");
+        }
+        let mut first = true;
+        for (p, &e) in &self.factors {
+            if !first {
+                write!(f, " · ")?;
+            }
+            first = false;
+            if e == 1 {
+                write!(f, "{}", p)?;
+            } else {
+                write!(f, "{}^{}", p, e)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/// The factorization of a nonzero signed integer.
+///
+/// The integer is the product of a unit, 1 or -1, and its factorization
+/// into powers of positive primes.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SignedFactorization<T: Ord> {
+    unit: T,
+    factorization: Factorization<T>,
+}
+
+impl<T: Ord + Signed> SignedFactorization<T> {
+    /// Creates a signed factorization from a unit and a factorization.
+    ///
+    /// The unit must be 1 or -1.
+    ///
+    pub fn new(unit: T, factorization: Factorization<T>) -> SignedFactorization<T> {
+        assert!(unit == T::one() || unit == -T::one());
+        SignedFactorization {
+            unit,
+            factorization,
+        }
+    }
+
+    /// Returns the unit, which is 1 or -1.
+    ///
+    pub fn unit(&self) -> &T {
+        &self.unit
+    }
+
+    /// Returns true if the factored integer is negative.
+    ///
+    pub fn is_negative(&self) -> bool {
+        self.unit.is_negative()
+    }
+
+    /// Returns the factorization of the absolute value.
+    ///
+    pub fn factorization(&self) -> &Factorization<T> {
+        &self.factorization
+    }
+
+    /// Splits into the unit and the factorization of the absolute value.
+    ///
+    pub fn into_parts(self) -> (T, Factorization<T>) {
+        (self.unit, self.factorization)
+    }
+}
+
+impl<T: Ord + Signed + Clone + CheckedMul> SignedFactorization<T> {
+    /// Returns the factored integer, or None if it overflows T.
+    ///
+    pub fn value(&self) -> Option<T> {
+        // starting with the unit allows the most negative value
+        let mut result = self.unit.clone();
+        for (p, &e) in &self.factorization {
+            for _ in 0..e {
+                result = result.checked_mul(p)?;
+            }
+        }
+        Some(result)
+    }
+}
+
+/// Formats a signed factorization like `-1 · 2^3 · 5`.
+///
+/// The unit is only shown for negative integers.
+///
+impl<T: Ord + Signed + fmt::Display> fmt::Display for SignedFactorization<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.is_negative() {
+            write!(f, "{}", self.factorization)
+        } else if self.factorization.is_one() {
+            write!(f, "-1")
+        } else {
+            write!(f, "-1 · {}", self.factorization)
+        }
+    }
+}
+
+
+/// Parses a factorization like `2^3*5*7^2` or `2^3 · 5 · 7^2`.
+///
+/// Factors are separated by `*` or `·` and may be surrounded by whitespace,
+/// repeated factors are combined.
+/// The factorization of 1 is written as `1`.
+/// The factors are not checked for primality,
+/// but factors less than 2 are an error.
+///
+impl<T: Ord + FromStr + One> FromStr for Factorization<T> {
+    type Err = ParseFactorizationError;
+
+    fn from_str(s: &str) -> Result<Factorization<T>, ParseFactorizationError> {
+        let mut result = Factorization::new();
+        if s.trim() == "1" {
+            return Ok(result);
+        }
+        for term in s.split(&['*', '·'][..]) {
+            let mut parts = term.trim().splitn(2, '^');
+            let p = parts.next().unwrap().trim();
+            let e = match parts.next() {
+                Some(e) => e.trim().parse().map_err(|_| ParseFactorizationError)?,
+                None => 1,
+            };
+            let p: T = p.parse().map_err(|_| ParseFactorizationError)?;
+            if p <= T::one() {
+                return Err(ParseFactorizationError);
+            }
+            if e != 0 {
+                let exponent = result.factors.entry(p).or_insert(0);
+                *exponent = exponent.checked_add(e).ok_or(ParseFactorizationError)?;
+            }
+        }
+        Ok(result)
+    }
+}
+
+
+/// The error returned when parsing a factorization fails.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseFactorizationError;
+
+impl fmt::Display for ParseFactorizationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid factorization syntax")
+    }
+}
+
+impl Error for ParseFactorizationError {}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn example() -> Factorization<u32> {
+        let mut map = BTreeMap::new();
+        map.insert(2, 3);
+        map.insert(5, 1);
+        map.insert(7, 1);
+        Factorization::from(map)
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!("2^3 · 5 · 7", example().to_string());
+        assert_eq!("1", Factorization::<u32>::new().to_string());
+    }
+
+    #[test]
+    fn test_primes() {
+        assert_eq!(vec![2, 5, 7], example().primes().cloned().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_exponent() {
+        let f = example();
+        assert_eq!(3, f.exponent(&2));
+        assert_eq!(0, f.exponent(&3));
+        assert_eq!(1, f.exponent(&7));
+    }
+
+    #[test]
+    fn test_omega() {
+        assert_eq!(3, example().omega());
+        assert_eq!(5, example().big_omega());
+        assert_eq!(0, Factorization::<u32>::new().omega());
+        assert_eq!(0, Factorization::<u32>::new().big_omega());
+    }
+
+    #[test]
+    fn test_num_divisors() {
+        assert_eq!(Some(16), example().num_divisors());
+        assert_eq!(Some(1), Factorization::<u32>::new().num_divisors());
+        // (10^8 + 1)^6 overflows u64
+        let f = from_pairs(&[(2, 1), (3, 1), (5, 1), (7, 1), (11, 1), (13, 1)]);
+        assert_eq!(None, f.pow(100_000_000).num_divisors());
+        let f = from_pairs(&[(2, 1), (3, 1)]);
+        assert_eq!(Some(100_000_001_u64.pow(2)), f.pow(100_000_000).num_divisors());
+    }
+
+    #[test]
+    fn test_radical() {
+        assert_eq!(70, example().radical());
+        assert_eq!(1, Factorization::<u32>::new().radical());
+    }
+
+    #[test]
+    fn test_gcd_lcm() {
+        // 280 = 2^3 · 5 · 7 and 1050 = 2 · 3 · 5^2 · 7
+        let mut map = BTreeMap::new();
+        map.insert(2, 1);
+        map.insert(3, 1);
+        map.insert(5, 2);
+        map.insert(7, 1);
+        let other = Factorization::from(map);
+        assert_eq!(Some(70), example().gcd(&other).value());
+        assert_eq!(Some(4200), example().lcm(&other).value());
+        let one = Factorization::new();
+        assert!(example().gcd(&one).is_one());
+        assert_eq!(example(), example().lcm(&one));
+        assert_eq!(example(), one.lcm(&example()));
+    }
+
+    #[test]
+    fn test_euler_phi() {
+        assert_eq!(96, example().euler_phi());
+        assert_eq!(1, Factorization::<u32>::new().euler_phi());
+    }
+
+    #[test]
+    fn test_sigma_k_big() {
+        assert_eq!(BigUint::from(16_u32), example().sigma_k_big(0));
+        assert_eq!(BigUint::from(720_u32), example().sigma_k_big(1));
+        assert_eq!(BigUint::from(1_u32), Factorization::<u32>::new().sigma_k_big(1));
+    }
+
+    #[test]
+    fn test_evaluate_multiplicative() {
+        // the number of divisors
+        assert_eq!(16, example().evaluate_multiplicative(|_, e| e + 1));
+        assert_eq!(1.0, Factorization::<u32>::new().evaluate_multiplicative(|p, _| p as f64));
+    }
+
+    #[test]
+    fn test_value() {
+        assert_eq!(Some(280), example().value());
+        assert_eq!(Some(1), Factorization::<u32>::new().value());
+
+        let mut map = BTreeMap::new();
+        map.insert(2_u32, 32);
+        assert_eq!(None, Factorization::from(map).value());
+    }
+
+    fn from_pairs(pairs: &[(u32, u32)]) -> Factorization<u32> {
+        Factorization::from(pairs.iter().cloned().collect::<BTreeMap<_, _>>())
+    }
+
+    #[test]
+    fn test_mul() {
+        let a = from_pairs(&[(2, 3), (5, 1)]);
+        let b = from_pairs(&[(3, 1), (5, 2)]);
+        let expected = from_pairs(&[(2, 3), (3, 1), (5, 3)]);
+        assert_eq!(expected, &a * &b);
+        assert_eq!(expected, a * b);
+    }
+
+    #[test]
+    #[should_panic(expected = "exponent overflow")]
+    fn test_mul_overflow() {
+        let a = from_pairs(&[(2, u32::MAX), (5, 1)]);
+        let _ = &a * &from_pairs(&[(2, 1)]);
+    }
+
+    #[test]
+    fn test_mul_one() {
+        assert_eq!(example(), example() * Factorization::new());
+    }
+
+    #[test]
+    fn test_div() {
+        let a = from_pairs(&[(2, 3), (5, 1), (7, 1)]);
+        let b = from_pairs(&[(2, 1), (5, 1)]);
+        assert_eq!(Ok(from_pairs(&[(2, 2), (7, 1)])), &a / &b);
+        assert_eq!(Ok(Factorization::new()), a.clone() / a);
+    }
+
+    #[test]
+    fn test_div_not_divisible() {
+        let a = from_pairs(&[(2, 3), (5, 1)]);
+        assert_eq!(Err(NotDivisibleError), &a / &from_pairs(&[(2, 4)]));
+        assert_eq!(Err(NotDivisibleError), &a / &from_pairs(&[(3, 1)]));
+    }
+
+    #[test]
+    fn test_pow() {
+        assert_eq!(from_pairs(&[(2, 9), (5, 3), (7, 3)]), example().pow(3));
+        assert_eq!(Factorization::new(), example().pow(0));
+    }
+
+    #[test]
+    fn test_pow_no_overflow() {
+        // 2^(2^31) is far too large to compute
+        let f = from_pairs(&[(2, 1 << 16)]).pow(1 << 15);
+        assert_eq!(1 << 31, f.exponent(&2));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Ok(from_pairs(&[(2, 3), (5, 1), (7, 2)])), "2^3*5*7^2".parse());
+        assert_eq!(Ok(from_pairs(&[(2, 3), (5, 1), (7, 2)])), "2^3·5·7^2".parse());
+        assert_eq!(Ok(from_pairs(&[(2, 3), (5, 1), (7, 2)])), " 2 ^ 3 * 5 * 7^2 ".parse());
+        assert_eq!(Ok(from_pairs(&[(13, 1)])), "13".parse());
+        assert_eq!(Ok(Factorization::new()), "1".parse::<Factorization<u32>>());
+    }
+
+    #[test]
+    fn test_parse_repeated() {
+        assert_eq!(Ok(from_pairs(&[(2, 3), (3, 1)])), "2*3*2^2".parse());
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let f = example();
+        assert_eq!(Ok(f.clone()), f.to_string().parse());
+    }
+
+    #[test]
+    fn test_parse_error() {
+        for s in &["", "2*", "*3", "2^", "2^x", "a", "2**3", "2^3^4", "-2"] {
+            assert_eq!(Err(ParseFactorizationError), s.parse::<Factorization<u32>>());
+        }
+        // 0 and 1 are not prime factors, only the whole "1" is allowed
+        for s in &["0", "1*2", "1^3*5", "2*1", "0^2"] {
+            assert_eq!(Err(ParseFactorizationError), s.parse::<Factorization<u32>>());
+        }
+        assert_eq!(Err(ParseFactorizationError), "-3*5".parse::<Factorization<i32>>());
+        // the exponents must not overflow when combined
+        assert_eq!(Err(ParseFactorizationError), "2^4294967295*2".parse::<Factorization<u32>>());
+        assert_eq!(Ok(from_pairs(&[(5, 1)])), "2^0*5".parse());
+    }
+
+    fn signed(unit: i64, pairs: &[(i64, u32)]) -> SignedFactorization<i64> {
+        let map = pairs.iter().cloned().collect::<BTreeMap<_, _>>();
+        SignedFactorization::new(unit, Factorization::from(map))
+    }
+
+    #[test]
+    fn test_signed_display() {
+        assert_eq!("-1 · 2^3 · 5", signed(-1, &[(2, 3), (5, 1)]).to_string());
+        assert_eq!("2^3 · 5", signed(1, &[(2, 3), (5, 1)]).to_string());
+        assert_eq!("-1", signed(-1, &[]).to_string());
+        assert_eq!("1", signed(1, &[]).to_string());
+    }
+
+    #[test]
+    fn test_signed_value() {
+        assert_eq!(Some(-40), signed(-1, &[(2, 3), (5, 1)]).value());
+        assert_eq!(Some(i64::MIN), signed(-1, &[(2, 63)]).value());
+        assert_eq!(None, signed(1, &[(2, 63)]).value());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_signed_bad_unit() {
+        signed(2, &[]);
+    }
+
+    #[test]
+    fn test_zero_exponent() {
+        let mut map = BTreeMap::new();
+        map.insert(2_u32, 0);
+        assert!(Factorization::from(map).is_one());
+    }
+}
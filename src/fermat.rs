@@ -0,0 +1,128 @@
+
+extern crate num;
+
+use self::num::{Integer, NumCast, PrimInt};
+
+use arith::{exact_sqrt, Word};
+use primality::is_prime_word;
+
+
+/// Finds a factor using Fermat's difference of squares method.
+///
+/// This searches for x with x^2 - n = y^2, which gives n = (x - y)(x + y).
+/// It starts from the square root of n, so the number of steps is about
+/// (p - q)^2 / (8 sqrt(n)) for the factors p > q closest to sqrt(n).
+/// This makes it very fast if n has two factors close to sqrt(n),
+/// and very slow otherwise.
+///
+/// Returns None if n is less than 4 or prime,
+/// or for 128-bit n if x^2 - n overflows.
+/// The returned factor may be composite.
+///
+pub fn fermat_factor<T: PrimInt + Integer>(n: T) -> Option<T> {
+    // x^2 - n grows up to about n^2 / 36 for n = 3 p,
+    // so the search is done one word size up
+    if n < NumCast::from(4).unwrap() {
+        None
+    } else if let Some(n) = n.to_u32() {
+        fermat_word(n as u64).map(|f| NumCast::from(f).unwrap())
+    } else if let Some(n) = n.to_u64() {
+        fermat_word(n as u128).map(|f| NumCast::from(f).unwrap())
+    } else {
+        fermat_word(n.to_u128().unwrap()).map(|f| NumCast::from(f).unwrap())
+    }
+}
+
+
+/// Finds a factor of a machine word using Fermat's method.
+///
+/// n must be at least 4.
+///
+fn fermat_word<T: Word>(n: T) -> Option<T> {
+    if is_prime_word(n) {
+        None
+    } else {
+        fermat_with_limit(n, u64::MAX)
+    }
+}
+
+
+/// Finds a factor of a machine word using Fermat's method,
+/// trying at most the given number of steps.
+///
+/// n must be at least 4.
+/// Returns None if x^2 - n overflows.
+///
+pub(crate) fn fermat_with_limit<T: Word>(n: T, iterations: u64) -> Option<T> {
+    let one = T::one();
+    if n & one == T::zero() {
+        return Some(NumCast::from(2).unwrap());
+    }
+    let s = n.sqrt();
+    if s * s == n {
+        return Some(s);
+    }
+
+    // x starts at the ceiling of the square root, r = x^2 - n,
+    // computed without overflow as s^2 < n < (s + 1)^2
+    let mut x = s + one;
+    let mut r = s + s + one - (n - s * s);
+    for _ in 0..iterations {
+        if let Some(y) = exact_sqrt(r) {
+            // x - y = 1 means n = x + y is prime
+            let f = x - y;
+            return if f != one { Some(f) } else { None };
+        }
+        r = r.checked_add(&(x + x + one))?;
+        x = x + one;
+    }
+    None
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_factors() {
+        assert_eq!(Some(1000003), fermat_factor(1000003_u64 * 1000033));
+        assert_eq!(Some(1000003), fermat_with_limit(1000003_u64 * 1000033, 1));
+    }
+
+    #[test]
+    fn test_u128() {
+        // two 64-bit primes close together
+        let p = 18446744073709551533_u128;
+        let q = 18446744073709551557_u128;
+        assert_eq!(Some(p), fermat_factor(p * q));
+    }
+
+    #[test]
+    fn test_distant_factors() {
+        assert_eq!(Some(3), fermat_factor(3 * 10007_u32));
+        assert_eq!(None, fermat_with_limit(3 * 10007_u32, 10));
+        // x^2 - n exceeds 32 bits before the factor is found
+        assert_eq!(Some(20011), fermat_factor(20011 * 214607_u32));
+        assert_eq!(Some(20011), fermat_factor(20011 * 214607_i64));
+    }
+
+    #[test]
+    fn test_square() {
+        assert_eq!(Some(1000003), fermat_factor(1000003_u64 * 1000003));
+    }
+
+    #[test]
+    fn test_small() {
+        assert_eq!(None, fermat_factor(3_u32));
+        assert_eq!(Some(2), fermat_factor(4_u32));
+        assert_eq!(Some(2), fermat_factor(1000_u32));
+        assert_eq!(Some(3), fermat_factor(15_u32));
+    }
+
+    #[test]
+    fn test_prime() {
+        assert_eq!(None, fermat_factor(1000003_u32));
+        assert_eq!(None, fermat_with_limit(7_u32, 100));
+    }
+}
@@ -0,0 +1,99 @@
+//! C bindings.
+//!
+//! This is only available with the `ffi` feature.
+//! The declarations for C are in `include/jfactor.h`,
+//! a library for linking can be built with
+//! `cargo rustc --release --features ffi --crate-type cdylib`
+//! or `--crate-type staticlib`.
+
+use std::slice;
+
+use factor::try_factor;
+
+
+/// Factors n into primes and exponents.
+///
+/// Writes the distinct prime factors of n in ascending order to
+/// out_primes and their exponents to out_exps, up to cap of them.
+/// Returns the number of distinct prime factors, which may be larger
+/// than cap, in which case only the first cap are written.
+/// 15 is enough for all 64-bit integers.
+/// Returns -1 if n is 0.
+///
+/// # Safety
+///
+/// out_primes and out_exps must point to arrays of at least cap elements,
+/// they may be null if cap is 0.
+///
+#[no_mangle]
+pub unsafe extern "C" fn jfactor_factor_u64(
+    n: u64,
+    out_primes: *mut u64,
+    out_exps: *mut u32,
+    cap: usize,
+) -> isize {
+    let factorization = match try_factor(n) {
+        Ok(factorization) => factorization,
+        Err(_) => return -1,
+    };
+    if cap > 0 {
+        let primes = slice::from_raw_parts_mut(out_primes, cap);
+        let exps = slice::from_raw_parts_mut(out_exps, cap);
+        for (i, (&p, &e)) in factorization.iter().take(cap).enumerate() {
+            primes[i] = p;
+            exps[i] = e;
+        }
+    }
+    factorization.as_map().len() as isize
+}
+
+
+/// Returns the number of divisors of n, or 0 if n is 0.
+///
+#[no_mangle]
+pub extern "C" fn jfactor_num_divisors_u64(n: u64) -> u64 {
+    try_factor(n).map_or(0, |factorization| factorization.num_divisors().unwrap())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    #[test]
+    fn test_factor_u64() {
+        let mut primes = [0_u64; 15];
+        let mut exps = [0_u32; 15];
+        let len = unsafe { jfactor_factor_u64(360, primes.as_mut_ptr(), exps.as_mut_ptr(), 15) };
+        assert_eq!(3, len);
+        assert_eq!([2, 3, 5], primes[..3]);
+        assert_eq!([3, 2, 1], exps[..3]);
+    }
+
+    #[test]
+    fn test_factor_u64_short() {
+        let mut primes = [0_u64; 2];
+        let mut exps = [0_u32; 2];
+        let len = unsafe { jfactor_factor_u64(30, primes.as_mut_ptr(), exps.as_mut_ptr(), 2) };
+        assert_eq!(3, len);
+        assert_eq!([2, 3], primes);
+        assert_eq!([1, 1], exps);
+    }
+
+    #[test]
+    fn test_factor_u64_edge_cases() {
+        let null_primes = ptr::null_mut();
+        let null_exps = ptr::null_mut();
+        assert_eq!(-1, unsafe { jfactor_factor_u64(0, null_primes, null_exps, 0) });
+        assert_eq!(0, unsafe { jfactor_factor_u64(1, null_primes, null_exps, 0) });
+        assert_eq!(2, unsafe { jfactor_factor_u64(6, null_primes, null_exps, 0) });
+    }
+
+    #[test]
+    fn test_num_divisors_u64() {
+        assert_eq!(24, jfactor_num_divisors_u64(360));
+        assert_eq!(0, jfactor_num_divisors_u64(0));
+    }
+}
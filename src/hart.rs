@@ -0,0 +1,98 @@
+
+extern crate num;
+
+use self::num::integer::{gcd, Roots};
+
+use arith::exact_sqrt;
+
+
+/// The multiplier of n, which makes squares modulo n more likely.
+const MULTIPLIER: u128 = 480;
+
+/// The number of steps after which the method gives up,
+/// as a multiple of the cube root of n.
+const ITERATIONS_PER_CUBE_ROOT: u64 = 4;
+
+
+/// Finds a factor using Hart's one line factoring method.
+///
+/// This computes s = ceil(sqrt(m n i)) for a fixed multiplier m and
+/// i = 1, 2, 3, ... until s^2 mod n is a square t^2,
+/// which gives the factor gcd(s - t, n).
+/// It is very simple and fast for numbers up to about 32 bits,
+/// with a runtime on the order of the cube root of n.
+///
+/// Returns None if no nontrivial factor was found,
+/// in particular if n is less than 4 or prime.
+/// The returned factor may be composite.
+///
+pub fn hart_factor(n: u64) -> Option<u64> {
+    if n < 4 {
+        return None;
+    }
+    if n & 1 == 0 {
+        return Some(2);
+    }
+    if let Some(s) = exact_sqrt(n) {
+        return Some(s);
+    }
+
+    let max_iterations = ITERATIONS_PER_CUBE_ROOT * (n.cbrt() + 1);
+    let n = n as u128;
+    for i in 1..=max_iterations as u128 {
+        let product = MULTIPLIER * n * i;
+        let mut s = product.sqrt();
+        if s * s != product {
+            s += 1;
+        }
+        if let Some(t) = exact_sqrt(s * s % n) {
+            let f = gcd(s - t, n);
+            if f != 1 && f != n {
+                return Some(f as u64);
+            }
+        }
+    }
+    None
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_factor(n: u64) {
+        let f = hart_factor(n).unwrap();
+        assert!(f != 1 && f != n);
+        assert_eq!(0, n % f);
+    }
+
+    #[test]
+    fn test_semiprimes() {
+        check_factor(101 * 103);
+        check_factor(10007 * 10009);
+        check_factor(1000003 * 1000033);
+        check_factor(65537 * 4294967291);
+    }
+
+    #[test]
+    fn test_small() {
+        assert_eq!(None, hart_factor(3));
+        assert_eq!(Some(2), hart_factor(4));
+        for n in 5..2000_u64 {
+            if let Some(f) = hart_factor(n) {
+                assert!(f != 1 && f != n);
+                assert_eq!(0, n % f);
+            }
+        }
+    }
+
+    #[test]
+    fn test_square() {
+        assert_eq!(Some(1000003), hart_factor(1000003 * 1000003));
+    }
+
+    #[test]
+    fn test_prime() {
+        assert_eq!(None, hart_factor(10007));
+    }
+}
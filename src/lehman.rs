@@ -0,0 +1,109 @@
+
+extern crate num;
+
+use self::num::integer::{gcd, Roots};
+
+use arith::exact_sqrt;
+
+
+/// Finds a factor using Lehman's method.
+///
+/// After trial division up to the cube root of n this searches for
+/// a^2 - 4 k n = b^2 for small k and a close to sqrt(4 k n),
+/// which gives the factor gcd(a + b, n).
+/// Unlike rho this is deterministic with a worst case runtime of
+/// O(n^(1/3)), which is up to about a tenth of a second for 64-bit
+/// numbers, while most composites are factored much faster.
+///
+/// Returns None if and only if n is less than 4 or prime.
+/// The returned factor may be composite.
+///
+pub fn lehman_factor(n: u64) -> Option<u64> {
+    if n < 4 {
+        return None;
+    }
+    if n & 1 == 0 {
+        return Some(2);
+    }
+
+    let cube_root = n.cbrt();
+    let mut d = 3;
+    while d <= cube_root {
+        if n.is_multiple_of(d) {
+            return Some(d);
+        }
+        d += 2;
+    }
+
+    // the search bound for a is sqrt(4 k n) + n^(1/6) / (4 sqrt(k))
+    let sixth_root = (n as f64).powf(1.0 / 6.0);
+    let n = n as u128;
+    for k in 1..=(cube_root as u128 + 1) {
+        let four_kn = 4 * k * n;
+        let s = four_kn.sqrt();
+        let a_min = if s * s == four_kn { s } else { s + 1 };
+        // one more to allow for rounding errors
+        let a_max = s + (sixth_root / (4.0 * (k as f64).sqrt())) as u128 + 1;
+        for a in a_min..=a_max {
+            if let Some(b) = exact_sqrt(a * a - four_kn) {
+                let f = gcd(a + b, n);
+                if f != 1 && f != n {
+                    return Some(f as u64);
+                }
+            }
+        }
+    }
+    None
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trial_division() {
+        assert_eq!(Some(3), lehman_factor(3 * 1000003));
+        let f = lehman_factor(101 * 103);
+        assert!(f == Some(101) || f == Some(103));
+    }
+
+    #[test]
+    fn test_semiprimes() {
+        for &(p, q) in &[
+            (1000003_u64, 1000033),
+            (1000003, 4294967291),
+            (65537, 4294967291),
+            (2147483647, 4294967291),
+        ] {
+            let f = lehman_factor(p * q);
+            assert!(f == Some(p) || f == Some(q));
+        }
+    }
+
+    #[test]
+    fn test_square() {
+        assert_eq!(Some(1000003), lehman_factor(1000003 * 1000003));
+    }
+
+    #[test]
+    fn test_prime() {
+        assert_eq!(None, lehman_factor(1));
+        assert_eq!(None, lehman_factor(3));
+        assert_eq!(None, lehman_factor(1000003));
+        assert_eq!(None, lehman_factor(4294967291));
+    }
+
+    #[test]
+    fn test_small() {
+        assert_eq!(Some(2), lehman_factor(4));
+        for n in 5..2000_u64 {
+            if let Some(f) = lehman_factor(n) {
+                assert!(f != 1 && f != n);
+                assert_eq!(0, n % f);
+            } else {
+                assert!((2..n).all(|d| n % d != 0));
+            }
+        }
+    }
+}
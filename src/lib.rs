@@ -10,8 +10,80 @@ extern crate test;
 extern crate num;
 
 
+#[cfg(feature = "aks")]
+mod aks;
+mod arith;
+mod batch;
+mod big;
+mod cache;
+mod ecm;
 mod factor;
+mod factorization;
+mod fermat;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod hart;
+mod lehman;
+mod p_minus_1;
+mod p_plus_1;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod pocklington;
+mod pratt;
+mod primality;
+mod properties;
+#[cfg(feature = "qs")]
+mod qs;
+#[cfg(feature = "rand")]
+mod random;
+mod range;
+mod relations;
+mod roots;
+#[cfg(feature = "serde")]
+mod serialization;
+mod sieve;
+mod squfof;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 mod divisors;
 mod multiplicative_functions;
+mod modular;
+mod continued_fraction;
+mod cfrac;
+mod special_forms;
 
+#[cfg(feature = "aks")]
+pub use aks::*;
+pub use batch::*;
+pub use big::*;
+pub use cache::*;
+pub use ecm::*;
 pub use factor::*;
+pub use factorization::*;
+pub use fermat::*;
+pub use hart::*;
+pub use lehman::*;
+pub use p_minus_1::*;
+pub use p_plus_1::*;
+#[cfg(feature = "parallel")]
+pub use parallel::*;
+pub use pocklington::*;
+pub use pratt::*;
+pub use primality::*;
+pub use properties::*;
+#[cfg(feature = "qs")]
+pub use qs::*;
+#[cfg(feature = "rand")]
+pub use random::*;
+pub use range::*;
+pub use roots::*;
+#[cfg(feature = "serde")]
+pub use serialization::*;
+pub use sieve::*;
+pub use squfof::*;
+pub use divisors::*;
+pub use multiplicative_functions::*;
+pub use modular::*;
+pub use continued_fraction::*;
+pub use cfrac::*;
+pub use special_forms::*;
@@ -0,0 +1,1228 @@
+
+extern crate num;
+
+use std::collections::HashMap;
+
+use self::num::{Integer, NumCast, PrimInt};
+
+use arith::{Modulus, MontgomeryModulus64, PlainModulus, Word};
+use factor::factor;
+use multiplicative_functions::carmichael_lambda;
+use primality::jacobi;
+
+
+/// Returns base^exp mod n.
+///
+/// This uses repeated squaring with Montgomery multiplication
+/// for odd n, so it does not overflow for any integer type up to 128 bits.
+/// A negative base is reduced to the range 0..n first.
+///
+/// exp must not be negative and n must be positive.
+///
+/// ```
+/// use jfactor::mod_pow;
+///
+/// assert_eq!(445, mod_pow(4, 13, 497));
+/// assert_eq!(1, mod_pow(2_u64, 18446744073709551556, 18446744073709551557));
+/// assert_eq!(4, mod_pow(-3, 3, 31));
+/// ```
+///
+pub fn mod_pow<T: PrimInt + Integer>(base: T, exp: T, n: T) -> T {
+    assert!(exp >= T::zero());
+    assert!(n > T::zero());
+    if n == T::one() {
+        return T::zero();
+    }
+    let base = base.mod_floor(&n).to_u128().unwrap();
+    let exp = exp.to_u128().unwrap();
+    let result = if let Some(n) = n.to_u64() {
+        mod_pow_word(base as u64, exp, n) as u128
+    } else {
+        mod_pow_word(base, exp, n.to_u128().unwrap())
+    };
+    NumCast::from(result).unwrap()
+}
+
+
+/// Computes base^exp mod n for base < n and n > 1.
+///
+fn mod_pow_word<W: Word>(base: W, exp: u128, n: W) -> W {
+    if n.is_odd() {
+        mod_pow_with(W::Modulus::new(n), base, exp)
+    } else {
+        mod_pow_with(PlainModulus::new(n), base, exp)
+    }
+}
+
+/// Computes base^exp mod the modulus of m.
+///
+fn mod_pow_with<W: Word, M: Modulus<W>>(m: M, base: W, exp: u128) -> W {
+    let mut result = m.one();
+    let mut a = m.to_residue(base);
+    let mut e = exp;
+    while e != 0 {
+        if e & 1 != 0 {
+            result = m.mul(result, a);
+        }
+        a = m.mul(a, a);
+        e >>= 1;
+    }
+    m.value(result)
+}
+
+
+/// Returns the inverse of a modulo n,
+/// the x in the range 0..n with a x = 1 mod n,
+/// or None if a and n are not coprime.
+///
+/// This uses the extended Euclidean algorithm.
+/// A negative a is reduced to the range 0..n first.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::mod_inverse;
+///
+/// assert_eq!(Some(4), mod_inverse(3, 11));
+/// assert_eq!(Some(7), mod_inverse(-3, 11));
+/// assert_eq!(None, mod_inverse(6, 9));
+/// ```
+///
+pub fn mod_inverse<T: PrimInt + Integer>(a: T, n: T) -> Option<T> {
+    assert!(n > T::zero());
+    let a = a.mod_floor(&n).to_u128().unwrap();
+    inverse(a, n.to_u128().unwrap()).map(|x| NumCast::from(x).unwrap())
+}
+
+
+/// Returns the inverse of a modulo n for a < n, if it exists.
+///
+fn inverse(a: u128, n: u128) -> Option<u128> {
+    // the coefficients of a alternate in sign,
+    // so only their absolute values are kept
+    let (mut r0, mut r1) = (n, a);
+    let (mut s0, mut s1) = (0, 1);
+    let mut negative = true;
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 + q * s1);
+        negative = !negative;
+    }
+    if r0 != 1 {
+        None
+    } else if negative && s0 != 0 {
+        Some(n - s0)
+    } else {
+        Some(s0)
+    }
+}
+
+
+/// Solves the linear congruence a x = b mod n.
+///
+/// Returns (x, step) such that the solutions are x + i step
+/// for i from 0 to g - 1, where g = gcd(a, n) = n / step,
+/// or None if there is no solution, which is the case iff
+/// g does not divide b.
+/// x is in the range 0..step, so it is the smallest solution.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::solve_linear_congruence;
+///
+/// // 6 x = 4 mod 10 for x = 4 and 9
+/// assert_eq!(Some((4, 5)), solve_linear_congruence(6, 4, 10));
+/// assert_eq!(None, solve_linear_congruence(6, 3, 10));
+/// ```
+///
+pub fn solve_linear_congruence<T: PrimInt + Integer>(a: T, b: T, n: T) -> Option<(T, T)> {
+    assert!(n > T::zero());
+    let a = a.mod_floor(&n).to_u128().unwrap();
+    let b = b.mod_floor(&n).to_u128().unwrap();
+    let n = n.to_u128().unwrap();
+    let g = a.gcd(&n);
+    if !b.is_multiple_of(g) {
+        return None;
+    }
+    let step = n / g;
+    let x = u128::mul_mod(b / g % step, inverse(a / g % step, step).unwrap(), step);
+    Some((NumCast::from(x).unwrap(), NumCast::from(step).unwrap()))
+}
+
+
+/// Solves a system of congruences x = r mod m by the Chinese remainder
+/// theorem, given the pairs (r, m).
+///
+/// Returns (x, l) where l is the least common multiple of the moduli
+/// and x in the range 0..l is the unique solution modulo l,
+/// or None if the congruences are inconsistent or l does not fit into
+/// the type.
+/// The moduli need not be coprime, only the residues must agree
+/// modulo the common factors.
+///
+/// The moduli must be positive.
+///
+/// ```
+/// use jfactor::crt;
+///
+/// assert_eq!(Some((23, 105)), crt(&[(2, 3), (3, 5), (2, 7)]));
+/// assert_eq!(Some((10, 12)), crt(&[(4, 6), (2, 4)]));
+/// assert_eq!(None, crt(&[(1, 6), (2, 4)]));
+/// ```
+///
+pub fn crt<T: PrimInt + Integer>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    let mut x = 0_u128;
+    let mut l = 1_u128;
+    for &(r, m) in congruences {
+        assert!(m > T::zero());
+        let r = r.mod_floor(&m).to_u128().unwrap();
+        let m = m.to_u128().unwrap();
+
+        // x + l t = r mod m has a solution t iff gcd(l, m) divides r - x
+        let g = l.gcd(&m);
+        let (d, negative) = if r >= x % m { (r - x % m, false) } else { (x % m - r, true) };
+        if !d.is_multiple_of(g) {
+            return None;
+        }
+        let m_g = m / g;
+        let d = d / g % m_g;
+        let d = if negative && d != 0 { m_g - d } else { d };
+        let t = u128::mul_mod(d, inverse(l / g % m_g, m_g).unwrap(), m_g);
+        let next_l = l.checked_mul(m_g)?;
+        x += l * t;
+        l = next_l;
+    }
+    Some((NumCast::from(x)?, NumCast::from(l)?))
+}
+
+
+/// Returns the Legendre symbol (a / p) for an odd prime p,
+/// which is 0 if p divides a, 1 if a is a square modulo p
+/// and -1 otherwise.
+///
+/// This uses Euler's criterion (a / p) = a^((p - 1) / 2) mod p.
+///
+/// p must be an odd prime, primality is not checked.
+///
+/// ```
+/// use jfactor::legendre_symbol;
+///
+/// assert_eq!(1, legendre_symbol(2, 7));
+/// assert_eq!(-1, legendre_symbol(3, 7));
+/// assert_eq!(0, legendre_symbol(14, 7));
+/// ```
+///
+pub fn legendre_symbol(a: u64, p: u64) -> i8 {
+    assert!(p.is_odd());
+    let m = MontgomeryModulus64::new(p);
+    let a = a % p;
+    if a == 0 {
+        0
+    } else if m.pow_u64(m.to_residue(a), (p - 1) / 2) == m.one() {
+        1
+    } else {
+        -1
+    }
+}
+
+
+/// Returns true if a is a quadratic residue modulo the odd prime p,
+/// that is a is not divisible by p and a square modulo p.
+///
+/// p must be an odd prime, primality is not checked.
+///
+/// ```
+/// use jfactor::is_quadratic_residue;
+///
+/// let residues: Vec<u64> = (0..11).filter(|&a| is_quadratic_residue(a, 11)).collect();
+/// assert_eq!(vec![1, 3, 4, 5, 9], residues);
+/// ```
+///
+pub fn is_quadratic_residue(a: u64, p: u64) -> bool {
+    legendre_symbol(a, p) == 1
+}
+
+
+/// Returns the Jacobi symbol (a / n) for odd positive n.
+///
+/// This is the product of the Legendre symbols (a / p)
+/// over the prime factors p of n counted with multiplicity,
+/// but it is computed without factoring n,
+/// with the binary algorithm based on quadratic reciprocity.
+/// It is 0 if a and n are not coprime,
+/// and -1 proves that a is not a square modulo n.
+///
+/// ```
+/// use jfactor::jacobi_symbol;
+///
+/// assert_eq!(1, jacobi_symbol(2, 15));
+/// assert_eq!(-1, jacobi_symbol(7, 15));
+/// assert_eq!(-1, jacobi_symbol(-1, 15));
+/// assert_eq!(0, jacobi_symbol(6, 15));
+/// ```
+///
+pub fn jacobi_symbol<T: PrimInt + Integer>(a: T, n: T) -> i8 {
+    assert!(n > T::zero() && n.is_odd());
+    let a = a.mod_floor(&n);
+    let j = if let Some(n) = n.to_u64() {
+        jacobi(a.to_u64().unwrap(), n)
+    } else {
+        jacobi(a.to_u128().unwrap(), n.to_u128().unwrap())
+    };
+    j as i8
+}
+
+
+/// Returns the Kronecker symbol (a / n) for any integers a and n.
+///
+/// This extends the Jacobi symbol to even and negative n
+/// by (a / 2) = 0 for even a, 1 for a = ±1 mod 8 and -1 for a = ±3 mod 8,
+/// (a / -1) = -1 for negative a and 1 otherwise,
+/// and (a / 0) = 1 for a = ±1 and 0 otherwise.
+/// For a discriminant D the map n -> (D / n) is the character
+/// of the quadratic field with that discriminant.
+///
+/// ```
+/// use jfactor::kronecker_symbol;
+///
+/// assert_eq!(-1, kronecker_symbol(5, 2));
+/// assert_eq!(1, kronecker_symbol(-7, 8));
+/// assert_eq!(-1, kronecker_symbol(-1, 3));
+/// assert_eq!(1, kronecker_symbol(-1, -3));
+/// assert_eq!(1, kronecker_symbol(1, 0));
+/// ```
+///
+pub fn kronecker_symbol<T: PrimInt + Integer>(a: T, n: T) -> i8 {
+    let negative_a = a < T::zero();
+    let a_abs = if negative_a {
+        a.to_i128().unwrap().unsigned_abs()
+    } else {
+        a.to_u128().unwrap()
+    };
+    if n == T::zero() {
+        return if a_abs == 1 { 1 } else { 0 };
+    }
+    let mut result = 1;
+    let n_abs = if n < T::zero() {
+        if negative_a {
+            result = -result;
+        }
+        n.to_i128().unwrap().unsigned_abs()
+    } else {
+        n.to_u128().unwrap()
+    };
+
+    // n = 2^e m with m odd
+    let e = n_abs.trailing_zeros();
+    let m = n_abs >> e;
+    if e > 0 {
+        let a_mod_8 = a.mod_floor(&NumCast::from(8).unwrap()).to_u32().unwrap();
+        match a_mod_8 {
+            0 | 2 | 4 | 6 => return 0,
+            3 | 5 if e % 2 == 1 => result = -result,
+            _ => {}
+        }
+    }
+
+    // the residue of a modulo m
+    let r = a_abs % m;
+    let r = if negative_a && r != 0 { m - r } else { r };
+    let j = if m >> 64 == 0 {
+        jacobi(r as u64, m as u64)
+    } else {
+        jacobi(r, m)
+    };
+    result * j as i8
+}
+
+
+/// Returns a square root of a modulo the prime p,
+/// the smaller of the two roots x with x^2 = a mod p,
+/// or None if a is not a square modulo p.
+///
+/// This uses the Tonelli-Shanks algorithm, which needs time O(s^2)
+/// for p - 1 = q 2^s with q odd.
+/// For large s Cipolla's algorithm is used instead,
+/// which computes in the field with p^2 elements.
+///
+/// p must be prime, this is not checked.
+///
+/// ```
+/// use jfactor::sqrt_mod_prime;
+///
+/// assert_eq!(Some(2), sqrt_mod_prime(4, 7));
+/// assert_eq!(Some(3), sqrt_mod_prime(2, 7));
+/// assert_eq!(None, sqrt_mod_prime(3, 7));
+/// ```
+///
+pub fn sqrt_mod_prime(a: u64, p: u64) -> Option<u64> {
+    let a = a % p;
+    if p == 2 || a == 0 {
+        return Some(a);
+    }
+    let m = MontgomeryModulus64::new(p);
+    let x = m.value(sqrt_mod_prime_residue(&m, m.to_residue(a))?);
+    Some(x.min(p - x))
+}
+
+
+/// Above this 2-adic valuation of p - 1
+/// Cipolla's algorithm is used instead of Tonelli-Shanks.
+const TONELLI_SHANKS_LIMIT: u32 = 16;
+
+
+/// Returns a square root of the nonzero residue a modulo the odd prime p,
+/// if it exists.
+///
+fn sqrt_mod_prime_residue<M: Modulus<u64>>(m: &M, a: u64) -> Option<u64> {
+    let p = m.modulus();
+    let one = m.one();
+    let minus_one = m.sub(m.to_residue(0), one);
+    if m.pow_u64(a, (p - 1) / 2) != one {
+        return None;
+    }
+    let is_non_residue = |z| m.pow_u64(z, (p - 1) / 2) == minus_one;
+
+    // p - 1 = q 2^s with q odd
+    let s = (p - 1).trailing_zeros();
+    let q = (p - 1) >> s;
+    if s == 1 {
+        return Some(m.pow_u64(a, (p + 1) / 4));
+    }
+
+    if s <= TONELLI_SHANKS_LIMIT {
+        // any non-residue z gives a generator of the 2-Sylow subgroup
+        let z = (2..).map(|z| m.to_residue(z)).find(|&z| is_non_residue(z)).unwrap();
+        let mut c = m.pow_u64(z, q);
+        let mut t = m.pow_u64(a, q);
+        let mut x = m.pow_u64(a, q.div_ceil(2));
+        let mut k = s;
+        while t != one {
+            // find the least i with t^(2^i) = 1
+            let mut i = 0;
+            let mut t2 = t;
+            while t2 != one {
+                t2 = m.mul(t2, t2);
+                i += 1;
+            }
+            let b = m.pow_u64(c, 1 << (k - i - 1));
+            x = m.mul(x, b);
+            c = m.mul(b, b);
+            t = m.mul(t, c);
+            k = i;
+        }
+        Some(x)
+    } else {
+        // with w = t^2 - a a non-residue, (t + √w)^((p + 1) / 2)
+        // is a square root of t^2 - w = a in the field F_p(√w)
+        let (t, w) = (1..)
+            .map(|t| {
+                let t = m.to_residue(t);
+                (t, m.sub(m.mul(t, t), a))
+            })
+            .find(|&(_, w)| is_non_residue(w))
+            .unwrap();
+        let mul = |(x0, x1): (u64, u64), (y0, y1): (u64, u64)| {
+            (
+                m.add(m.mul(x0, y0), m.mul(m.mul(x1, y1), w)),
+                m.add(m.mul(x0, y1), m.mul(x1, y0)),
+            )
+        };
+        let mut result = (one, m.to_residue(0));
+        let mut base = (t, one);
+        let mut e = p.div_ceil(2);
+        while e != 0 {
+            if e & 1 != 0 {
+                result = mul(result, base);
+            }
+            base = mul(base, base);
+            e >>= 1;
+        }
+        Some(result.0)
+    }
+}
+
+
+/// Lifts a simple root of a polynomial modulo the prime p
+/// to a root modulo p^k.
+///
+/// The polynomial is given by its coefficients, lowest degree first.
+/// The root must satisfy f(r) = 0 mod p and f'(r) != 0 mod p,
+/// then there is a unique root modulo p^k congruent to r modulo p,
+/// which Newton's iteration r - f(r) / f'(r) finds
+/// doubling the precision in each step.
+/// Returns None if r is not a simple root modulo p
+/// or p^k does not fit into u64.
+///
+/// k must be positive and p must be prime, this is not checked.
+///
+/// ```
+/// use jfactor::hensel_lift;
+///
+/// // x^2 - 2 = 0 mod 7 has the roots 3 and 4
+/// let r = hensel_lift(&[-2, 0, 1], 3, 7, 5).unwrap();
+/// assert_eq!(3, r % 7);
+/// assert_eq!(2, r * r % 16807);
+/// // 1 is a double root of x^2 - 2 x + 1
+/// assert_eq!(None, hensel_lift(&[1, -2, 1], 1, 7, 2));
+/// ```
+///
+pub fn hensel_lift(coefficients: &[i64], root: u64, p: u64, k: u32) -> Option<u64> {
+    assert!(k > 0);
+    let pk = p.checked_pow(k)?;
+
+    // the values of f and its derivative at x modulo q
+    let evaluate = |x: u64, q: u64| {
+        let mut f = 0;
+        let mut df = 0;
+        for &c in coefficients.iter().rev() {
+            df = u64::add_mod(u64::mul_mod(df, x, q), f, q);
+            f = u64::add_mod(u64::mul_mod(f, x, q), (c as i128).rem_euclid(q as i128) as u64, q);
+        }
+        (f, df)
+    };
+
+    let mut r = root % p;
+    let (f, df) = evaluate(r, p);
+    if f != 0 || df == 0 {
+        return None;
+    }
+    let mut q = p;
+    while q < pk {
+        q = q.checked_mul(q).map_or(pk, |q2| q2.min(pk));
+        let (f, df) = evaluate(r, q);
+        let step = u64::mul_mod(f, mod_inverse(df, q).unwrap(), q);
+        r = u64::sub_mod(r, step, q);
+    }
+    Some(r)
+}
+
+
+/// Returns the multiplicative order of a modulo n,
+/// the smallest positive k with a^k = 1 mod n,
+/// or None if a and n are not coprime.
+///
+/// The order divides the Carmichael function λ(n),
+/// so this factors λ(n) and removes prime factors p
+/// as long as a^(k / p) is still 1.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::multiplicative_order;
+///
+/// assert_eq!(Some(6), multiplicative_order(3, 7));
+/// assert_eq!(Some(2), multiplicative_order(7, 8));
+/// assert_eq!(None, multiplicative_order(2, 10));
+/// ```
+///
+pub fn multiplicative_order(a: u64, n: u64) -> Option<u64> {
+    assert!(n > 0);
+    if a.gcd(&n) != 1 {
+        return None;
+    }
+    let m = PlainModulus::new(n);
+    let one = 1 % n;
+    let a = a % n;
+    let lambda = carmichael_lambda(n);
+    let mut k = lambda;
+    for &p in factor(lambda).primes() {
+        while k.is_multiple_of(p) && m.pow_u64(a, k / p) == one {
+            k /= p;
+        }
+    }
+    Some(k)
+}
+
+
+/// The maximum number of baby steps stored by `discrete_log`.
+const BABY_STEP_LIMIT: u64 = 1 << 20;
+
+
+/// Returns the discrete logarithm of target to the given base modulo n,
+/// the smallest x >= 0 with base^x = target mod n,
+/// or None if there is none.
+/// If the order of the base is above 2^40 this is `discrete_log_rho`,
+/// which can return None with a negligible probability
+/// even if there is a logarithm.
+///
+/// Common factors of the base and n are split off first,
+/// the rest is solved with the baby-step giant-step algorithm
+/// in the group generated by the base.
+/// This needs time and memory O(√k) for the order k of the base,
+/// the table of baby steps is limited to 2^20 entries, about 32 MB.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::discrete_log;
+///
+/// assert_eq!(Some(4), discrete_log(3, 13, 17));
+/// assert_eq!(Some(2), discrete_log(2, 4, 12));
+/// assert_eq!(None, discrete_log(2, 3, 7));
+/// ```
+///
+pub fn discrete_log(base: u64, target: u64, n: u64) -> Option<u64> {
+    let n0 = n;
+    let (g, h, n, k) = match split_common_factors(base, target, n) {
+        Reduced::Solved(x) => return Some(x),
+        Reduced::Unsolvable => return None,
+        Reduced::Coprime { g, h, n, k } => (g, h, n, k),
+    };
+
+    // baby steps h g^j and giant steps g^(i m) for x = i m - j,
+    // keeping the largest j finds the smallest x
+    let order = multiplicative_order(g, n).unwrap();
+    let m = order.isqrt() + 1;
+    if m > BABY_STEP_LIMIT {
+        return discrete_log_rho(base, target, n0);
+    }
+    let mut baby_steps = HashMap::with_capacity(m as usize);
+    let mut y = h;
+    for j in 0..m {
+        baby_steps.insert(y, j);
+        y = u64::mul_mod(y, g, n);
+    }
+    let giant_step = PlainModulus::new(n).pow_u64(g, m);
+    let mut y = 1 % n;
+    for i in 1..=m {
+        y = u64::mul_mod(y, giant_step, n);
+        if let Some(&j) = baby_steps.get(&y) {
+            return (i * m - j).checked_add(k);
+        }
+    }
+    None
+}
+
+
+/// Returns the discrete logarithm of target to the given base modulo n,
+/// the smallest x >= 0 with base^x = target mod n,
+/// or None if there is none.
+///
+/// This solves the same problem as `discrete_log` in constant memory.
+/// The Pohlig-Hellman algorithm reduces it to groups of prime order q
+/// dividing the order of the base,
+/// where Pollard's rho algorithm finds the logarithm in O(√q) steps.
+/// It is practical as long as the largest such q has up to about 50 bits,
+/// however large the order is.
+///
+/// The rho walks may fail to find a logarithm which exists,
+/// which is retried with another starting point a few times.
+/// After that None is returned, with a negligible probability of error.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::discrete_log_rho;
+///
+/// assert_eq!(Some(4), discrete_log_rho(3, 13, 17));
+/// assert_eq!(Some(2), discrete_log_rho(2, 4, 12));
+/// assert_eq!(None, discrete_log_rho(2, 3, 7));
+/// ```
+///
+pub fn discrete_log_rho(base: u64, target: u64, n: u64) -> Option<u64> {
+    let (g, h, n, k) = match split_common_factors(base, target, n) {
+        Reduced::Solved(x) => return Some(x),
+        Reduced::Unsolvable => return None,
+        Reduced::Coprime { g, h, n, k } => (g, h, n, k),
+    };
+    let x = if n % 2 == 1 {
+        pohlig_hellman::<MontgomeryModulus64>(g, h, n)
+    } else {
+        pohlig_hellman::<PlainModulus<u64>>(g, h, n)
+    };
+    x.map(|x| k + x)
+}
+
+
+/// A discrete logarithm problem base^x = target mod n
+/// after splitting off common factors of the base and n.
+///
+enum Reduced {
+    Solved(u64),
+    Unsolvable,
+    /// The solutions are k + x with g^x = h mod n and g coprime to n.
+    Coprime { g: u64, h: u64, n: u64, k: u64 },
+}
+
+
+/// Reduces base^x = target mod n to a problem with the base coprime to n.
+///
+/// While d = gcd(base, n) is not 1, the solutions x > 0 are
+/// the solutions x - 1 of (base / d) base^(x - 1) = target / d mod n / d.
+///
+fn split_common_factors(base: u64, target: u64, n: u64) -> Reduced {
+    assert!(n > 0);
+    let mut n = n;
+    let g = base % n;
+    let mut h = target % n;
+
+    let mut c = 1 % n;
+    let mut k = 0;
+    loop {
+        if c == h {
+            return Reduced::Solved(k);
+        }
+        let d = g.gcd(&n);
+        if d == 1 {
+            break;
+        }
+        if !h.is_multiple_of(d) {
+            return Reduced::Unsolvable;
+        }
+        h /= d;
+        n /= d;
+        c = u64::mul_mod(c % n, (g / d) % n, n);
+        k += 1;
+    }
+
+    // c is a product of divisors of the base, so it is coprime to n
+    let h = u64::mul_mod(h, mod_inverse(c, n).unwrap(), n);
+    Reduced::Coprime { g: g % n, h, n, k }
+}
+
+
+/// The number of starting points tried by the rho walks.
+const RHO_ATTEMPTS: u64 = 10;
+
+/// Below this bound logarithms in groups of prime order
+/// are found by exhaustive search.
+const EXHAUSTIVE_SEARCH_LIMIT: u64 = 1 << 16;
+
+
+/// Returns the smallest x with g^x = h mod n,
+/// where g is coprime to n.
+///
+fn pohlig_hellman<M: Modulus<u64>>(g: u64, h: u64, n: u64) -> Option<u64> {
+    let m = M::new(n);
+    let g = m.to_residue(g);
+    let h = m.to_residue(h);
+    let order = multiplicative_order(m.value(g), n).unwrap();
+
+    // combine x mod q^e by the Chinese remainder theorem
+    let mut x = 0_u64;
+    let mut modulus = 1_u64;
+    for (q, e) in factor(order) {
+        let gamma = m.pow_u64(g, order / q);
+        let mut x_q = 0;
+        let mut qj = 1;
+        for j in 0..e {
+            // eta = (h g^(-x_q))^(order / q^(j + 1)) has order dividing q
+            let y = m.mul(h, m.pow_u64(g, order - x_q));
+            let eta = m.pow_u64(y, order / qj / q);
+            let d = log_prime_order(&m, gamma, eta, q)?;
+            x_q += d * qj;
+            if j + 1 < e {
+                qj *= q;
+            }
+        }
+        let qe = qj * q;
+        let t = u64::mul_mod(u64::sub_mod(x_q, x % qe, qe), mod_inverse(modulus, qe).unwrap(), qe);
+        x += modulus * t;
+        modulus *= qe;
+    }
+    if m.pow_u64(g, x) == h {
+        Some(x)
+    } else {
+        None
+    }
+}
+
+
+/// Returns x with gamma^x = eta, where gamma has prime order q,
+/// or None if there is none.
+///
+fn log_prime_order<M: Modulus<u64>>(m: &M, gamma: u64, eta: u64, q: u64) -> Option<u64> {
+    if q < EXHAUSTIVE_SEARCH_LIMIT {
+        let mut y = m.one();
+        for x in 0..q {
+            if y == eta {
+                return Some(x);
+            }
+            y = m.mul(y, gamma);
+        }
+        return None;
+    }
+
+    // walk through gamma^a eta^b, each step multiplies by gamma or eta
+    // or squares, depending on the residue class of the element
+    let step = |(y, a, b): (u64, u64, u64)| match y % 3 {
+        0 => (m.mul(y, gamma), u64::add_mod(a, 1, q), b),
+        1 => (m.mul(y, y), u64::add_mod(a, a, q), u64::add_mod(b, b, q)),
+        _ => (m.mul(y, eta), a, u64::add_mod(b, 1, q)),
+    };
+    for attempt in 1..=RHO_ATTEMPTS {
+        // Floyd's cycle detection
+        let start = (m.mul(m.pow_u64(gamma, attempt), eta), attempt, 1);
+        let mut tortoise = step(start);
+        let mut hare = step(tortoise);
+        while tortoise.0 != hare.0 {
+            tortoise = step(tortoise);
+            hare = step(step(hare));
+        }
+
+        // gamma^a eta^b = gamma^a' eta^b' gives a - a' = (b' - b) x mod q
+        let (_, a, b) = tortoise;
+        let (_, a2, b2) = hare;
+        let db = u64::sub_mod(b2, b, q);
+        if db != 0 {
+            let x = u64::mul_mod(u64::sub_mod(a, a2, q), mod_inverse(db, q).unwrap(), q);
+            return if m.pow_u64(gamma, x) == eta { Some(x) } else { None };
+        }
+    }
+    None
+}
+
+
+#[cfg(test)]
+mod tests {
+    use primality::is_prime;
+
+    use super::*;
+
+    #[test]
+    fn test_multiplicative_order() {
+        for n in 1..200_u64 {
+            for a in 0..n {
+                let expected = if a.gcd(&n) == 1 {
+                    let mut x = a % n;
+                    let mut k = 1;
+                    while x != 1 % n {
+                        x = x * a % n;
+                        k += 1;
+                    }
+                    Some(k)
+                } else {
+                    None
+                };
+                assert_eq!(expected, multiplicative_order(a, n), "{} {}", a, n);
+            }
+        }
+    }
+
+    #[test]
+    fn test_multiplicative_order_large() {
+        // 2 is a primitive root modulo this prime
+        let p = 18446744073709551557_u64;
+        assert_eq!(Some(p - 1), multiplicative_order(2, p));
+        assert_eq!(Some(1), multiplicative_order(1, p));
+        assert_eq!(Some(2), multiplicative_order(p - 1, p));
+        // 2^64 = -1 modulo the factor 67280421310721 of 2^64 + 1
+        assert_eq!(Some(128), multiplicative_order(2, 67280421310721));
+        assert_eq!(None, multiplicative_order(274177, 274177 * 3));
+        assert_eq!(Some(1), multiplicative_order(5, 1));
+    }
+
+    #[test]
+    fn test_discrete_log() {
+        for n in 1..100_u64 {
+            for g in 0..n {
+                let mut expected = vec![None; n as usize];
+                let mut x = 1 % n;
+                for k in 0..2 * n {
+                    if expected[x as usize].is_none() {
+                        expected[x as usize] = Some(k);
+                    }
+                    x = x * g % n;
+                }
+                for h in 0..n {
+                    assert_eq!(expected[h as usize], discrete_log(g, h, n), "{} {} {}", g, h, n);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_discrete_log_large() {
+        let p = 1000000007_u64;
+        let x = 123456789;
+        let h = PlainModulus::new(p).pow_u64(5, x);
+        assert_eq!(Some(x), discrete_log(5, h, p));
+        assert_eq!(Some(0), discrete_log(5, 1, p));
+        assert_eq!(Some(1), discrete_log(5, 5 + p, p));
+        // 2 has order 128 modulo this factor of 2^64 + 1
+        let q = 67280421310721;
+        assert_eq!(Some(64), discrete_log(2, q - 1, q));
+        assert_eq!(None, discrete_log(2, 3, q));
+        // the order is the product of two 20-bit primes
+        let n = 1000003_u64 * 1000033;
+        let h = PlainModulus::new(n).pow_u64(2, 987654321098);
+        let x = discrete_log(2, h, n).unwrap();
+        assert_eq!(h, PlainModulus::new(n).pow_u64(2, x));
+        // an order above 2^40 falls back to rho, p - 1 = 2^32 3 5 17 257 65537
+        let p = 18446744069414584321_u64;
+        let order = multiplicative_order(7, p).unwrap();
+        assert!(order > 1 << 40);
+        let h = PlainModulus::new(p).pow_u64(7, 0x123456789abcdef);
+        assert_eq!(Some(0x123456789abcdef % order), discrete_log(7, h, p));
+        assert_eq!(discrete_log_rho(7, h, p), discrete_log(7, h, p));
+        // 7 generates all residues, so it is not a power of 49
+        assert_eq!(None, discrete_log(49, 7, p));
+    }
+
+    #[test]
+    fn test_discrete_log_rho() {
+        for n in 1..60_u64 {
+            for g in 0..n {
+                for h in 0..n {
+                    assert_eq!(discrete_log(g, h, n), discrete_log_rho(g, h, n), "{} {} {}", g, h, n);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_discrete_log_rho_large() {
+        // p - 1 = 2 * 5 * 13^3 * 4073 * 103073081453
+        let p = 9223372036854775931_u64;
+        let m = PlainModulus::new(p);
+        let g = (2..).find(|&g| multiplicative_order(g, p) == Some(p - 1)).unwrap();
+        let x = 8167125392718145523;
+        assert_eq!(Some(x), discrete_log_rho(g, m.pow_u64(g, x), p));
+        // a base of order (p - 1) / 13^2
+        let g2 = m.pow_u64(g, 169);
+        let x = 12345678901234;
+        assert_eq!(Some(x), discrete_log_rho(g2, m.pow_u64(g2, x), p));
+        assert_eq!(None, discrete_log_rho(g2, g, p));
+        // an even modulus with a non-cyclic group
+        let n = 4 * 1000000007_u64 * 998244353;
+        let x = discrete_log_rho(3, PlainModulus::new(n).pow_u64(3, 987654321), n).unwrap();
+        assert_eq!(PlainModulus::new(n).pow_u64(3, 987654321), PlainModulus::new(n).pow_u64(3, x));
+        assert_eq!(None, discrete_log_rho(3, n - 1, n));
+    }
+
+    #[test]
+    fn test_log_prime_order() {
+        // 5 has prime order q = 500000003 modulo this prime
+        let p = 1000000007_u64;
+        let q = 500000003;
+        let m = MontgomeryModulus64::new(p);
+        let gamma = m.pow_u64(m.to_residue(5), 2);
+        for &x in &[0, 1, 2, 123456789, q - 1] {
+            let eta = m.pow_u64(gamma, x);
+            assert_eq!(Some(x), log_prime_order(&m, gamma, eta, q));
+        }
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        for n in 1..200_u64 {
+            for a in 0..n {
+                match mod_inverse(a, n) {
+                    Some(x) => {
+                        assert!(x < n);
+                        assert_eq!(1 % n, a * x % n);
+                    }
+                    None => assert!(a.gcd(&n) != 1),
+                }
+            }
+        }
+        assert_eq!(Some(0), mod_inverse(5, 1));
+        assert_eq!(Some(10), mod_inverse(-1_i32, 11));
+    }
+
+    #[test]
+    fn test_mod_inverse_large() {
+        let p = 18446744073709551557_u64;
+        assert_eq!(1, u64::mul_mod(12345, mod_inverse(12345, p).unwrap(), p));
+        assert_eq!(Some(u64::MAX - 1), mod_inverse(u64::MAX - 1, u64::MAX));
+        let p = (1_u128 << 127) - 1;
+        assert_eq!(Some(1 << 126), mod_inverse(2, p));
+        assert_eq!(Some(u128::MAX - 1), mod_inverse(u128::MAX - 1, u128::MAX));
+        assert_eq!(None, mod_inverse(1 << 64, u128::MAX - 1));
+    }
+
+    #[test]
+    fn test_mod_pow() {
+        for n in 1..50_u32 {
+            for a in 0..n {
+                let mut expected = 1 % n;
+                for e in 0..50 {
+                    assert_eq!(expected, mod_pow(a, e, n));
+                    expected = expected * a % n;
+                }
+            }
+        }
+        assert_eq!(0, mod_pow(0_u32, 0, 1));
+        assert_eq!(1, mod_pow(0_u32, 0, 2));
+        assert_eq!(12, mod_pow(-2_i8, 3, 20));
+    }
+
+    #[test]
+    fn test_mod_pow_large() {
+        let p = 18446744073709551557_u64;
+        assert_eq!(1, mod_pow(3, p - 1, p));
+        assert_eq!(u64::MAX - 1, mod_pow(u64::MAX - 1, 1, u64::MAX));
+        assert_eq!(1, mod_pow(u64::MAX - 1, 2, u64::MAX));
+        let p = (1_u128 << 127) - 1;
+        assert_eq!(1, mod_pow(3, p - 1, p));
+        assert_eq!(2, mod_pow(2, 128, p));
+        let n = 1_u128 << 100;
+        assert_eq!(0, mod_pow(6, 100, n));
+        assert_eq!(3_u128.pow(80) % n, mod_pow(3, 80, n));
+        assert_eq!(u128::MAX - 1, mod_pow(u128::MAX - 1, 3, u128::MAX));
+    }
+
+    #[test]
+    fn test_crt() {
+        for m1 in 1..30_u32 {
+            for m2 in 1..30 {
+                let l = m1.lcm(&m2);
+                for r1 in 0..m1 {
+                    for r2 in 0..m2 {
+                        let expected = (0..l).find(|x| x % m1 == r1 && x % m2 == r2).map(|x| (x, l));
+                        assert_eq!(expected, crt(&[(r1, m1), (r2, m2)]));
+                    }
+                }
+            }
+        }
+        assert_eq!(Some((0, 1)), crt::<u32>(&[]));
+        assert_eq!(Some((3, 5)), crt(&[(-2_i32, 5)]));
+    }
+
+    #[test]
+    fn test_crt_large() {
+        let p = 18446744073709551557_u128;
+        let q = 18446744073709551533_u128;
+        let x = 123456789012345678901234567890123456789;
+        assert_eq!(Some((x, p * q)), crt(&[(x % p, p), (x % q, q)]));
+        // the least common multiple fits but not the product
+        let n = 1_u128 << 100;
+        assert_eq!(Some((n + 1, n * 3)), crt(&[(n + 1, n * 3), (n + 1, n * 3 / 2)]));
+        assert_eq!(None, crt(&[(0, p), (0, q), (0, 3)]));
+        assert_eq!(None, crt(&[(1_u64, 1 << 40), (0, 1 << 30)]));
+        assert_eq!(None, crt(&[(0_u64, 1 << 40), (0, (1 << 30) + 1)]));
+    }
+
+    #[test]
+    fn test_sqrt_mod_prime() {
+        for p in (2..300_u64).filter(|&p| is_prime(p)) {
+            for a in 0..p {
+                let expected = (0..p).find(|x| x * x % p == a);
+                assert_eq!(expected, sqrt_mod_prime(a, p), "{} {}", a, p);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sqrt_mod_prime_large() {
+        // p - 1 = q 2^s with s from 1 up to 32 for the last prime
+        let primes = [
+            18446744073709551557_u64,
+            1000000009,
+            65537,
+            998244353,
+            3221225473,
+            18446744069414584321,
+        ];
+        for &p in &primes {
+            for a in 1..200 {
+                match sqrt_mod_prime(a, p) {
+                    Some(x) => {
+                        assert!(x <= p / 2);
+                        assert_eq!(a, u64::mul_mod(x, x, p));
+                    }
+                    None => assert_eq!(p - 1, mod_pow(a, (p - 1) / 2, p)),
+                }
+            }
+            let x = p / 3;
+            assert_eq!(Some(x.min(p - x)), sqrt_mod_prime(u64::mul_mod(x, x, p), p));
+        }
+    }
+
+    #[test]
+    fn test_sqrt_mod_prime_unreduced() {
+        // 1000000009 = 1 mod 8 exercises the loop of Tonelli-Shanks
+        for &p in &[7_u64, 13, 1000003, 1000000009] {
+            for a in 1..50 {
+                match sqrt_mod_prime(a, p) {
+                    Some(x) => assert_eq!(a % p, x * x % p),
+                    None => assert_eq!(p - 1, mod_pow(a % p, (p - 1) / 2, p)),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_legendre_symbol() {
+        for p in (3..300_u64).filter(|&p| is_prime(p)) {
+            for a in 0..2 * p {
+                let expected = if a % p == 0 {
+                    0
+                } else if (1..p).any(|x| x * x % p == a % p) {
+                    1
+                } else {
+                    -1
+                };
+                assert_eq!(expected, legendre_symbol(a, p), "{} {}", a, p);
+                assert_eq!(expected == 1, is_quadratic_residue(a, p));
+            }
+        }
+    }
+
+    #[test]
+    fn test_legendre_symbol_large() {
+        // 2 is a square modulo p iff p = 1 or 7 mod 8
+        let p = 18446744073709551557_u64;
+        assert_eq!(-1, legendre_symbol(2, p));
+        // -1 is a square modulo p iff p = 1 mod 4
+        assert_eq!(1, legendre_symbol(p - 1, p));
+        let x = 1234567890123456789;
+        assert_eq!(1, legendre_symbol(u64::mul_mod(x, x, p), p));
+        assert_eq!(0, legendre_symbol(0, p));
+        let q = 18446744073709551359;
+        assert_eq!(1, legendre_symbol(2, q));
+        assert_eq!(-1, legendre_symbol(q - 1, q));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_legendre_symbol_even() {
+        legendre_symbol(1, 2);
+    }
+
+    #[test]
+    fn test_jacobi_symbol() {
+        let primes = (3..200_i64).filter(|&p| is_prime(p)).collect::<Vec<_>>();
+        for n in (1..200_i64).step_by(2) {
+            for a in -200..200_i64 {
+                let mut expected = 1;
+                let mut rest = n;
+                for &p in &primes {
+                    while rest % p == 0 {
+                        expected *= legendre_symbol(a.rem_euclid(p) as u64, p as u64);
+                        rest /= p;
+                    }
+                }
+                assert_eq!(expected, jacobi_symbol(a, n), "{} {}", a, n);
+            }
+        }
+    }
+
+    #[test]
+    fn test_jacobi_symbol_large() {
+        let p = 18446744073709551557_u128;
+        let q = 18446744073709551359_u128;
+        for a in 1..100 {
+            let expected = legendre_symbol(a, p as u64) * legendre_symbol(a, q as u64);
+            assert_eq!(expected, jacobi_symbol(a as u128, p * q));
+        }
+        assert_eq!(0, jacobi_symbol(q, p * q));
+        assert_eq!(-1, jacobi_symbol(-1, q as i128));
+        assert_eq!(-1, jacobi_symbol(u128::MAX - 1, u128::MAX));
+        assert_eq!(1, jacobi_symbol(2, u128::MAX));
+    }
+
+    #[test]
+    fn test_kronecker_symbol() {
+        fn kronecker_2(a: i64) -> i8 {
+            match a.rem_euclid(8) {
+                1 | 7 => 1,
+                3 | 5 => -1,
+                _ => 0,
+            }
+        }
+        for a in -100..100_i64 {
+            assert_eq!(if a.abs() == 1 { 1 } else { 0 }, kronecker_symbol(a, 0));
+            assert_eq!(if a < 0 { -1 } else { 1 }, kronecker_symbol(a, -1));
+            let mut power_of_2 = 1;
+            for e in 0..10 {
+                for m in (1..100_i64).step_by(2) {
+                    let expected = kronecker_2(a).pow(e) * jacobi_symbol(a, m);
+                    let n = power_of_2 * m;
+                    assert_eq!(expected, kronecker_symbol(a, n), "{} {}", a, n);
+                    assert_eq!(expected * kronecker_symbol(a, -1), kronecker_symbol(a, -n));
+                }
+                power_of_2 *= 2;
+            }
+        }
+    }
+
+    #[test]
+    fn test_kronecker_symbol_large() {
+        // multiplicative in n, also for the extreme values
+        assert_eq!(kronecker_symbol(3, -1) * kronecker_symbol(3, 2).pow(63), kronecker_symbol(3, i64::MIN));
+        assert_eq!(1, kronecker_symbol(i64::MIN + 3, i64::MIN));
+        assert_eq!(0, kronecker_symbol(i128::MIN, 6));
+        assert_eq!(jacobi_symbol(5, u128::MAX), kronecker_symbol(5, u128::MAX));
+        assert_eq!(1, kronecker_symbol(u128::MAX, 1 << 127));
+        assert_eq!(1, kronecker_symbol(-1, u64::MAX as i128 + 1));
+    }
+
+    #[test]
+    fn test_hensel_lift() {
+        let polynomials: [&[i64]; 4] = [&[-2, 0, 1], &[1, 1, 1], &[-6, -5, 0, 1], &[5, 0, 0, 0, 0, -1]];
+        let evaluate = |f: &[i64], x: u64, n: u64| {
+            f.iter().rev().fold(0, |y, &c| (y * x as i64 + c).rem_euclid(n as i64))
+        };
+        for &f in &polynomials {
+            for &p in &[2_u64, 3, 5, 7, 11] {
+                for k in 1..5 {
+                    let pk = p.pow(k);
+                    for r in 0..p {
+                        let df = f.iter().enumerate().skip(1).rev().fold(0, |y, (i, &c)| {
+                            (y * r as i64 + i as i64 * c).rem_euclid(p as i64)
+                        });
+                        let expected = if evaluate(f, r, p) == 0 && df != 0 {
+                            (r..pk).step_by(p as usize).find(|&x| evaluate(f, x, pk) == 0)
+                        } else {
+                            None
+                        };
+                        assert_eq!(expected, hensel_lift(f, r, p, k), "{:?} {} {} {}", f, r, p, k);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_hensel_lift_large() {
+        // square roots of 2 modulo 7^22 and of -1 modulo 5^27
+        let pk = 7_u64.pow(22);
+        let r = hensel_lift(&[-2, 0, 1], 4, 7, 22).unwrap();
+        assert_eq!(2, u64::mul_mod(r, r, pk));
+        let pk = 5_u64.pow(27);
+        let r = hensel_lift(&[1, 0, 1], 2, 5, 27).unwrap();
+        assert_eq!(pk - 1, u64::mul_mod(r, r, pk));
+        assert_eq!(None, hensel_lift(&[1, 0, 1], 2, 5, 28));
+        // large coefficients and a large prime
+        let p = 4294967291;
+        let f = [i64::MIN, i64::MAX, 1];
+        let r = (0..p).find(|&x| hensel_lift(&f, x, p, 2).is_some()).unwrap();
+        let x = hensel_lift(&f, r, p, 2).unwrap() as i128;
+        let p2 = (p * p) as i128;
+        assert_eq!(0, (x * x + (i64::MAX as i128 % p2) * x + i64::MIN as i128).rem_euclid(p2));
+    }
+
+    #[test]
+    fn test_solve_linear_congruence() {
+        for n in 1..60_u32 {
+            for a in 0..n {
+                for b in 0..n {
+                    let solutions = (0..n).filter(|x| a * x % n == b).collect::<Vec<_>>();
+                    match solve_linear_congruence(a, b, n) {
+                        Some((x, step)) => {
+                            let expected = (0..n / step).map(|i| x + i * step).collect::<Vec<_>>();
+                            assert_eq!(expected, solutions);
+                        }
+                        None => assert!(solutions.is_empty()),
+                    }
+                }
+            }
+        }
+        assert_eq!(Some((3, 5)), solve_linear_congruence(-4_i32, -2, 5));
+    }
+
+    #[test]
+    fn test_solve_linear_congruence_large() {
+        let n = u128::MAX - 1;
+        assert_eq!(Some((1, n / 2)), solve_linear_congruence(2, 2, n));
+        assert_eq!(None, solve_linear_congruence(2, 1, n));
+        let p = 18446744073709551557_u64;
+        let (x, step) = solve_linear_congruence(12345, 67890, p).unwrap();
+        assert_eq!(p, step);
+        assert_eq!(67890, u64::mul_mod(12345, x, p));
+    }
+}
@@ -1 +1,546 @@
 
+extern crate num;
+
+use std::ops::Mul;
+
+use self::num::{Integer, One, PrimInt, Zero};
+
+use factor::factor;
+
+
+/// Returns the radical of n, the product of its distinct prime factors.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::radical;
+///
+/// assert_eq!(30, radical(360_u32));
+/// assert_eq!(1, radical(1_u32));
+/// ```
+///
+pub fn radical<T: PrimInt + Integer>(n: T) -> T {
+    factor(n).radical()
+}
+
+
+/// Splits n into its square part and its squarefree part,
+/// returns (s, f) with n = s^2 f and f squarefree.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::square_free_decomposition;
+///
+/// // 360 = 6^2 * 10
+/// assert_eq!((6, 10), square_free_decomposition(360_u32));
+/// ```
+///
+pub fn square_free_decomposition<T: PrimInt + Integer>(n: T) -> (T, T) {
+    let mut s = T::one();
+    let mut f = T::one();
+    for (&p, &e) in factor(n).iter() {
+        s = s * p.pow(e / 2);
+        if e % 2 == 1 {
+            f = f * p;
+        }
+    }
+    (s, f)
+}
+
+
+/// Returns Euler's totient φ(n),
+/// the number of integers from 1 to n which are coprime to n.
+///
+/// As φ(n) is at most n this does not overflow.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::euler_phi;
+///
+/// assert_eq!(96, euler_phi(360_u32));
+/// assert_eq!(1, euler_phi(1_u32));
+/// assert_eq!(1000002, euler_phi(1000003_u64));
+/// ```
+///
+pub fn euler_phi<T: PrimInt + Integer>(n: T) -> T {
+    factor(n).euler_phi()
+}
+
+
+/// Returns Jordan's totient J_k(n), or None if it does not fit into u128.
+///
+/// J_k(n) counts the k-tuples of integers from 1 to n which together
+/// with n have no common divisor except 1, so J_1 is Euler's totient.
+/// It is the product of p^(k (e - 1)) (p^k - 1) over the prime powers p^e.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::jordan_totient;
+///
+/// assert_eq!(Some(96), jordan_totient(360_u32, 1));
+/// assert_eq!(Some(12), jordan_totient(4_u32, 2));
+/// ```
+///
+pub fn jordan_totient<T: PrimInt + Integer>(n: T, k: u32) -> Option<u128> {
+    let mut j = 1_u128;
+    for (p, &e) in factor(n).iter() {
+        let pk = p.to_u128().unwrap().checked_pow(k)?;
+        j = j.checked_mul(pk - 1)?;
+        for _ in 1..e {
+            j = j.checked_mul(pk)?;
+        }
+    }
+    Some(j)
+}
+
+
+/// Returns the Liouville function λ(n) = (-1)^Ω(n),
+/// where Ω(n) is the number of prime factors of n counted with multiplicity.
+///
+/// See `liouville_sieve` for a table of values.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::liouville;
+///
+/// assert_eq!(1, liouville(1_u32));
+/// assert_eq!(-1, liouville(8_u32));
+/// assert_eq!(1, liouville(10_u32));
+/// ```
+///
+pub fn liouville<T: PrimInt + Integer>(n: T) -> i8 {
+    if factor(n).big_omega().is_multiple_of(2) {
+        1
+    } else {
+        -1
+    }
+}
+
+
+/// Returns the number of distinct prime factors ω(n) of n.
+///
+/// See `omega_sieve` for a table of values.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::omega;
+///
+/// assert_eq!(3, omega(360_u32));
+/// ```
+///
+pub fn omega<T: PrimInt + Integer>(n: T) -> u32 {
+    factor(n).omega()
+}
+
+
+/// Returns the number of prime factors Ω(n) of n counted with multiplicity.
+///
+/// See `big_omega_sieve` for a table of values.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::big_omega;
+///
+/// assert_eq!(6, big_omega(360_u32));
+/// ```
+///
+pub fn big_omega<T: PrimInt + Integer>(n: T) -> u32 {
+    factor(n).big_omega()
+}
+
+
+/// Returns the prime p and exponent e if n is a prime power p^e,
+/// or None otherwise.
+///
+/// This is the von Mangoldt function Λ(n) in exact form,
+/// as Λ(n) is log p for n = p^e and 0 otherwise.
+/// Use `von_mangoldt_f64` for the value itself.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::von_mangoldt;
+///
+/// assert_eq!(Some((2, 3)), von_mangoldt(8_u32));
+/// assert_eq!(Some((7, 1)), von_mangoldt(7_u32));
+/// assert_eq!(None, von_mangoldt(12_u32));
+/// assert_eq!(None, von_mangoldt(1_u32));
+/// ```
+///
+pub fn von_mangoldt<T: PrimInt + Integer>(n: T) -> Option<(T, u32)> {
+    let factorization = factor(n);
+    if factorization.omega() == 1 {
+        factorization.into_iter().next()
+    } else {
+        None
+    }
+}
+
+
+/// Returns the von Mangoldt function Λ(n),
+/// which is log p for a prime power n = p^e and 0 otherwise.
+///
+/// The sum of Λ(n) for n up to x is the Chebyshev function ψ(x).
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::von_mangoldt_f64;
+///
+/// assert_eq!(2_f64.ln(), von_mangoldt_f64(8_u32));
+/// assert_eq!(0.0, von_mangoldt_f64(12_u32));
+/// ```
+///
+pub fn von_mangoldt_f64<T: PrimInt + Integer>(n: T) -> f64 {
+    von_mangoldt(n).map_or(0.0, |(p, _)| p.to_f64().unwrap().ln())
+}
+
+
+/// Returns the Carmichael function λ(n),
+/// the exponent of the multiplicative group modulo n.
+///
+/// This is the smallest positive m with a^m = 1 mod n
+/// for all a coprime to n.
+/// It is the least common multiple of λ(p^e) over the prime powers p^e,
+/// which is φ(p^e) except for 2^e with e >= 3, where it is 2^(e - 2).
+/// As λ(n) divides φ(n) this does not overflow.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::carmichael_lambda;
+///
+/// assert_eq!(12, carmichael_lambda(360_u32));
+/// assert_eq!(2, carmichael_lambda(8_u32));
+/// ```
+///
+pub fn carmichael_lambda<T: PrimInt + Integer>(n: T) -> T {
+    let two = T::one() + T::one();
+    factor(n).iter().fold(T::one(), |lambda, (&p, &e)| {
+        let lambda_pe = if p == two && e >= 3 {
+            p.pow(e - 2)
+        } else {
+            (p - T::one()) * p.pow(e - 1)
+        };
+        lambda.lcm(&lambda_pe)
+    })
+}
+
+
+/// Returns the Dedekind psi function ψ(n) = n ∏ (1 + 1 / p)
+/// over the prime factors p of n,
+/// or None if it does not fit into u128.
+///
+/// This is the product of p^(e - 1) (p + 1) over the prime powers p^e,
+/// it is the index of the congruence subgroup Γ_0(n) in SL_2(Z).
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::dedekind_psi;
+///
+/// assert_eq!(Some(864), dedekind_psi(360_u32));
+/// assert_eq!(Some(1), dedekind_psi(1_u32));
+/// ```
+///
+pub fn dedekind_psi<T: PrimInt + Integer>(n: T) -> Option<u128> {
+    let mut psi = 1_u128;
+    for (p, &e) in factor(n).iter() {
+        let p = p.to_u128().unwrap();
+        psi = psi.checked_mul(p.checked_add(1)?)?;
+        for _ in 1..e {
+            psi = psi.checked_mul(p)?;
+        }
+    }
+    Some(psi)
+}
+
+
+/// Evaluates a multiplicative function at n,
+/// given by its values f(p, e) on the prime powers p^e.
+///
+/// This factors n and returns the product of f(p, e) over the prime
+/// powers in n, so custom functions can be defined without
+/// handling the factorization.
+///
+/// n must be positive.
+///
+/// ```
+/// use jfactor::evaluate_multiplicative;
+///
+/// // the sum of the odd divisors
+/// let sigma_odd = |n: u64| {
+///     evaluate_multiplicative(n, |p, e| {
+///         if p == 2 { 1 } else { (p.pow(e + 1) - 1) / (p - 1) }
+///     })
+/// };
+/// assert_eq!(13, sigma_odd(36));
+/// assert_eq!(1, sigma_odd(1024));
+/// ```
+///
+pub fn evaluate_multiplicative<T, U, F>(n: T, f: F) -> U
+where
+    T: PrimInt + Integer,
+    U: One + Mul<Output = U>,
+    F: FnMut(T, u32) -> U,
+{
+    factor(n).evaluate_multiplicative(f)
+}
+
+
+/// Returns the Dirichlet convolution f * g of two arithmetic functions
+/// given as tables, (f * g)(n) = Σ f(d) g(n / d) over the divisors d of n.
+///
+/// The tables are indexed by n from 1 on, the entry at index 0
+/// is ignored and set to zero in the result, like in the sieves
+/// `phi_sieve` and `moebius_sieve`.
+/// The result is as long as the shorter table.
+/// This takes time O(N log N) for tables of length N.
+///
+/// ```
+/// use jfactor::{dirichlet_convolve, moebius_sieve, phi_sieve};
+///
+/// // Id * μ = φ
+/// let id = (0..=100).collect::<Vec<i64>>();
+/// let mu = moebius_sieve(100).into_iter().map(i64::from).collect::<Vec<_>>();
+/// let phi = phi_sieve(100).into_iter().map(i64::from).collect::<Vec<_>>();
+/// assert_eq!(phi, dirichlet_convolve(&id, &mu));
+/// ```
+///
+pub fn dirichlet_convolve<T>(f: &[T], g: &[T]) -> Vec<T>
+where
+    T: Copy + Zero + Mul<Output = T>,
+{
+    let len = f.len().min(g.len());
+    let mut h = vec![T::zero(); len];
+    for d in 1..len {
+        for k in 1..=(len - 1) / d {
+            h[d * k] = h[d * k] + f[d] * g[k];
+        }
+    }
+    h
+}
+
+
+/// Replaces the table of f by the Dirichlet convolution f * g.
+///
+/// This is like `dirichlet_convolve`, but needs no extra memory.
+/// g must be at least as long as f.
+///
+/// ```
+/// use jfactor::dirichlet_convolve_in_place;
+///
+/// // 1 * 1 = τ
+/// let mut f = vec![1; 13];
+/// dirichlet_convolve_in_place(&mut f, &[1; 13]);
+/// assert_eq!(6, f[12]);
+/// ```
+///
+pub fn dirichlet_convolve_in_place<T>(f: &mut [T], g: &[T])
+where
+    T: Copy + Zero + Mul<Output = T>,
+{
+    assert!(g.len() >= f.len(), "g is shorter than f");
+
+    // going down from the top, f(d) is still unchanged when it is used,
+    // as the contributions to f(d) come from its smaller divisors
+    let len = f.len();
+    if len > 0 {
+        f[0] = T::zero();
+    }
+    for d in (1..len).rev() {
+        for k in 2..=(len - 1) / d {
+            f[d * k] = f[d * k] + f[d] * g[k];
+        }
+        f[d] = f[d] * g[1];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use divisors::{num_divisors, sum_of_divisors};
+
+    use super::*;
+
+    #[test]
+    fn test_radical() {
+        assert_eq!(2, radical(1_u64 << 63));
+        assert_eq!(4294967291, radical(4294967291_u64 * 4294967291));
+        assert_eq!(30030, radical(30030_u32));
+        let p = 18446744073709551557_u128;
+        assert_eq!(2 * p, radical(8 * p));
+    }
+
+    #[test]
+    fn test_square_free_decomposition() {
+        for n in 1..10000_u32 {
+            let (s, f) = square_free_decomposition(n);
+            assert_eq!(n, s * s * f);
+            assert!(factor(f).iter().all(|(_, &e)| e == 1));
+        }
+        assert_eq!((1 << 63, 1), square_free_decomposition(1_u128 << 126));
+        let m = 1000003_u128 * 4294967291;
+        assert_eq!((m, 6), square_free_decomposition(6 * m * m));
+    }
+
+    #[test]
+    fn test_euler_phi() {
+        for n in 1..1000_u32 {
+            let expected = (1..=n).filter(|k| k.gcd(&n) == 1).count() as u32;
+            assert_eq!(expected, euler_phi(n));
+        }
+        let p = 18446744073709551557_u128;
+        assert_eq!(p - 1, euler_phi(p));
+        assert_eq!(1 << 126, euler_phi(1_u128 << 127));
+        assert_eq!(9208981628670443520, euler_phi(u64::MAX));
+        assert_eq!(169875107699410294159549716941399654400, euler_phi(u128::MAX));
+    }
+
+    #[test]
+    fn test_jordan_totient() {
+        for n in 1..300_u32 {
+            assert_eq!(Some(euler_phi(n) as u128), jordan_totient(n, 1));
+            // the number of pairs (a, b) with gcd(a, b, n) = 1
+            let pairs = (1..=n)
+                .map(|a| (1..=n).filter(|b| a.gcd(b).gcd(&n) == 1).count() as u128)
+                .sum::<u128>();
+            assert_eq!(Some(pairs), jordan_totient(n, 2));
+        }
+        assert_eq!(Some(0), jordan_totient(12_u32, 0));
+        assert_eq!(Some(1), jordan_totient(1_u32, 0));
+        assert_eq!(Some((1 << 127) - 1), jordan_totient(2_u32, 127));
+        assert_eq!(Some((1 << 64) * ((1 << 64) - 1)), jordan_totient(4_u32, 64));
+        assert_eq!(None, jordan_totient(8_u32, 64));
+    }
+
+    #[test]
+    fn test_liouville() {
+        assert_eq!(-1, liouville(1_u128 << 127));
+        assert_eq!(-1, liouville(u64::MAX));
+        assert_eq!(-1, liouville(u128::MAX));
+    }
+
+    #[test]
+    fn test_omega() {
+        assert_eq!(0, omega(1_u32));
+        assert_eq!(0, big_omega(1_u32));
+        assert_eq!(1, omega(1_u128 << 127));
+        assert_eq!(127, big_omega(1_u128 << 127));
+        assert_eq!(9, omega(u128::MAX));
+        assert_eq!(9, big_omega(u128::MAX));
+    }
+
+    #[test]
+    fn test_von_mangoldt() {
+        for n in 1..1000_u32 {
+            let expected = (2..=n)
+                .find(|&p| n % p == 0)
+                .and_then(|p| (1..32).find(|&e| p.checked_pow(e) == Some(n)).map(|e| (p, e)));
+            assert_eq!(expected, von_mangoldt(n));
+        }
+        assert_eq!(Some((2, 127)), von_mangoldt(1_u128 << 127));
+        assert_eq!(None, von_mangoldt(u128::MAX));
+        let p = 18446744073709551557_u128;
+        assert_eq!(Some((p, 2)), von_mangoldt(p * p));
+    }
+
+    #[test]
+    fn test_von_mangoldt_f64() {
+        // the Chebyshev function ψ(100)
+        let psi = (1..=100_u32).map(von_mangoldt_f64).sum::<f64>();
+        assert!((psi - 94.045).abs() < 0.001);
+        assert_eq!(0.0, von_mangoldt_f64(1_u64));
+    }
+
+    #[test]
+    fn test_carmichael_lambda() {
+        fn order(a: u64, n: u64) -> u64 {
+            let mut x = a % n;
+            let mut k = 1;
+            while x != 1 % n {
+                x = x * a % n;
+                k += 1;
+            }
+            k
+        }
+
+        for n in 1..500_u64 {
+            let expected = (1..=n)
+                .filter(|a| a.gcd(&n) == 1)
+                .map(|a| order(a, n))
+                .fold(1, |l, k| l.lcm(&k));
+            assert_eq!(expected, carmichael_lambda(n), "{}", n);
+        }
+        assert_eq!(1 << 125, carmichael_lambda(1_u128 << 127));
+        let p = 18446744073709551557_u128;
+        assert_eq!(p - 1, carmichael_lambda(4 * p));
+    }
+
+    #[test]
+    fn test_dedekind_psi() {
+        for n in 1..1000_u32 {
+            // ψ(n) = n Σ μ(d)^2 / d over the divisors d
+            let expected = (1..=n)
+                .filter(|&d| n % d == 0 && square_free_decomposition(d).0 == 1)
+                .map(|d| (n / d) as u128)
+                .sum::<u128>();
+            assert_eq!(Some(expected), dedekind_psi(n));
+        }
+        assert_eq!(Some(3 << 126), dedekind_psi(1_u128 << 127));
+        assert_eq!(None, dedekind_psi(3_u128 << 126));
+        assert_eq!(None, dedekind_psi(u128::MAX));
+    }
+
+    #[test]
+    fn test_evaluate_multiplicative() {
+        for n in 1..1000_u64 {
+            assert_eq!(euler_phi(n), evaluate_multiplicative(n, |p, e| (p - 1) * p.pow(e - 1)));
+            assert_eq!(radical(n), evaluate_multiplicative(n, |p, _| p));
+            let lambda = evaluate_multiplicative(n, |_, e| if e % 2 == 0 { 1 } else { -1 });
+            assert_eq!(liouville(n), lambda);
+        }
+    }
+
+    #[test]
+    fn test_dirichlet_convolve() {
+        let n = 1000;
+        let one = vec![1_i64; n + 1];
+        let id = (0..=n as i64).collect::<Vec<_>>();
+        let mut tau = vec![0; n + 1];
+        let mut sigma = vec![0; n + 1];
+        for k in 1..=n {
+            tau[k] = num_divisors(k as u32) as i64;
+            sigma[k] = sum_of_divisors(k as u32).unwrap() as i64;
+        }
+        assert_eq!(tau, dirichlet_convolve(&one, &one));
+        assert_eq!(sigma, dirichlet_convolve(&id, &one));
+        assert_eq!(sigma, dirichlet_convolve(&one, &id));
+        assert_eq!(vec![0, 1, 2], dirichlet_convolve(&one[..3], &one));
+        assert!(dirichlet_convolve::<i64>(&[], &one).is_empty());
+
+        let mut f = one.clone();
+        dirichlet_convolve_in_place(&mut f, &id);
+        assert_eq!(sigma, f);
+        let mut f = id.clone();
+        dirichlet_convolve_in_place(&mut f, &one);
+        assert_eq!(sigma, f);
+        // the identity for convolution is 1 at 1 and 0 elsewhere
+        let mut epsilon = vec![0; n + 1];
+        epsilon[1] = 1;
+        let mut f = sigma.clone();
+        dirichlet_convolve_in_place(&mut f, &epsilon);
+        assert_eq!(sigma, f);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_radical_zero() {
+        radical(0_u32);
+    }
+}
@@ -0,0 +1,197 @@
+
+extern crate num;
+
+use self::num::integer::gcd;
+use self::num::{Integer, NumCast, PrimInt};
+
+use arith::{Modulus, Word};
+use sieve::prime_table;
+
+
+/// The number of stage 1 primes between gcd computations.
+const STAGE_1_BATCH_SIZE: usize = 64;
+
+
+/// Finds a factor using Pollard's p-1 algorithm.
+///
+/// This finds a prime factor p of n if all prime powers dividing p - 1
+/// are at most b1, except possibly a single prime up to b2.
+/// The runtime is roughly proportional to b1 plus the number of primes
+/// up to b2, independently of the size of p.
+///
+/// Returns None if no nontrivial factor was found,
+/// in particular if n is less than 4 or prime.
+/// The returned factor may be composite.
+///
+pub fn pollard_p_minus_1<T: PrimInt + Integer>(n: T, b1: u32, b2: u32) -> Option<T> {
+    if n < NumCast::from(4).unwrap() {
+        None
+    } else if let Some(n) = n.to_u32() {
+        p_minus_1_word(n, b1, b2).map(|f| NumCast::from(f).unwrap())
+    } else if let Some(n) = n.to_u64() {
+        p_minus_1_word(n, b1, b2).map(|f| NumCast::from(f).unwrap())
+    } else {
+        p_minus_1_word(n.to_u128().unwrap(), b1, b2).map(|f| NumCast::from(f).unwrap())
+    }
+}
+
+
+/// Finds a factor of a machine word using Pollard's p-1 algorithm.
+///
+/// n must be at least 4.
+///
+pub(crate) fn p_minus_1_word<T: Word>(n: T, b1: u32, b2: u32) -> Option<T> {
+    p_minus_1_with_primes(n, b1, &prime_table(b1.max(b2)))
+}
+
+
+/// Finds a factor of a machine word using Pollard's p-1 algorithm,
+/// with the primes up to b2 given.
+///
+/// n must be at least 4.
+///
+pub(crate) fn p_minus_1_with_primes<T: Word>(n: T, b1: u32, primes: &[u32]) -> Option<T> {
+    if n & T::one() == T::zero() {
+        return Some(NumCast::from(2).unwrap());
+    }
+
+    let m = T::Modulus::new(n);
+    let one = m.one();
+    let stage_1_len = primes.iter().take_while(|&&p| p <= b1).count();
+
+    // stage 1: compute a = 2^E where E is the product of all
+    // prime powers up to b1
+    let mut a = m.to_residue(NumCast::from(2).unwrap());
+    let mut saved_a = a;
+    for (i, chunk) in primes[..stage_1_len].chunks(STAGE_1_BATCH_SIZE).enumerate() {
+        for &p in chunk {
+            a = m.pow_u64(a, max_power(p, b1));
+        }
+        let g = gcd(m.sub(a, one), n);
+        if g == n {
+            // all factors appeared in this batch, repeat it one prime at a time
+            a = saved_a;
+            let start = i * STAGE_1_BATCH_SIZE;
+            for &p in &primes[start..start + chunk.len()] {
+                let mut q = p as u64;
+                while q <= b1 as u64 {
+                    a = m.pow_u64(a, p as u64);
+                    let g = gcd(m.sub(a, one), n);
+                    if g == n {
+                        return None;
+                    } else if g != T::one() {
+                        return Some(g);
+                    }
+                    q *= p as u64;
+                }
+            }
+            return None;
+        } else if g != T::one() {
+            return Some(g);
+        }
+        saved_a = a;
+    }
+
+    // stage 2: compute the product of a^q - 1 for primes b1 < q <= b2,
+    // stepping from prime to prime with precomputed powers a^d
+    // for the even differences d
+    let stage_2_primes = &primes[stage_1_len..];
+    if stage_2_primes.is_empty() {
+        return None;
+    }
+    let mut a_powers = vec![one];
+    let max_gap = stage_2_primes
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .max()
+        .unwrap_or(0);
+    let a2 = m.mul(a, a);
+    while 2 * (a_powers.len() - 1) < max_gap as usize {
+        let last = *a_powers.last().unwrap();
+        a_powers.push(m.mul(last, a2));
+    }
+
+    let mut x = m.pow_u64(a, stage_2_primes[0] as u64);
+    let mut product = m.sub(x, one);
+    for w in stage_2_primes.windows(2) {
+        x = m.mul(x, a_powers[(w[1] - w[0]) as usize / 2]);
+        product = m.mul(product, m.sub(x, one));
+    }
+    let g = gcd(product, n);
+    if g != T::one() && g != n {
+        Some(g)
+    } else {
+        None
+    }
+}
+
+
+/// Returns the largest power of p which is at most limit, for p <= limit.
+///
+pub(crate) fn max_power(p: u32, limit: u32) -> u64 {
+    let p = p as u64;
+    let limit = limit as u64;
+    let mut q = p;
+    while q * p <= limit {
+        q *= p;
+    }
+    q
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_power() {
+        assert_eq!(512, max_power(2, 1000));
+        assert_eq!(729, max_power(3, 1000));
+        assert_eq!(997, max_power(997, 1000));
+    }
+
+    #[test]
+    fn test_stage_1() {
+        // 1000003 - 1 = 2 * 3 * 166667 is not smooth,
+        // 1065601 - 1 = 2^7 * 3^2 * 5^2 * 37 is
+        let n = 1000003_u64 * 1065601;
+        assert_eq!(Some(1065601), pollard_p_minus_1(n, 100, 100));
+    }
+
+    #[test]
+    fn test_stage_2() {
+        // 1000081 - 1 = 2^4 * 3^3 * 5 * 463
+        let n = 1000003_u64 * 1000081;
+        assert_eq!(None, pollard_p_minus_1(n, 30, 462));
+        assert_eq!(Some(1000081), pollard_p_minus_1(n, 30, 463));
+    }
+
+    #[test]
+    fn test_backtrack() {
+        // 1065601 - 1 = 2^7 * 3^2 * 5^2 * 37 and 2000147 - 1 = 2 * 37 * 151 * 179
+        // are found by the same batch
+        let n = 1065601_u64 * 2000147;
+        let f = pollard_p_minus_1(n, 1000, 1000);
+        assert!(f == Some(1065601) || f == Some(2000147));
+    }
+
+    #[test]
+    fn test_u128() {
+        // 34359738587 - 1 = 2 * 19 * 487 * 587 * 3163
+        // and 309485009821345068724793413 - 1 has the factor 393316099663620121
+        let p = 34359738587_u128;
+        let n = p * 309485009821345068724793413;
+        assert_eq!(Some(p), pollard_p_minus_1(n, 1000, 100000));
+    }
+
+    #[test]
+    fn test_prime() {
+        assert_eq!(None, pollard_p_minus_1(1000003_u32, 1000, 10000));
+    }
+
+    #[test]
+    fn test_small() {
+        assert_eq!(None, pollard_p_minus_1(3_u32, 1000, 10000));
+        assert_eq!(Some(2), pollard_p_minus_1(6_u32, 1000, 10000));
+    }
+}
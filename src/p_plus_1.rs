@@ -0,0 +1,265 @@
+
+extern crate num;
+
+use self::num::integer::gcd;
+use self::num::{Integer, NumCast, PrimInt};
+
+use arith::{Modulus, Word};
+use p_minus_1::max_power;
+use sieve::prime_table;
+
+
+/// The number of stage 1 primes between gcd computations.
+const STAGE_1_BATCH_SIZE: usize = 64;
+
+/// The starting values tried in turn.
+///
+/// For each seed A the method succeeds where it would for p - 1
+/// or for p + 1, depending on whether A^2 - 4 is a quadratic residue mod p.
+/// The seeds give the discriminants 5, 12 and 32 with different squarefree
+/// parts, so at least one of them works for p + 1 with probability 7/8.
+///
+const SEEDS: [u32; 3] = [3, 4, 6];
+
+/// The giant step width for stage 2, the product of the smallest primes.
+const STAGE_2_WIDTH: u32 = 210;
+
+
+/// Finds a factor using Williams' p+1 algorithm.
+///
+/// This finds a prime factor p of n if all prime powers dividing p + 1
+/// are at most b1, except possibly a single prime up to b2.
+/// As a side effect it also finds factors which p-1 would find.
+/// A few different starting values are tried, which increases the
+/// runtime accordingly.
+///
+/// Returns None if no nontrivial factor was found,
+/// in particular if n is less than 4 or prime.
+/// The returned factor may be composite.
+///
+pub fn williams_p_plus_1<T: PrimInt + Integer>(n: T, b1: u32, b2: u32) -> Option<T> {
+    if n < NumCast::from(4).unwrap() {
+        return None;
+    }
+    let primes = prime_table(b1.max(b2));
+    if let Some(n) = n.to_u32() {
+        p_plus_1_with_primes(n, b1, &primes).map(|f| NumCast::from(f).unwrap())
+    } else if let Some(n) = n.to_u64() {
+        p_plus_1_with_primes(n, b1, &primes).map(|f| NumCast::from(f).unwrap())
+    } else {
+        p_plus_1_with_primes(n.to_u128().unwrap(), b1, &primes)
+            .map(|f| NumCast::from(f).unwrap())
+    }
+}
+
+
+/// Finds a factor of a machine word using Williams' p+1 algorithm,
+/// with the primes up to b2 given.
+///
+/// n must be at least 4.
+///
+pub(crate) fn p_plus_1_with_primes<T: Word>(n: T, b1: u32, primes: &[u32]) -> Option<T> {
+    if n & T::one() == T::zero() {
+        return Some(NumCast::from(2).unwrap());
+    }
+    let m = T::Modulus::new(n);
+    SEEDS
+        .iter()
+        .filter_map(|&seed| p_plus_1_seed(&m, seed, b1, primes))
+        .next()
+}
+
+
+/// Runs both stages of p+1 with the Lucas sequence V(A) for A = seed.
+///
+fn p_plus_1_seed<T: Word>(m: &T::Modulus, seed: u32, b1: u32, primes: &[u32]) -> Option<T> {
+    let n = m.modulus();
+    let two = m.add(m.one(), m.one());
+    let stage_1_len = primes.iter().take_while(|&&p| p <= b1).count();
+
+    // stage 1: compute v = V_E(A) where E is the product of all
+    // prime powers up to b1, using V_mn(A) = V_m(V_n(A))
+    let mut v = m.to_residue(NumCast::from(seed).unwrap());
+    let mut saved_v = v;
+    for (i, chunk) in primes[..stage_1_len].chunks(STAGE_1_BATCH_SIZE).enumerate() {
+        for &p in chunk {
+            v = lucas_v(m, v, max_power(p, b1));
+        }
+        let g = gcd(m.sub(v, two), n);
+        if g == n {
+            // all factors appeared in this batch, repeat it one prime at a time
+            v = saved_v;
+            let start = i * STAGE_1_BATCH_SIZE;
+            for &p in &primes[start..start + chunk.len()] {
+                let mut q = p as u64;
+                while q <= b1 as u64 {
+                    v = lucas_v(m, v, p as u64);
+                    let g = gcd(m.sub(v, two), n);
+                    if g == n {
+                        return None;
+                    } else if g != T::one() {
+                        return Some(g);
+                    }
+                    q *= p as u64;
+                }
+            }
+            return None;
+        } else if g != T::one() {
+            return Some(g);
+        }
+        saved_v = v;
+    }
+
+    // stage 2: a prime q = k w + j or k w - j divides the order if and only if
+    // V_kw(v) = V_j(v) mod p, so multiply together all the differences
+    let stage_2_primes = &primes[stage_1_len..];
+    if stage_2_primes.is_empty() {
+        return None;
+    }
+    let w = STAGE_2_WIDTH;
+
+    // baby steps V_j for odd j <= w / 2, using V_(j+2) = V_j V_2 - V_(j-2)
+    // and V_-1 = V_1
+    let v2 = m.sub(m.mul(v, v), two);
+    let mut baby = vec![v, m.sub(m.mul(v2, v), v)];
+    while baby.len() <= w as usize / 4 {
+        let len = baby.len();
+        baby.push(m.sub(m.mul(baby[len - 1], v2), baby[len - 2]));
+    }
+
+    // giant steps V_kw, using V_(k+1)w = V_kw V_w - V_(k-1)w
+    // and V_-w = V_w
+    let vw = lucas_v(m, v, w as u64);
+    let mut k = stage_2_primes[0] / w;
+    let mut giant = lucas_v(m, v, k as u64 * w as u64);
+    let mut giant_prev = if k == 0 {
+        vw
+    } else {
+        lucas_v(m, v, (k as u64 - 1) * w as u64)
+    };
+
+    let mut product = m.one();
+    for &q in stage_2_primes {
+        while q > k * w + w / 2 {
+            let next = m.sub(m.mul(giant, vw), giant_prev);
+            giant_prev = giant;
+            giant = next;
+            k += 1;
+        }
+        let j = q.abs_diff(k * w);
+        if j % 2 == 1 {
+            product = m.mul(product, m.sub(giant, baby[j as usize / 2]));
+        } else {
+            // only happens for q = 2
+            product = m.mul(product, m.sub(lucas_v(m, v, q as u64), two));
+        }
+    }
+    let g = gcd(product, n);
+    if g != T::one() && g != n {
+        Some(g)
+    } else {
+        None
+    }
+}
+
+
+/// Computes the Lucas sequence value V_k(a) for residues.
+///
+/// The sequence is defined by V_0 = 2, V_1 = a and
+/// V_(i+1) = a V_i - V_(i-1).
+/// It is computed with a ladder on the pairs (V_i, V_(i+1)), using
+/// V_2i = V_i^2 - 2 and V_(2i+1) = V_i V_(i+1) - a.
+///
+pub(crate) fn lucas_v<T: Word>(m: &T::Modulus, a: T, k: u64) -> T {
+    let two = m.add(m.one(), m.one());
+    if k == 0 {
+        return two;
+    }
+    let mut x = a;
+    let mut y = m.sub(m.mul(a, a), two);
+    let bits = 64 - k.leading_zeros();
+    for i in (0..bits - 1).rev() {
+        if (k >> i) & 1 == 1 {
+            x = m.sub(m.mul(x, y), a);
+            y = m.sub(m.mul(y, y), two);
+        } else {
+            y = m.sub(m.mul(x, y), a);
+            x = m.sub(m.mul(x, x), two);
+        }
+    }
+    x
+}
+
+
+#[cfg(test)]
+mod tests {
+    use arith::PlainModulus;
+
+    use super::*;
+
+    #[test]
+    fn test_lucas_v() {
+        // V(3): 2, 3, 7, 18, 47, 123, 322, 843, ...
+        let m = PlainModulus::new(1_000_000_007_u32);
+        let expected = [2, 3, 7, 18, 47, 123, 322, 843];
+        for (k, &v) in expected.iter().enumerate() {
+            assert_eq!(v, lucas_v(&m, 3_u32, k as u64));
+        }
+    }
+
+    #[test]
+    fn test_lucas_v_composition() {
+        let m = PlainModulus::new(1_000_000_007_u32);
+        assert_eq!(lucas_v(&m, 3_u32, 35), lucas_v(&m, lucas_v(&m, 3, 5), 7));
+    }
+
+    // for the cofactor 1000003 neither
+    // 1000003 - 1 = 2 * 3 * 166667 nor 1000003 + 1 = 2^2 * 53^2 * 89
+    // is smooth for the bounds used
+
+    #[test]
+    fn test_stage_1() {
+        // 1000159 + 1 = 2^5 * 5 * 7 * 19 * 47 and 1000159 - 1 = 2 * 3 * 166693
+        let p = 1000159_u64;
+        let n = p * 1000003;
+        assert_eq!(Some(p), williams_p_plus_1(n, 100, 100));
+    }
+
+    #[test]
+    fn test_stage_2() {
+        // 1000367 + 1 = 2^4 * 3^2 * 6947 and 1000367 - 1 = 2 * 197 * 2539
+        let p = 1000367_u64;
+        let n = p * 1000003;
+        assert_eq!(None, williams_p_plus_1(n, 30, 6946));
+        assert_eq!(Some(p), williams_p_plus_1(n, 30, 6947));
+    }
+
+    #[test]
+    fn test_stage_2_small_primes() {
+        // 1006739 + 1 = 2^2 * 3^2 * 5 * 7 * 17 * 47 and 1006739 - 1 = 2 * 503369,
+        // the stage 2 prime is below the giant step width
+        let p = 1006739_u64;
+        let n = p * 1000003;
+        assert_eq!(None, williams_p_plus_1(n, 20, 46));
+        assert_eq!(Some(p), williams_p_plus_1(n, 20, 47));
+    }
+
+    #[test]
+    fn test_u128() {
+        // 34359739021 + 1 = 2 * 7 * 59 * 421 * 98807
+        let p = 34359739021_u128;
+        let n = p * 309485009821345068724793413;
+        assert_eq!(Some(p), williams_p_plus_1(n, 1000, 100000));
+    }
+
+    #[test]
+    fn test_prime() {
+        assert_eq!(None, williams_p_plus_1(1000003_u32, 1000, 10000));
+    }
+
+    #[test]
+    fn test_small() {
+        assert_eq!(None, williams_p_plus_1(3_u32, 1000, 10000));
+        assert_eq!(Some(2), williams_p_plus_1(6_u32, 1000, 10000));
+    }
+}
@@ -0,0 +1,85 @@
+
+extern crate rayon;
+
+use self::rayon::prelude::*;
+
+use factor::factor;
+use factorization::Factorization;
+use range::factor_range;
+
+
+/// The minimum number of integers factored by `factor_many` per work item.
+const FACTOR_CHUNK_SIZE: usize = 64;
+
+/// The number of integers sieved by `par_factor_range` per work item.
+const RANGE_CHUNK_SIZE: u64 = 1 << 16;
+
+
+/// Factors many integers in parallel.
+///
+/// The work is distributed in small chunks over rayon's global thread pool,
+/// whose work stealing balances inputs which take very different times.
+/// The factorizations are returned in the order of the input.
+///
+/// This is only available with the `parallel` feature.
+///
+/// All integers must be positive.
+///
+pub fn factor_many(ns: &[u64]) -> Vec<Factorization<u64>> {
+    ns.par_iter()
+        .with_min_len(FACTOR_CHUNK_SIZE)
+        .map(|&n| factor(n))
+        .collect()
+}
+
+
+/// Factors all integers in the interval from start to start + len - 1
+/// in parallel.
+///
+/// This is like `factor_range`, with parts of the interval sieved
+/// on rayon's global thread pool, and returns the factorizations in
+/// ascending order of n.
+///
+/// This is only available with the `parallel` feature.
+///
+/// start must be positive and start + len must not overflow.
+///
+pub fn par_factor_range(start: u64, len: u64) -> Vec<(u64, Factorization<u64>)> {
+    assert!(start > 0);
+    assert!(start.checked_add(len).is_some(), "end of range overflows");
+
+    let chunks = len.div_ceil(RANGE_CHUNK_SIZE);
+    (0..chunks)
+        .into_par_iter()
+        .flat_map_iter(|i| {
+            let chunk_start = start + i * RANGE_CHUNK_SIZE;
+            let chunk_len = RANGE_CHUNK_SIZE.min(start + len - chunk_start);
+            factor_range(chunk_start, chunk_len)
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_factor_many() {
+        let ns = (1..10000_u64)
+            .map(|i| i * 4294967291 % 1000000007 + 1)
+            .collect::<Vec<_>>();
+        let expected = ns.iter().map(|&n| factor(n)).collect::<Vec<_>>();
+        assert_eq!(expected, factor_many(&ns));
+        assert!(factor_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_par_factor_range() {
+        let start = 1_000_000_000;
+        let len = 3 * RANGE_CHUNK_SIZE + 5;
+        let expected = factor_range(start, len).collect::<Vec<_>>();
+        assert_eq!(expected, par_factor_range(start, len));
+        assert!(par_factor_range(1, 0).is_empty());
+    }
+}
@@ -0,0 +1,346 @@
+
+extern crate num;
+extern crate num_bigint;
+
+use self::num::{Integer, One, ToPrimitive, Zero};
+use self::num_bigint::BigUint;
+
+use big::{from_u128, to_u128};
+use factor::factor;
+use factorization::Factorization;
+use primality::{bpsw_is_prime_big, is_prime};
+use sieve::{prime_table, PRIME_TABLE_LIMIT};
+
+
+/// The number of bases tried when searching for a witness.
+const MAX_WITNESS: u32 = 1000;
+
+
+/// A Pocklington-Lehmer certificate proving that an integer n is prime.
+///
+/// It consists of prime factors q of n - 1 whose product F,
+/// counting multiplicities, exceeds the square root of n,
+/// each with a witness a such that a^(n - 1) = 1 mod n
+/// and a^((n - 1) / q) - 1 is coprime to n.
+/// Then every prime factor of n is 1 mod F, so n is prime.
+///
+/// Factors below 2^64 are checked with the deterministic primality test,
+/// larger factors need their own certificate.
+/// Integers below 2^64 need no factors at all.
+///
+/// ```
+/// extern crate jfactor;
+/// extern crate num_bigint;
+///
+/// use num_bigint::BigUint;
+///
+/// use jfactor::prove_prime_pocklington;
+///
+/// # fn main() {
+/// // 6 * 2^200 + 1
+/// let n = (BigUint::from(6_u32) << 200) + BigUint::from(1_u32);
+/// let certificate = prove_prime_pocklington(&n).unwrap();
+/// assert!(certificate.verify());
+/// # }
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PocklingtonCertificate {
+    prime: BigUint,
+    factors: Vec<PocklingtonFactor>,
+}
+
+impl PocklingtonCertificate {
+    /// Creates a certificate from its parts without checking it.
+    ///
+    pub fn new(prime: BigUint, factors: Vec<PocklingtonFactor>) -> PocklingtonCertificate {
+        PocklingtonCertificate { prime, factors }
+    }
+
+    /// Returns the prime this certificate is for.
+    ///
+    pub fn prime(&self) -> &BigUint {
+        &self.prime
+    }
+
+    /// Returns the factors of n - 1 with their witnesses.
+    ///
+    pub fn factors(&self) -> &[PocklingtonFactor] {
+        &self.factors
+    }
+
+    /// Checks the certificate, returns true if it proves
+    /// that the integer is prime.
+    ///
+    pub fn verify(&self) -> bool {
+        let n = &self.prime;
+        if let Some(n) = n.to_u64() {
+            return is_prime(n);
+        }
+
+        let one = BigUint::one();
+        let n_minus_1 = n - &one;
+        let mut f = BigUint::one();
+        for factor in &self.factors {
+            let q = &factor.prime;
+            let q_is_prime = match q.to_u64() {
+                Some(q) => is_prime(q),
+                None => factor
+                    .certificate
+                    .as_ref()
+                    .is_some_and(|c| &c.prime == q && c.verify()),
+            };
+            if !q_is_prime || factor.exponent == 0 {
+                return false;
+            }
+            for _ in 0..factor.exponent {
+                f *= q;
+            }
+
+            let a = &factor.witness;
+            if a.modpow(&n_minus_1, n) != one {
+                return false;
+            }
+            let x = a.modpow(&(&n_minus_1 / q), n);
+            let x_minus_1 = (x + &n_minus_1) % n;
+            if !x_minus_1.gcd(n).is_one() {
+                return false;
+            }
+        }
+        (&n_minus_1 % &f).is_zero() && &(&f * &f) > n
+    }
+}
+
+
+/// A prime factor q of n - 1 in a Pocklington-Lehmer certificate.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PocklingtonFactor {
+    prime: BigUint,
+    exponent: u32,
+    witness: BigUint,
+    certificate: Option<PocklingtonCertificate>,
+}
+
+impl PocklingtonFactor {
+    /// Creates a factor from its parts without checking it.
+    ///
+    /// The certificate is needed if the prime is at least 2^64.
+    ///
+    pub fn new(
+        prime: BigUint,
+        exponent: u32,
+        witness: BigUint,
+        certificate: Option<PocklingtonCertificate>,
+    ) -> PocklingtonFactor {
+        PocklingtonFactor {
+            prime,
+            exponent,
+            witness,
+            certificate,
+        }
+    }
+
+    /// Returns the prime factor q.
+    ///
+    pub fn prime(&self) -> &BigUint {
+        &self.prime
+    }
+
+    /// Returns the exponent of q in F.
+    ///
+    pub fn exponent(&self) -> u32 {
+        self.exponent
+    }
+
+    /// Returns the witness for q.
+    ///
+    pub fn witness(&self) -> &BigUint {
+        &self.witness
+    }
+
+    /// Returns the certificate proving that q is prime, if needed.
+    ///
+    pub fn certificate(&self) -> Option<&PocklingtonCertificate> {
+        self.certificate.as_ref()
+    }
+}
+
+
+/// Creates a Pocklington-Lehmer certificate proving that n is prime.
+///
+/// This factors n - 1 by trial division and, if the remaining cofactor
+/// is prime or fits into 128 bits, completely.
+/// It returns None if n is not prime or if the factored part of n - 1
+/// is not larger than the square root of n,
+/// use `pocklington_certificate` to supply the factors in that case.
+///
+pub fn prove_prime_pocklington(n: &BigUint) -> Option<PocklingtonCertificate> {
+    if !bpsw_is_prime_big(n) {
+        return None;
+    }
+    if n.to_u64().is_some() {
+        return Some(PocklingtonCertificate::new(n.clone(), Vec::new()));
+    }
+
+    let mut factored = Factorization::new();
+    let mut rest = n - BigUint::one();
+    for &p in prime_table(PRIME_TABLE_LIMIT).iter() {
+        let d = BigUint::from(p);
+        loop {
+            let (q, r) = rest.div_rem(&d);
+            if !r.is_zero() {
+                break;
+            }
+            rest = q;
+            factored.insert(d.clone(), 1);
+        }
+    }
+    if let Some(r) = to_u128(&rest) {
+        if r > 1 {
+            for (q, e) in factor(r) {
+                factored.insert(from_u128(q), e);
+            }
+        }
+    } else if bpsw_is_prime_big(&rest) {
+        factored.insert(rest, 1);
+    }
+    pocklington_certificate(n, &factored)
+}
+
+
+/// Creates a Pocklington-Lehmer certificate proving that n is prime,
+/// from a partial factorization of n - 1.
+///
+/// The factored part must divide n - 1 and be larger than
+/// the square root of n.
+/// Prime factors of at least 2^64 are proven prime recursively
+/// with `prove_prime_pocklington`.
+/// Returns None if n is not prime or no certificate could be created.
+///
+pub fn pocklington_certificate(
+    n: &BigUint,
+    factored: &Factorization<BigUint>,
+) -> Option<PocklingtonCertificate> {
+    if !bpsw_is_prime_big(n) {
+        return None;
+    }
+    if n.to_u64().is_some() {
+        return Some(PocklingtonCertificate::new(n.clone(), Vec::new()));
+    }
+
+    let one = BigUint::one();
+    let n_minus_1 = n - &one;
+    let mut f = BigUint::one();
+    for (q, &e) in factored.iter() {
+        for _ in 0..e {
+            f *= q;
+        }
+    }
+    if !(&n_minus_1 % &f).is_zero() || &(&f * &f) <= n {
+        return None;
+    }
+
+    let mut factors = Vec::new();
+    for (q, &e) in factored.iter() {
+        let certificate = match q.to_u64() {
+            Some(q) if is_prime(q) => None,
+            Some(_) => return None,
+            None => Some(prove_prime_pocklington(q)?),
+        };
+        // as n is prime a^((n - 1) / q) = 1 with probability 1 / q
+        let e_q = &n_minus_1 / q;
+        let witness = (2..MAX_WITNESS)
+            .map(BigUint::from)
+            .find(|a| a.modpow(&e_q, n) != one)?;
+        factors.push(PocklingtonFactor::new(q.clone(), e, witness, certificate));
+    }
+    Some(PocklingtonCertificate::new(n.clone(), factors))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(n: u128) -> BigUint {
+        from_u128(n)
+    }
+
+    #[test]
+    fn test_small() {
+        let c = prove_prime_pocklington(&big(18446744073709551557)).unwrap();
+        assert!(c.factors().is_empty());
+        assert!(c.verify());
+        assert!(prove_prime_pocklington(&big(18446744073709551559)).is_none());
+        assert!(!PocklingtonCertificate::new(big(91), Vec::new()).verify());
+    }
+
+    #[test]
+    fn test_u128() {
+        let n = big((1 << 127) - 1);
+        let c = prove_prime_pocklington(&n).unwrap();
+        assert_eq!(&n, c.prime());
+        assert!(c.verify());
+        assert!(prove_prime_pocklington(&big((1 << 127) + 1)).is_none());
+    }
+
+    #[test]
+    fn test_proth() {
+        let n = (big(6) << 200) + big(1);
+        let c = prove_prime_pocklington(&n).unwrap();
+        assert!(c.verify());
+        assert!(prove_prime_pocklington(&((big(5) << 200) + big(1))).is_none());
+    }
+
+    #[test]
+    fn test_recursive() {
+        // a safe prime 2 q + 1, where q has its own certificate
+        let q = big(1267650600228229401496703206331);
+        let n = big(2) * &q + big(1);
+        let c = prove_prime_pocklington(&n).unwrap();
+        assert!(c.verify());
+        let factor = c.factors().iter().find(|f| f.prime() == &q).unwrap();
+        assert!(factor.certificate().is_some());
+    }
+
+    #[test]
+    fn test_partial() {
+        // only the power of 2 is supplied
+        let n = (big(6) << 200) + big(1);
+        let mut factored = Factorization::new();
+        factored.insert(big(2), 201);
+        let c = pocklington_certificate(&n, &factored).unwrap();
+        assert_eq!(1, c.factors().len());
+        assert!(c.verify());
+
+        // the factored part is too small
+        let mut factored = Factorization::new();
+        factored.insert(big(2), 100);
+        assert!(pocklington_certificate(&n, &factored).is_none());
+    }
+
+    #[test]
+    fn test_verify_invalid() {
+        let n = (big(6) << 200) + big(1);
+        let c = prove_prime_pocklington(&n).unwrap();
+        let factors = c.factors().to_vec();
+
+        // the factored part 3 is too small
+        assert!(!PocklingtonCertificate::new(n.clone(), factors[1..].to_vec()).verify());
+
+        // a bad witness
+        let mut bad = factors.clone();
+        bad[0] = PocklingtonFactor::new(big(2), 201, big(1), None);
+        assert!(!PocklingtonCertificate::new(n.clone(), bad).verify());
+
+        // a composite
+        let m = (big(6) << 200) + big(3);
+        assert!(!PocklingtonCertificate::new(m, factors).verify());
+
+        // a large factor without certificate
+        let q = big(1267650600228229401496703206331);
+        let f = PocklingtonFactor::new(q.clone(), 1, big(2), None);
+        assert!(!PocklingtonCertificate::new(big(2) * q + big(1), vec![f]).verify());
+    }
+}
@@ -0,0 +1,232 @@
+
+extern crate num;
+
+use self::num::{Integer, NumCast, PrimInt};
+
+use arith::{Modulus, Word};
+use factor::factor;
+use primality::is_prime;
+
+
+/// A Pratt certificate proving that an integer is prime.
+///
+/// It consists of a witness a of order p - 1 modulo p,
+/// together with the factorization of p - 1 and certificates
+/// for its prime factors.
+/// As the multiplicative group modulo p has an element of order p - 1
+/// only if p is prime, it can be checked with a few modular
+/// exponentiations, without trusting the code which created it.
+///
+/// ```
+/// use jfactor::prove_prime;
+///
+/// let certificate = prove_prime(1000003_u64).unwrap();
+/// assert_eq!(1000003, certificate.prime());
+/// assert!(certificate.verify());
+/// assert!(prove_prime(1000001_u64).is_none());
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PrattCertificate<T> {
+    prime: T,
+    witness: T,
+    factors: Vec<(PrattCertificate<T>, u32)>,
+}
+
+impl<T: PrimInt + Integer> PrattCertificate<T> {
+    /// Creates a certificate from its parts without checking it.
+    ///
+    /// The factors are the certificates for the prime factors of p - 1
+    /// with their exponents.
+    ///
+    pub fn new(prime: T, witness: T, factors: Vec<(PrattCertificate<T>, u32)>) -> PrattCertificate<T> {
+        PrattCertificate {
+            prime,
+            witness,
+            factors,
+        }
+    }
+
+    /// Returns the prime this certificate is for.
+    ///
+    pub fn prime(&self) -> T {
+        self.prime
+    }
+
+    /// Returns the witness of order p - 1 modulo p.
+    ///
+    pub fn witness(&self) -> T {
+        self.witness
+    }
+
+    /// Returns the certificates for the prime factors of p - 1
+    /// with their exponents.
+    ///
+    pub fn factors(&self) -> &[(PrattCertificate<T>, u32)] {
+        &self.factors
+    }
+
+    /// Checks the certificate, returns true if it proves
+    /// that the integer is prime.
+    ///
+    /// 2 needs no witness and no factors.
+    ///
+    pub fn verify(&self) -> bool {
+        let p = match self.prime.to_u128() {
+            Some(p) => p,
+            None => return false,
+        };
+        if p == 2 {
+            return true;
+        }
+        if p < 2 || p % 2 == 0 {
+            return false;
+        }
+
+        // the factors must multiply to p - 1 and be proven prime
+        let mut product = 1_u128;
+        let mut qs = Vec::new();
+        for &(ref certificate, e) in &self.factors {
+            let q = match certificate.prime.to_u128() {
+                Some(q) => q,
+                None => return false,
+            };
+            product = match q.checked_pow(e).and_then(|qe| product.checked_mul(qe)) {
+                Some(product) => product,
+                None => return false,
+            };
+            if e == 0 || !certificate.verify() {
+                return false;
+            }
+            qs.push(q);
+        }
+        if product != p - 1 {
+            return false;
+        }
+
+        let a = match self.witness.to_u128() {
+            Some(a) if 0 < a && a < p => a,
+            _ => return false,
+        };
+        has_order_p_minus_1(a, p, &qs)
+    }
+}
+
+
+/// Creates a Pratt certificate proving that p is prime,
+/// or returns None if p is not prime.
+///
+/// This needs to factor p - 1 and recursively the prime factors of that,
+/// which is fast for machine words.
+///
+pub fn prove_prime<T: PrimInt + Integer>(p: T) -> Option<PrattCertificate<T>> {
+    if is_prime(p) {
+        Some(certify(p))
+    } else {
+        None
+    }
+}
+
+
+fn certify<T: PrimInt + Integer>(p: T) -> PrattCertificate<T> {
+    let two = T::one() + T::one();
+    if p == two {
+        return PrattCertificate::new(p, T::one(), Vec::new());
+    }
+
+    let factorization = factor(p - T::one());
+    let factors = factorization
+        .iter()
+        .map(|(&q, &e)| (certify(q), e))
+        .collect::<Vec<_>>();
+
+    // a random element has order p - 1 with probability φ(p - 1) / (p - 1),
+    // so the search is short
+    let p_u128 = p.to_u128().unwrap();
+    let qs = factorization.primes().map(|q| q.to_u128().unwrap()).collect::<Vec<_>>();
+    let a = (2..).find(|&a| has_order_p_minus_1(a, p_u128, &qs)).unwrap();
+    PrattCertificate::new(p, NumCast::from(a).unwrap(), factors)
+}
+
+
+/// Returns true if a has order p - 1 modulo the odd integer p,
+/// where qs are the prime factors of p - 1.
+///
+fn has_order_p_minus_1(a: u128, p: u128, qs: &[u128]) -> bool {
+    if p >> 64 == 0 {
+        let qs = qs.iter().map(|&q| q as u64).collect::<Vec<_>>();
+        has_order_p_minus_1_word(a as u64, p as u64, &qs)
+    } else {
+        has_order_p_minus_1_word(a, p, qs)
+    }
+}
+
+fn has_order_p_minus_1_word<W: Word>(a: W, p: W, qs: &[W]) -> bool {
+    let m = W::Modulus::new(p);
+    let one = m.one();
+    let a = m.to_residue(a);
+    let p_minus_1 = p - W::one();
+    m.pow(a, p_minus_1) == one && qs.iter().all(|&q| m.pow(a, p_minus_1 / q) != one)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prove_prime() {
+        for p in 0..2000_u32 {
+            match prove_prime(p) {
+                Some(certificate) => {
+                    assert!(is_prime(p));
+                    assert_eq!(p, certificate.prime());
+                    assert!(certificate.verify());
+                }
+                None => assert!(!is_prime(p)),
+            }
+        }
+        assert!(prove_prime(-7_i32).is_none());
+    }
+
+    #[test]
+    fn test_prove_prime_large() {
+        let p = 18446744073709551557_u64;
+        assert!(prove_prime(p).unwrap().verify());
+        let p = (1_u128 << 89) - 1;
+        assert!(prove_prime(p).unwrap().verify());
+        assert!(prove_prime((1_u128 << 89) + 1).is_none());
+    }
+
+    #[test]
+    fn test_structure() {
+        let c = prove_prime(7_u32).unwrap();
+        assert_eq!(3, c.witness());
+        let factors = c.factors();
+        assert_eq!(2, factors.len());
+        assert_eq!((2, 1), (factors[0].0.prime(), factors[0].1));
+        assert_eq!((3, 1), (factors[1].0.prime(), factors[1].1));
+    }
+
+    #[test]
+    fn test_verify_invalid() {
+        let two = PrattCertificate::new(2_u64, 1, Vec::new());
+        let three = PrattCertificate::new(3, 2, vec![(two.clone(), 1)]);
+        assert!(three.verify());
+        // 4 has order 2 modulo 5
+        let five = PrattCertificate::new(5, 4, vec![(two.clone(), 2)]);
+        assert!(!five.verify());
+        // the factors do not multiply to p - 1
+        let five = PrattCertificate::new(5, 2, vec![(two.clone(), 1)]);
+        assert!(!five.verify());
+        // no element has order 8 modulo 9
+        let nine = PrattCertificate::new(9, 2, vec![(two.clone(), 3)]);
+        assert!(!nine.verify());
+        // the certificate for a factor is invalid
+        let bad_three = PrattCertificate::new(3, 1, vec![(two.clone(), 1)]);
+        let seven = PrattCertificate::new(7, 3, vec![(two.clone(), 1), (bad_three, 1)]);
+        assert!(!seven.verify());
+        // an even integer
+        assert!(!PrattCertificate::new(4, 3, vec![(three, 1)]).verify());
+    }
+}
@@ -0,0 +1,846 @@
+
+extern crate num;
+extern crate num_bigint;
+
+use self::num::{Integer, NumCast, One, PrimInt, ToPrimitive, Zero};
+use self::num_bigint::BigUint;
+
+use arith::{exact_sqrt, Modulus, Word};
+use big::sqrt_big;
+
+
+/// The primes used for trial division before the probable prime tests.
+///
+const SMALL_PRIMES: [u32; 20] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71,
+];
+
+/// Bases which give a deterministic test for n < 4759123141 (Jaeschke).
+///
+const BASES_32: [u32; 3] = [2, 7, 61];
+
+/// Bases which give a deterministic test for all n < 2^64 (Sinclair).
+///
+const BASES_64: [u32; 7] = [2, 325, 9375, 28178, 450775, 9780504, 1795265022];
+
+
+/// Tests whether an integer is prime.
+///
+/// For integers below 2^64 this is a deterministic Miller-Rabin test,
+/// above that it is the Baillie-PSW test, see `bpsw_is_prime`.
+/// Integers less than 2, including all negative integers, are not prime.
+///
+/// ```
+/// use jfactor::is_prime;
+///
+/// assert!(is_prime(4294967291_u32));
+/// assert!(!is_prime(3825123056546413051_u64));
+/// assert!(is_prime((1_u128 << 127) - 1));
+/// ```
+///
+pub fn is_prime<T: PrimInt + Integer>(n: T) -> bool {
+    if n < T::zero() {
+        false
+    } else if let Some(n) = n.to_u32() {
+        is_prime_word(n)
+    } else if let Some(n) = n.to_u64() {
+        is_prime_word(n)
+    } else {
+        is_prime_word(n.to_u128().unwrap())
+    }
+}
+
+
+/// Returns the smallest prime greater than n,
+/// or None if it does not fit into the type.
+///
+/// ```
+/// use jfactor::next_prime;
+///
+/// assert_eq!(Some(600851475149), next_prime(600851475143_u64));
+/// assert_eq!(None, next_prime(4294967291_u32));
+/// ```
+///
+pub fn next_prime<T: PrimInt + Integer>(n: T) -> Option<T> {
+    let two = T::one() + T::one();
+    if n < two {
+        return Some(two);
+    }
+    // the next odd integer
+    let mut candidate = if n.is_even() {
+        n.checked_add(&T::one())?
+    } else {
+        n.checked_add(&two)?
+    };
+    while !is_prime(candidate) {
+        candidate = candidate.checked_add(&two)?;
+    }
+    Some(candidate)
+}
+
+
+/// Returns the largest prime less than n,
+/// or None if there is none.
+///
+/// ```
+/// use jfactor::prev_prime;
+///
+/// assert_eq!(Some(18446744073709551557), prev_prime(u64::MAX));
+/// assert_eq!(None, prev_prime(2_u32));
+/// ```
+///
+pub fn prev_prime<T: PrimInt + Integer>(n: T) -> Option<T> {
+    let two = T::one() + T::one();
+    let three = two + T::one();
+    if n <= two {
+        return None;
+    }
+    if n == three {
+        return Some(two);
+    }
+    // the previous odd integer
+    let mut candidate = if n.is_even() { n - T::one() } else { n - two };
+    while !is_prime(candidate) {
+        candidate = candidate - two;
+    }
+    Some(candidate)
+}
+
+
+/// Tests whether an integer is prime using the Baillie-PSW test.
+///
+/// This is a strong probable prime test to base 2 followed by
+/// a strong Lucas probable prime test with Selfridge's parameters.
+/// It is known to be correct below 2^64, and although composites
+/// passing it probably exist, none is known.
+/// Integers less than 2, including all negative integers, are not prime.
+///
+/// ```
+/// use jfactor::bpsw_is_prime;
+///
+/// assert!(bpsw_is_prime(18446744073709551557_u64));
+/// assert!(!bpsw_is_prime(2047_u32));
+/// ```
+///
+pub fn bpsw_is_prime<T: PrimInt + Integer>(n: T) -> bool {
+    if n < T::zero() {
+        false
+    } else if let Some(n) = n.to_u32() {
+        bpsw_word(n)
+    } else if let Some(n) = n.to_u64() {
+        bpsw_word(n)
+    } else {
+        bpsw_word(n.to_u128().unwrap())
+    }
+}
+
+
+/// Tests whether a big integer is prime using the Baillie-PSW test.
+///
+/// See `bpsw_is_prime`.
+///
+pub fn bpsw_is_prime_big(n: &BigUint) -> bool {
+    if let Some(result) = trial_division_big(n) {
+        return result;
+    }
+    miller_rabin_big(n, &[2]) && is_strong_lucas_prp_big(n)
+}
+
+
+/// Tests whether an integer is a strong Lucas probable prime.
+///
+/// This uses Selfridge's parameters P = 1 and Q = (1 - D) / 4,
+/// where D is the first of 5, -7, 9, -11, ... with Jacobi symbol
+/// (D/n) = -1.
+/// All primes pass the test, the composites that pass are called
+/// strong Lucas pseudoprimes.
+/// Together with a strong probable prime test to base 2
+/// this gives the Baillie-PSW test, see `bpsw_is_prime`.
+/// Integers less than 2, including all negative integers, do not pass.
+///
+/// ```
+/// use jfactor::is_strong_lucas_prp;
+///
+/// assert!(is_strong_lucas_prp(1000003_u32));
+/// // the smallest strong Lucas pseudoprime
+/// assert!(is_strong_lucas_prp(5459_u32));
+/// assert!(!is_strong_lucas_prp(5461_u32));
+/// ```
+///
+pub fn is_strong_lucas_prp<T: PrimInt + Integer>(n: T) -> bool {
+    if n < T::zero() {
+        false
+    } else if let Some(n) = n.to_u32() {
+        strong_lucas(n)
+    } else if let Some(n) = n.to_u64() {
+        strong_lucas(n)
+    } else {
+        strong_lucas(n.to_u128().unwrap())
+    }
+}
+
+
+/// Tests whether an integer is a strong probable prime
+/// to each of the given bases with the Miller-Rabin test.
+///
+/// For odd n > 2 write n - 1 = 2^s d with d odd,
+/// then n passes for base b if b^d = 1 mod n
+/// or b^(2^r d) = -1 mod n for some r < s.
+/// All primes pass, a composite passing for base b
+/// is a strong pseudoprime to base b, see `is_strong_pseudoprime`.
+/// The bases are reduced modulo n and those divisible by n are skipped,
+/// there is no trial division, so the result depends only on the bases.
+/// Integers less than 2, including all negative integers, do not pass,
+/// 2 passes and even integers above it do not.
+///
+/// ```
+/// use jfactor::miller_rabin;
+///
+/// assert!(miller_rabin(1000003_u32, &[2, 3, 5]));
+/// // a strong pseudoprime to the first eleven prime bases
+/// let n = 3825123056546413051_u64;
+/// assert!(miller_rabin(n, &[2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31]));
+/// assert!(!miller_rabin(n, &[37]));
+/// ```
+///
+pub fn miller_rabin<T: PrimInt + Integer>(n: T, bases: &[T]) -> bool {
+    let two = T::one() + T::one();
+    if n < two {
+        return false;
+    }
+    if n.is_even() {
+        return n == two;
+    }
+    let bases = bases.iter().map(|b| b.mod_floor(&n).to_u128().unwrap());
+    if let Some(n) = n.to_u32() {
+        strong_test(n, bases.map(|b| b as u32))
+    } else if let Some(n) = n.to_u64() {
+        strong_test(n, bases.map(|b| b as u64))
+    } else {
+        strong_test(n.to_u128().unwrap(), bases)
+    }
+}
+
+
+/// Tests whether an integer is a strong pseudoprime to the given base,
+/// that is a composite which is a strong probable prime to that base.
+///
+/// Bases divisible by n are never witnesses, so for them this is false,
+/// as it is for primes and integers less than 3.
+/// See `miller_rabin` for the test itself.
+///
+/// ```
+/// use jfactor::is_strong_pseudoprime;
+///
+/// // the smallest strong pseudoprime to base 2
+/// assert!(is_strong_pseudoprime(2047_u32, 2));
+/// assert!(!is_strong_pseudoprime(2047_u32, 3));
+/// assert!(!is_strong_pseudoprime(2053_u32, 2));
+/// ```
+///
+pub fn is_strong_pseudoprime<T: PrimInt + Integer>(n: T, base: T) -> bool {
+    let three = T::one() + T::one() + T::one();
+    n >= three && !base.mod_floor(&n).is_zero() && miller_rabin(n, &[base]) && !is_prime(n)
+}
+
+
+/// Tests a machine word for primality,
+/// with bases chosen by the size of n.
+///
+pub(crate) fn is_prime_word<T: Word>(n: T) -> bool {
+    match n.to_u64() {
+        Some(m) if m < 4759123141 => miller_rabin_word(n, &BASES_32),
+        Some(_) => miller_rabin_word(n, &BASES_64),
+        None => bpsw_word(n),
+    }
+}
+
+
+fn bpsw_word<T: Word>(n: T) -> bool {
+    miller_rabin_word(n, &[2]) && strong_lucas(n)
+}
+
+
+/// Returns whether n is prime if trial division by the small primes
+/// decides it.
+///
+fn trial_division<T: Word>(n: T) -> Option<bool> {
+    let two: T = NumCast::from(2).unwrap();
+    if n < two {
+        return Some(false);
+    }
+    for &p in SMALL_PRIMES.iter() {
+        let p: T = NumCast::from(p).unwrap();
+        if n == p {
+            return Some(true);
+        }
+        if n % p == T::zero() {
+            return Some(false);
+        }
+    }
+    None
+}
+
+
+/// Tests n with the Miller-Rabin test for the given bases,
+/// after trial division by the primes up to 71.
+///
+fn miller_rabin_word<T: Word>(n: T, bases: &[u32]) -> bool {
+    if let Some(result) = trial_division(n) {
+        return result;
+    }
+    // the bases may be larger than n
+    strong_test(n, bases.iter().map(|&b| NumCast::from(b).map(|b: T| b % n).unwrap()))
+}
+
+
+/// Tests whether an odd n > 2 is a strong probable prime
+/// to each of the bases, which must be less than n.
+/// Bases 0 are skipped.
+///
+fn strong_test<T: Word, I: Iterator<Item = T>>(n: T, bases: I) -> bool {
+    let n_minus_1 = n - T::one();
+    let s = n_minus_1.trailing_zeros();
+    let d = n_minus_1 >> s as usize;
+
+    let m = T::Modulus::new(n);
+    let one = m.one();
+    let minus_one = m.to_residue(n_minus_1);
+    'witness: for b in bases {
+        if b == T::zero() {
+            continue;
+        }
+        let mut x = m.pow(m.to_residue(b), d);
+        if x == one || x == minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = m.mul(x, x);
+            if x == minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+
+/// Computes the Jacobi symbol (a/n) for odd n.
+///
+pub(crate) fn jacobi<T: Word>(a: T, n: T) -> i32 {
+    let three: T = NumCast::from(3).unwrap();
+    let seven: T = NumCast::from(7).unwrap();
+    let mut a = a % n;
+    let mut n = n;
+    let mut result = 1;
+    while a != T::zero() {
+        let s = a.trailing_zeros();
+        a = a >> s as usize;
+        let n_mod_8 = (n & seven).to_u32().unwrap();
+        if s % 2 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+            result = -result;
+        }
+        if a & three == three && n & three == three {
+            result = -result;
+        }
+        let r = n % a;
+        n = a;
+        a = r;
+    }
+    if n == T::one() {
+        result
+    } else {
+        0
+    }
+}
+
+
+/// Finds Selfridge's parameter D for the Lucas test,
+/// the first of 5, -7, 9, -11, ... with Jacobi symbol (D/n) = -1.
+///
+/// Returns None if (D/n) = 0 for one of them first,
+/// then n is composite if it does not divide D.
+/// The caller should map (D/n) to 1 when n divides D to skip it.
+///
+fn selfridge_d<F: Fn(i64) -> i32>(jacobi: F) -> Option<i64> {
+    let mut d: i64 = 5;
+    loop {
+        match jacobi(d) {
+            -1 => return Some(d),
+            0 => return None,
+            _ => {}
+        }
+        d = if d > 0 { -(d + 2) } else { -d + 2 };
+    }
+}
+
+
+/// Tests whether n is a strong Lucas probable prime
+/// with Selfridge's parameters P = 1 and Q = (1 - D) / 4.
+///
+fn strong_lucas<T: Word>(n: T) -> bool {
+    let two: T = NumCast::from(2).unwrap();
+    if n <= two {
+        return n == two;
+    }
+    if n & T::one() == T::zero() || exact_sqrt(n).is_some() {
+        return false;
+    }
+
+    // the residue of a small signed integer
+    let signed = |x: i64| -> T {
+        let r: T = NumCast::from(x.unsigned_abs()).map(|x: T| x % n).unwrap();
+        if x < 0 && r != T::zero() {
+            n - r
+        } else {
+            r
+        }
+    };
+    let d = match selfridge_d(|d| {
+        let r = signed(d);
+        if r == T::zero() { 1 } else { jacobi(r, n) }
+    }) {
+        Some(d) => d,
+        None => return false,
+    };
+    let q = (1 - d) / 4;
+
+    // n + 1 = k 2^s, where n + 1 may overflow
+    let (k, s) = if n == T::max_value() {
+        (T::one(), T::zero().count_zeros())
+    } else {
+        let n_plus_1 = n + T::one();
+        let s = n_plus_1.trailing_zeros();
+        (n_plus_1 >> s as usize, s)
+    };
+
+    let m = T::Modulus::new(n);
+    let d = m.to_residue(signed(d));
+    let q = m.to_residue(signed(q));
+    let half = |x: T| -> T {
+        if x & T::one() == T::zero() {
+            x >> 1
+        } else {
+            // (x + n) / 2 without overflow
+            (x >> 1) + (n >> 1) + T::one()
+        }
+    };
+
+    // compute U_k, V_k and Q^k from the most significant bit of k down
+    let mut u = m.one();
+    let mut v = m.one();
+    let mut q_k = q;
+    let bits = T::zero().count_zeros() - k.leading_zeros();
+    for i in (0..bits - 1).rev() {
+        u = m.mul(u, v);
+        v = m.sub(m.mul(v, v), m.add(q_k, q_k));
+        q_k = m.mul(q_k, q_k);
+        if (k >> i as usize) & T::one() != T::zero() {
+            let u_next = half(m.add(u, v));
+            v = half(m.add(m.mul(d, u), v));
+            u = u_next;
+            q_k = m.mul(q_k, q);
+        }
+    }
+
+    if u == T::zero() || v == T::zero() {
+        return true;
+    }
+    for _ in 1..s {
+        v = m.sub(m.mul(v, v), m.add(q_k, q_k));
+        if v == T::zero() {
+            return true;
+        }
+        q_k = m.mul(q_k, q_k);
+    }
+    false
+}
+
+
+/// Returns whether the big integer n is prime if trial division
+/// by the small primes decides it.
+///
+fn trial_division_big(n: &BigUint) -> Option<bool> {
+    if n < &BigUint::from(2_u32) {
+        return Some(false);
+    }
+    for &p in SMALL_PRIMES.iter() {
+        let p = BigUint::from(p);
+        if n == &p {
+            return Some(true);
+        }
+        if (n % &p).is_zero() {
+            return Some(false);
+        }
+    }
+    None
+}
+
+
+/// Tests a big integer with the Miller-Rabin test for the given bases.
+///
+/// n must be odd and greater than the bases.
+///
+fn miller_rabin_big(n: &BigUint, bases: &[u32]) -> bool {
+    let one = BigUint::one();
+    let n_minus_1 = n - &one;
+    let mut s = 0;
+    let mut d = n_minus_1.clone();
+    while d.is_even() {
+        d >>= 1;
+        s += 1;
+    }
+
+    'witness: for &b in bases.iter() {
+        let mut x = BigUint::from(b).modpow(&d, n);
+        if x == one || x == n_minus_1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = &x * &x % n;
+            if x == n_minus_1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+
+/// Computes the Jacobi symbol (a/n) for odd n.
+///
+fn jacobi_big(a: &BigUint, n: &BigUint) -> i32 {
+    let mut a = a % n;
+    let mut n = n.clone();
+    let mut result = 1;
+    while !a.is_zero() {
+        let mut s = 0;
+        while a.is_even() {
+            a >>= 1;
+            s += 1;
+        }
+        let n_mod_8 = (&n % BigUint::from(8_u32)).to_u32().unwrap();
+        if s % 2 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+            result = -result;
+        }
+        let a_mod_4 = (&a % BigUint::from(4_u32)).to_u32().unwrap();
+        if a_mod_4 == 3 && n_mod_8 % 4 == 3 {
+            result = -result;
+        }
+        let r = &n % &a;
+        n = a;
+        a = r;
+    }
+    if n.is_one() {
+        result
+    } else {
+        0
+    }
+}
+
+
+/// Tests whether a big integer is a strong Lucas probable prime.
+///
+/// See `is_strong_lucas_prp`.
+///
+pub fn is_strong_lucas_prp_big(n: &BigUint) -> bool {
+    let two = BigUint::from(2_u32);
+    if n <= &two {
+        return n == &two;
+    }
+    if n.is_even() {
+        return false;
+    }
+    let root = sqrt_big(n);
+    if &(&root * &root) == n {
+        return false;
+    }
+
+    // the residue of a small signed integer
+    let signed = |x: i64| -> BigUint {
+        let r = BigUint::from(x.unsigned_abs()) % n;
+        if x < 0 && !r.is_zero() {
+            n - r
+        } else {
+            r
+        }
+    };
+    let d = match selfridge_d(|d| {
+        let r = signed(d);
+        if r.is_zero() { 1 } else { jacobi_big(&r, n) }
+    }) {
+        Some(d) => d,
+        None => return false,
+    };
+    let q = signed((1 - d) / 4);
+    let d = signed(d);
+    let half = |x: BigUint| -> BigUint {
+        if x.is_even() {
+            x >> 1
+        } else {
+            (x + n) >> 1
+        }
+    };
+    let double_sub = |v: &BigUint, q_k: &BigUint| -> BigUint {
+        (v * v + n + n - q_k - q_k) % n
+    };
+
+    let n_plus_1 = n + BigUint::one();
+    let mut s = 0;
+    let mut k = n_plus_1;
+    while k.is_even() {
+        k >>= 1;
+        s += 1;
+    }
+
+    // compute U_k, V_k and Q^k from the most significant bit of k down
+    let mut u = BigUint::one();
+    let mut v = BigUint::one();
+    let mut q_k = q.clone();
+    for bit in k.to_str_radix(2).bytes().skip(1) {
+        u = &u * &v % n;
+        v = double_sub(&v, &q_k);
+        q_k = &q_k * &q_k % n;
+        if bit == b'1' {
+            let u_next = half((&u + &v) % n);
+            v = half((&d * &u + &v) % n);
+            u = u_next;
+            q_k = &q_k * &q % n;
+        }
+    }
+
+    if u.is_zero() || v.is_zero() {
+        return true;
+    }
+    for _ in 1..s {
+        v = double_sub(&v, &q_k);
+        if v.is_zero() {
+            return true;
+        }
+        q_k = &q_k * &q_k % n;
+    }
+    false
+}
+
+
+#[cfg(test)]
+mod tests {
+    use big::from_u128;
+    use sieve::primes_up_to;
+
+    use super::*;
+
+    #[test]
+    fn test_is_prime() {
+        let primes: Vec<u32> = (0..1_000_000).filter(|&n| is_prime(n)).collect();
+        assert_eq!(primes_up_to(1_000_000), primes);
+        assert!(!is_prime(-7_i32));
+        assert!(!is_prime(0_u8));
+        assert!(is_prime(251_u8));
+        assert!(is_prime(4294967291_u32));
+        assert!(!is_prime(u32::MAX));
+    }
+
+    #[test]
+    fn test_next_prime() {
+        let primes = primes_up_to(10_000);
+        for (i, &p) in primes.iter().enumerate().skip(1) {
+            for n in primes[i - 1]..p {
+                assert_eq!(Some(p), next_prime(n));
+            }
+        }
+        assert_eq!(Some(2), next_prime(-5_i32));
+        assert_eq!(Some(2), next_prime(1_u8));
+        assert_eq!(Some(251), next_prime(250_u8));
+        assert_eq!(None, next_prime(251_u8));
+        assert_eq!(Some(4294967311), next_prime(4294967291_u64));
+        assert_eq!(None, next_prime(18446744073709551557_u64));
+    }
+
+    #[test]
+    fn test_prev_prime() {
+        let primes = primes_up_to(10_000);
+        for (i, &p) in primes.iter().enumerate().skip(1) {
+            for n in primes[i - 1] + 1..=p {
+                assert_eq!(Some(primes[i - 1]), prev_prime(n));
+            }
+        }
+        assert_eq!(None, prev_prime(-5_i32));
+        assert_eq!(None, prev_prime(2_u8));
+        assert_eq!(Some(2), prev_prime(3_u8));
+        assert_eq!(Some(251), prev_prime(u8::MAX));
+        assert_eq!(Some(4294967291), prev_prime(u32::MAX));
+        assert_eq!(Some((1_u128 << 127) - 1), prev_prime(1_u128 << 127));
+    }
+
+    #[test]
+    fn test_is_prime_pseudoprimes() {
+        // the smallest strong pseudoprime to bases 2, 7 and 61
+        assert!(!is_prime(4759123141_u64));
+        // strong pseudoprimes to base 2
+        for &n in &[2047_u64, 3277, 4033, 4681, 8321, 3215031751, 2152302898747] {
+            assert!(!is_prime(n));
+        }
+        // Carmichael numbers
+        for &n in &[561_u64, 1105, 1729, 2465, 2821, 6601, 8911] {
+            assert!(!is_prime(n));
+        }
+        assert!(!is_prime(3825123056546413051_u64));
+        assert!(!is_prime(318665857834031151167461_u128));
+    }
+
+    #[test]
+    fn test_is_prime_u64() {
+        assert!(is_prime(4759123151_u64));
+        assert!(is_prime(18446744073709551557_u64));
+        assert!(!is_prime(18446744073709551559_u64));
+        assert!(!is_prime(4294967279_u64 * 4294967291));
+        assert!(!is_prime(u64::MAX));
+    }
+
+    #[test]
+    fn test_small() {
+        let primes: Vec<u32> = (0..100).filter(|&n| bpsw_is_prime(n)).collect();
+        assert_eq!(vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47,
+                        53, 59, 61, 67, 71, 73, 79, 83, 89, 97],
+                   primes);
+    }
+
+    #[test]
+    fn test_u64() {
+        assert!(bpsw_is_prime(18446744073709551557_u64));
+        assert!(!bpsw_is_prime(4294967279_u64 * 4294967291));
+        // strong pseudoprime to bases 2, 3, 5, 7, 11, 13, 17, 19, 23
+        assert!(!bpsw_is_prime(3825123056546413051_u64));
+    }
+
+    #[test]
+    fn test_u128() {
+        assert!(bpsw_is_prime((1_u128 << 127) - 1));
+        assert!(bpsw_is_prime(u128::MAX - 158));
+        assert!(!bpsw_is_prime((1_u128 << 127) + 1));
+        assert!(!bpsw_is_prime(18446744073709551557_u128 * 18446744073709551557));
+    }
+
+    #[test]
+    fn test_big() {
+        let one = BigUint::one();
+        assert!(!bpsw_is_prime_big(&one));
+        assert!(bpsw_is_prime_big(&BigUint::from(71_u32)));
+        assert!(bpsw_is_prime_big(&((BigUint::one() << 521) - &one)));
+        assert!(!bpsw_is_prime_big(&((BigUint::one() << 523) - &one)));
+        assert!(!bpsw_is_prime_big(&BigUint::from(3825123056546413051_u64)));
+    }
+
+    #[test]
+    fn test_bpsw() {
+        let primes: Vec<u32> = (0..1_000_000).filter(|&n| bpsw_is_prime(n)).collect();
+        assert_eq!(primes_up_to(1_000_000), primes);
+        assert!(!bpsw_is_prime(-7_i64));
+        // strong pseudoprimes to base 2
+        for &n in &[2047_u64, 3277, 4033, 4681, 8321, 3215031751, 2152302898747] {
+            assert!(!bpsw_is_prime(n));
+        }
+    }
+
+    #[test]
+    fn test_bpsw_big() {
+        for n in (0..10_000_u64).chain(4294967000..4294968000) {
+            assert_eq!(bpsw_is_prime(n), bpsw_is_prime_big(&BigUint::from(n)));
+        }
+        let n = 18446744073709551557_u128 * 18446744073709551557;
+        assert!(!bpsw_is_prime_big(&from_u128(n)));
+        assert!(bpsw_is_prime_big(&from_u128(u128::MAX - 158)));
+    }
+
+    #[test]
+    fn test_strong_lucas() {
+        // strong Lucas pseudoprimes
+        for &n in &[5459_u32, 5777, 10877, 16109, 18971, 22499, 24569, 25199, 40309, 58519] {
+            assert!(strong_lucas(n));
+            assert!(is_strong_lucas_prp_big(&BigUint::from(n)));
+            assert!(!bpsw_is_prime(n));
+        }
+        for &n in &[2_u32, 3, 5, 7, 73, 1000003, 4294967291] {
+            assert!(strong_lucas(n));
+            assert!(is_strong_lucas_prp_big(&BigUint::from(n)));
+        }
+        assert!(!strong_lucas(73_u32 * 73));
+        assert!(!strong_lucas(u32::MAX));
+        assert!(!strong_lucas(u128::MAX));
+        assert!(!strong_lucas(1000003_u64 * 1000033));
+    }
+
+    #[test]
+    fn test_is_strong_lucas_prp() {
+        assert!(!is_strong_lucas_prp(-5459_i32));
+        assert!(!is_strong_lucas_prp(1_u8));
+        assert!(is_strong_lucas_prp(2_u8));
+        assert!(is_strong_lucas_prp(5459_i64));
+        assert!(is_strong_lucas_prp((1_u128 << 127) - 1));
+        assert!(!is_strong_lucas_prp((1_u128 << 127) + 1));
+        let two = BigUint::from(2_u32);
+        assert!(is_strong_lucas_prp_big(&two));
+        assert!(!is_strong_lucas_prp_big(&(&two << 1)));
+    }
+
+    #[test]
+    fn test_miller_rabin() {
+        assert!(!miller_rabin(-7_i32, &[2]));
+        assert!(!miller_rabin(1_u8, &[2]));
+        assert!(miller_rabin(2_u8, &[2]));
+        assert!(!miller_rabin(4_u8, &[3]));
+        // bases divisible by n are skipped, negative bases are reduced
+        assert!(miller_rabin(3_i32, &[3, -3, 6]));
+        assert!(miller_rabin(2047_i32, &[-2047 * 3 + 2]));
+        assert!(miller_rabin(9_u8, &[9]));
+        assert!(!miller_rabin(9_u8, &[2]));
+        let primes = primes_up_to(10000);
+        for n in 3..10000_u32 {
+            let bases = [2, 3, 5];
+            assert_eq!(primes.binary_search(&n).is_ok(), miller_rabin(n, &bases));
+        }
+        let n = 3215031751_u64;
+        assert!(miller_rabin(n, &[2, 3, 5, 7]));
+        assert!(!miller_rabin(n, &[2, 3, 5, 7, 11]));
+        // a strong pseudoprime to the first thirteen prime bases
+        let n = 3317044064679887385961981_u128;
+        assert!(miller_rabin(n, &[2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41]));
+        assert!(!miller_rabin(n, &[43]));
+        assert!(miller_rabin((1_u128 << 127) - 1, &[2, 3, u128::MAX]));
+    }
+
+    #[test]
+    fn test_is_strong_pseudoprime() {
+        let spsp2 = (3..100000_u32)
+            .filter(|&n| is_strong_pseudoprime(n, 2))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                2047, 3277, 4033, 4681, 8321, 15841, 29341, 42799, 49141, 52633, 65281, 74665,
+                80581, 85489, 88357, 90751,
+            ],
+            spsp2
+        );
+        assert!(!is_strong_pseudoprime(2_u8, 2));
+        assert!(!is_strong_pseudoprime(-2047_i32, 2));
+        assert!(!is_strong_pseudoprime(9_u8, 9));
+        assert!(is_strong_pseudoprime(9_u8, 8));
+        assert!(!is_strong_pseudoprime(4294967291_u32, 2));
+        assert!(is_strong_pseudoprime(3825123056546413051_u64, 23));
+        assert!(is_strong_pseudoprime(3317044064679887385961981_u128, 37));
+    }
+
+    #[test]
+    fn test_jacobi() {
+        // (a/15) for a = 0, ..., 14
+        let expected = [0, 1, 1, 0, 1, 0, 0, -1, 1, 0, 0, -1, 0, -1, -1];
+        for (a, &j) in expected.iter().enumerate() {
+            assert_eq!(j, jacobi(a as u32, 15));
+            assert_eq!(j, jacobi_big(&BigUint::from(a as u32), &BigUint::from(15_u32)));
+        }
+        assert_eq!(-1, jacobi(2_u64, 1000003));
+    }
+}
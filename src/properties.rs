@@ -0,0 +1,297 @@
+
+extern crate num;
+
+use self::num::{Integer, NumCast, PrimInt};
+
+use arith::{exact_sqrt, Modulus, Word};
+use factor::factor;
+use primality::is_prime_word;
+use sieve::prime_table;
+
+
+/// The limit for trial division before falling back to factoring.
+const TRIAL_FACTOR_LIMIT: u32 = 1000;
+
+
+/// Returns true if n is the product of exactly two primes,
+/// which may be equal.
+///
+/// This finds the smallest prime factor by trial division where possible,
+/// and then only tests the cofactor for primality.
+/// Once there is no prime factor up to the cube root of n,
+/// n has at most two prime factors and a primality test decides.
+/// Only if n has no small prime factors and is too large for that
+/// it is factored completely.
+/// Integers less than 4, including all negative integers, are not semiprime.
+///
+/// ```
+/// use jfactor::is_semiprime;
+///
+/// assert!(is_semiprime(4_u32));
+/// assert!(is_semiprime(1000003_u64 * 4294967291));
+/// assert!(!is_semiprime(30_u32));
+/// ```
+///
+pub fn is_semiprime<T: PrimInt + Integer>(n: T) -> bool {
+    if n < T::zero() {
+        false
+    } else if let Some(n) = n.to_u32() {
+        is_semiprime_word(n)
+    } else if let Some(n) = n.to_u64() {
+        is_semiprime_word(n)
+    } else {
+        is_semiprime_word(n.to_u128().unwrap())
+    }
+}
+
+fn is_semiprime_word<T: Word>(n: T) -> bool {
+    if n < NumCast::from(4).unwrap() {
+        return false;
+    }
+    for &p in prime_table(TRIAL_FACTOR_LIMIT).iter() {
+        let p: T = NumCast::from(p).unwrap();
+        if n % p == T::zero() {
+            return is_prime_word(n / p);
+        }
+        if n / p / p < p {
+            // no prime factor up to the cube root
+            return !is_prime_word(n);
+        }
+    }
+    !is_prime_word(n) && factor(n).iter().map(|(_, &e)| e).sum::<u32>() == 2
+}
+
+
+/// Returns true if n is not divisible by the square of any prime.
+///
+/// This returns false as soon as trial division finds a prime
+/// which divides n twice.
+/// Once there is no prime factor of the rest up to its cube root,
+/// the rest has at most two prime factors and is squarefree
+/// unless it is a square.
+/// Only if the rest is too large for that it is factored completely.
+/// 1 is squarefree, integers less than 1 are not.
+///
+/// ```
+/// use jfactor::is_squarefree;
+///
+/// assert!(is_squarefree(30_u32));
+/// assert!(!is_squarefree(1000003_u64 * 1000003 * 7));
+/// ```
+///
+pub fn is_squarefree<T: PrimInt + Integer>(n: T) -> bool {
+    if n < T::zero() {
+        false
+    } else if let Some(n) = n.to_u32() {
+        is_squarefree_word(n)
+    } else if let Some(n) = n.to_u64() {
+        is_squarefree_word(n)
+    } else {
+        is_squarefree_word(n.to_u128().unwrap())
+    }
+}
+
+fn is_squarefree_word<T: Word>(n: T) -> bool {
+    if n == T::zero() {
+        return false;
+    }
+    let mut rest = n;
+    for &p in prime_table(TRIAL_FACTOR_LIMIT).iter() {
+        let p: T = NumCast::from(p).unwrap();
+        if rest / p / p < p {
+            // the rest has no prime factor up to its cube root
+            return exact_sqrt(rest).is_none_or(|s| s == T::one());
+        }
+        if rest % p == T::zero() {
+            rest = rest / p;
+            if rest % p == T::zero() {
+                return false;
+            }
+        }
+    }
+    factor(rest).iter().all(|(_, &e)| e == 1)
+}
+
+
+/// Returns the p-adic valuation of n, the largest e with p^e dividing n.
+///
+/// This only divides by p repeatedly, p need not be prime
+/// and n need not be factored.
+///
+/// n must be nonzero and p must be at least 2.
+///
+/// ```
+/// use jfactor::valuation;
+///
+/// assert_eq!(3, valuation(360_u32, 2));
+/// assert_eq!(2, valuation(360_u32, 3));
+/// assert_eq!(0, valuation(360_u32, 7));
+/// ```
+///
+pub fn valuation<T: PrimInt + Integer>(n: T, p: T) -> u32 {
+    assert!(n != T::zero(), "the valuation of 0 is infinite");
+    assert!(p > T::one(), "p must be at least 2");
+
+    if p == T::one() + T::one() {
+        return n.trailing_zeros();
+    }
+    let mut rest = n;
+    let mut e = 0;
+    loop {
+        let (q, r) = rest.div_rem(&p);
+        if r != T::zero() {
+            return e;
+        }
+        rest = q;
+        e += 1;
+    }
+}
+
+
+/// Returns true if n is a Carmichael number,
+/// a composite n with a^(n - 1) = 1 mod n for all a coprime to n.
+///
+/// By Korselt's criterion these are the composite squarefree n
+/// with p - 1 dividing n - 1 for all prime factors p.
+/// Most integers fail the Fermat test to base 2 and are rejected
+/// without factoring, so ranges can be screened quickly.
+///
+/// ```
+/// use jfactor::is_carmichael;
+///
+/// let carmichael: Vec<u32> = (1..10000).filter(|&n| is_carmichael(n)).collect();
+/// assert_eq!(vec![561, 1105, 1729, 2465, 2821, 6601, 8911], carmichael);
+/// ```
+///
+pub fn is_carmichael<T: PrimInt + Integer>(n: T) -> bool {
+    if n < T::zero() {
+        false
+    } else if let Some(n) = n.to_u32() {
+        is_carmichael_word(n)
+    } else if let Some(n) = n.to_u64() {
+        is_carmichael_word(n)
+    } else {
+        is_carmichael_word(n.to_u128().unwrap())
+    }
+}
+
+fn is_carmichael_word<T: Word>(n: T) -> bool {
+    // 561 is the smallest Carmichael number, and all of them are odd
+    if n < NumCast::from(561).unwrap() || n.is_even() {
+        return false;
+    }
+    let m = T::Modulus::new(n);
+    let two = m.to_residue(NumCast::from(2).unwrap());
+    if m.pow(two, n - T::one()) != m.one() {
+        return false;
+    }
+    let factorization = factor(n);
+    factorization.omega() >= 2
+        && factorization
+            .iter()
+            .all(|(&p, &e)| e == 1 && (n - T::one()) % (p - T::one()) == T::zero())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_semiprime() {
+        for n in 0..10000_u32 {
+            let expected = factor(n.max(1)).iter().map(|(_, &e)| e).sum::<u32>() == 2;
+            assert_eq!(expected, is_semiprime(n), "{}", n);
+        }
+        assert!(!is_semiprime(-4_i32));
+    }
+
+    #[test]
+    fn test_is_squarefree() {
+        for n in 0..10000_u32 {
+            let expected = n > 0 && factor(n.max(1)).iter().all(|(_, &e)| e == 1);
+            assert_eq!(expected, is_squarefree(n), "{}", n);
+        }
+        assert!(!is_squarefree(-1_i32));
+    }
+
+    #[test]
+    fn test_is_squarefree_large() {
+        let p = 18446744073709551557_u128;
+        assert!(is_squarefree(4294967291_u64 * 4294967279));
+        assert!(!is_squarefree(4294967291_u64 * 4294967291));
+        assert!(!is_squarefree(1009 * 1009 * 4294967291_u64));
+        assert!(is_squarefree(p * 4294967291));
+        assert!(!is_squarefree(p * p));
+        assert!(is_squarefree(p));
+        assert!(!is_squarefree(p * 1000003 * 1000003));
+        assert!(is_squarefree(p * 1000003 * 1000033));
+        assert!(is_squarefree(u128::MAX));
+    }
+
+    #[test]
+    fn test_is_semiprime_large() {
+        let p = 18446744073709551557_u128;
+        assert!(is_semiprime(1009 * 1013_u64));
+        assert!(is_semiprime(4294967291_u64 * 4294967279));
+        assert!(!is_semiprime(1009 * 4294967291_u128 * 4294967279));
+        assert!(is_semiprime(p * 4294967291));
+        assert!(is_semiprime(p * p));
+        assert!(!is_semiprime(p));
+        assert!(!is_semiprime(p * 1000003 * 1000033));
+        assert!(!is_semiprime(2 * p * 4294967291));
+    }
+
+    #[test]
+    fn test_valuation() {
+        for n in 1..1000_u32 {
+            let f = factor(n);
+            for p in 2..20 {
+                if is_prime_word(p) {
+                    assert_eq!(f.exponent(&p), valuation(n, p));
+                }
+            }
+        }
+        assert_eq!(2, valuation(360_u32, 6));
+        assert_eq!(63, valuation(1_u64 << 63, 2));
+        assert_eq!(80, valuation(3_u128.pow(80), 3));
+        assert_eq!(3, valuation(-1000_i32, 10));
+        assert_eq!(2, valuation(i64::MIN + 1, 7));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_valuation_zero() {
+        valuation(0_u32, 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_valuation_one() {
+        valuation(8_u32, 1);
+    }
+
+    #[test]
+    fn test_is_carmichael() {
+        for n in 0..20000_u32 {
+            let f = factor(n.max(1));
+            let expected = n > 1
+                && f.omega() >= 2
+                && f.iter().all(|(&p, &e)| e == 1 && (n - 1) % (p - 1) == 0);
+            assert_eq!(expected, is_carmichael(n), "{}", n);
+        }
+        assert!(!is_carmichael(-561_i32));
+    }
+
+    #[test]
+    fn test_is_carmichael_large() {
+        // a strong pseudoprime to the bases 2, 3, 5 and 7
+        assert!(is_carmichael(3215031751_u64));
+        assert!(!is_carmichael(3215031751_u64 + 2));
+        // (6 k + 1) (12 k + 1) (18 k + 1) with three primes for k = 1000000511
+        assert!(is_carmichael(1296001987165015643369032371289_u128));
+        // 341 = 11 * 31 is a Fermat pseudoprime to base 2, but 30 does not divide 340
+        assert!(!is_carmichael(341_u32));
+        assert!(!is_carmichael(18446744073709551557_u64));
+    }
+}
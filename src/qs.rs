@@ -0,0 +1,304 @@
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use arith::{Modulus, PlainModulus, Word};
+use modular::{mod_inverse, sqrt_mod_prime};
+use primality::is_prime_word;
+use relations::{factor_base, find_factor, Relation, EXTRA_RELATIONS};
+
+
+/// The sieve parameters for numbers up to the given number of bits:
+/// the size of the factor base and half the width of the sieve interval.
+const PARAMETERS: [(u32, usize, usize); 8] = [
+    (64, 80, 8192),
+    (72, 100, 8192),
+    (80, 150, 8192),
+    (88, 200, 8192),
+    (96, 250, 16384),
+    (104, 300, 16384),
+    (112, 450, 16384),
+    (120, 700, 32768),
+];
+
+/// Primes below this are not sieved, as they are expensive to sieve
+/// and contribute little to the logarithms.
+const SMALL_PRIME_LIMIT: u32 = 32;
+
+/// The tolerance for sieve values below the expected logarithm,
+/// as a multiple of the logarithm of the largest factor base prime.
+const THRESHOLD_TOLERANCE: f64 = 1.5;
+
+/// The number of polynomials after which the sieve gives up.
+const MAX_POLYNOMIALS: usize = 100000;
+
+
+/// Finds a factor using the multiple polynomial quadratic sieve.
+///
+/// The quadratic sieve collects many x with (a x + b)^2 mod n
+/// factoring over a base of small primes, and combines them into
+/// a congruence of squares X^2 = Y^2 mod n, where gcd(X - Y, n) is
+/// a factor with probability at least 1/2.
+/// Its runtime depends only on the size of n, not on the size of the
+/// factors, which makes it the method of choice for balanced semiprimes
+/// from about 60 to 110 bits, where rho and ECM are slow.
+///
+/// This is only available with the `qs` feature.
+///
+/// n should be an odd composite which is not a prime power.
+/// Returns None if no nontrivial factor was found,
+/// in particular if n is less than 4 or prime, or at least 2^120.
+/// The returned factor may be composite.
+///
+pub fn quadratic_sieve(n: u128) -> Option<u128> {
+    qs_with_cancel(n, &AtomicBool::new(false))
+}
+
+
+/// Finds a factor using the quadratic sieve,
+/// giving up when the flag is set.
+///
+pub(crate) fn qs_with_cancel(n: u128, cancel: &AtomicBool) -> Option<u128> {
+    if n < 4 || n >> 120 != 0 {
+        return None;
+    }
+    if n & 1 == 0 {
+        return Some(2);
+    }
+    let s = n.isqrt();
+    if s * s == n {
+        return Some(s);
+    }
+
+    let bits = 128 - n.leading_zeros();
+    let &(_, fb_size, m) = PARAMETERS
+        .iter()
+        .find(|&&(max_bits, _, _)| bits <= max_bits)
+        .unwrap();
+
+    let fb = match factor_base(n, fb_size) {
+        Ok(fb) => fb,
+        Err(p) => return if n != p as u128 { Some(p as u128) } else { None },
+    };
+    let fb = fb
+        .into_iter()
+        .map(|p| FactorBasePrime {
+            p,
+            sqrt_n: sqrt_mod_prime((n % p as u128) as u64, p).unwrap(),
+            log: (p as f64).log2().round() as u8,
+        })
+        .collect::<Vec<_>>();
+    let p_max = fb.last().unwrap().p;
+
+    // the columns are the sign, the prime 2 and the factor base primes
+    let mut column_primes = vec![0, 2];
+    column_primes.extend(fb.iter().map(|fbp| fbp.p));
+    let columns = column_primes.len();
+    let mut relations = Vec::new();
+
+    // a = q^2 for a prime q, chosen such that the values of the polynomial
+    // are about as small at the ends and in the middle of the interval
+    let target_a = (2.0 * n as f64).sqrt() / m as f64;
+    let mut q = (target_a.sqrt() as u64).max(p_max + 1);
+    let mut polynomials = 0;
+    while relations.len() < columns + EXTRA_RELATIONS {
+        polynomials += 1;
+        if polynomials > MAX_POLYNOMIALS || cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        q = next_polynomial_prime(n, q + 1);
+        let polynomial = Polynomial::new(n, q);
+        sieve(n, &polynomial, &fb, m, &mut relations);
+    }
+
+    find_factor(n, &relations, &column_primes)
+}
+
+
+/// A prime in the factor base, with n a square modulo p.
+///
+struct FactorBasePrime {
+    p: u64,
+    /// a square root of n modulo p
+    sqrt_n: u64,
+    /// log2(p), rounded
+    log: u8,
+}
+
+
+/// A polynomial ((a x + b)^2 - n) / a = a x^2 + 2 b x + c with a = q^2.
+///
+struct Polynomial {
+    q: u64,
+    a: i128,
+    b: i128,
+    c: i128,
+}
+
+impl Polynomial {
+    /// Creates the polynomial for a prime q = 3 mod 4 with n a square mod q.
+    ///
+    fn new(n: u128, q: u64) -> Polynomial {
+        let a = q as u128 * q as u128;
+
+        // a square root of n mod q, lifted to a square root mod q^2
+        // using b = b0 + k q with 2 b0 k = (n - b0^2) / q mod q
+        let b0 = PlainModulus::new(q).pow_u64((n % q as u128) as u64, (q + 1) / 4);
+        let n_mod_a = (n % a) as i128;
+        let d = (n_mod_a - b0 as i128 * b0 as i128) / q as i128;
+        let d = d.rem_euclid(q as i128) as u64;
+        let k = u64::mul_mod(d, mod_inverse(2 * b0 % q, q).unwrap(), q);
+        let b = b0 as u128 + k as u128 * q as u128;
+
+        let a = a as i128;
+        let b = b as i128;
+        Polynomial {
+            q,
+            a,
+            b,
+            c: (b * b - n as i128) / a,
+        }
+    }
+
+    /// Returns the value of the polynomial at x.
+    ///
+    fn value(&self, x: i128) -> i128 {
+        (self.a * x + 2 * self.b) * x + self.c
+    }
+}
+
+
+/// Sieves one polynomial over the interval -m..m and adds the relations found.
+///
+fn sieve(
+    n: u128,
+    polynomial: &Polynomial,
+    fb: &[FactorBasePrime],
+    m: usize,
+    relations: &mut Vec<Relation>,
+) {
+    // the offsets in the sieve array of the roots of the polynomial mod p,
+    // which are x = (+-sqrt(n) - b) / a mod p
+    let roots = fb
+        .iter()
+        .map(|fbp| {
+            let p = fbp.p;
+            let a_inv = mod_inverse((polynomial.a % p as i128) as u64, p).unwrap();
+            let b = polynomial.b.rem_euclid(p as i128) as u64;
+            let m = (m as u64) % p;
+            let root = |t: u64| (u64::mul_mod((t + p - b) % p, a_inv, p) + m) % p;
+            (root(fbp.sqrt_n), root(p - fbp.sqrt_n))
+        })
+        .collect::<Vec<_>>();
+
+    let mut sieve = vec![0_u8; 2 * m];
+    for (fbp, &(r1, r2)) in fb.iter().zip(&roots) {
+        let p = fbp.p as usize;
+        if p >= SMALL_PRIME_LIMIT as usize {
+            for r in [r1, r2] {
+                let mut i = r as usize;
+                while i < sieve.len() {
+                    sieve[i] += fbp.log;
+                    i += p;
+                }
+            }
+        }
+    }
+
+    // the values are at most about m sqrt(n / 2) in absolute value
+    let p_max = fb.last().unwrap().p as f64;
+    let expected = (m as f64).log2() + (n as f64).log2() / 2.0 - 0.5;
+    let threshold = (expected - THRESHOLD_TOLERANCE * p_max.log2()).max(0.0) as u8;
+
+    for (i, &s) in sieve.iter().enumerate() {
+        if s < threshold {
+            continue;
+        }
+        let x = i as i128 - m as i128;
+        let value = polynomial.value(x);
+        if value == 0 {
+            continue;
+        }
+
+        let mut factors = Vec::new();
+        if value < 0 {
+            factors.push((0, 1));
+        }
+        let mut rest = value.unsigned_abs();
+        let twos = rest.trailing_zeros();
+        if twos > 0 {
+            factors.push((1, twos));
+            rest >>= twos;
+        }
+        for (j, (fbp, &(r1, r2))) in fb.iter().zip(&roots).enumerate() {
+            let offset = (i as u64) % fbp.p;
+            if offset == r1 || offset == r2 {
+                let p = fbp.p as u128;
+                let mut exponent = 0;
+                while rest.is_multiple_of(p) {
+                    rest /= p;
+                    exponent += 1;
+                }
+                factors.push((j + 2, exponent));
+            }
+        }
+        if rest == 1 {
+            let square_root = (polynomial.a * x + polynomial.b).rem_euclid(n as i128) as u128;
+            relations.push(Relation {
+                square_root,
+                q: polynomial.q,
+                factors,
+            });
+        }
+    }
+}
+
+
+/// Returns the next prime q >= start with q = 3 mod 4 and n a square mod q.
+///
+fn next_polynomial_prime(n: u128, start: u64) -> u64 {
+    let mut q = start;
+    while !(q % 4 == 3 && is_prime_word(q) && sqrt_mod_prime((n % q as u128) as u64, q).is_some()) {
+        q += 1;
+    }
+    q
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_factor(n: u128) {
+        let f = quadratic_sieve(n).unwrap();
+        assert!(f != 1 && f != n);
+        assert_eq!(0, n % f);
+    }
+
+    #[test]
+    fn test_polynomial() {
+        let n = 1000003_u128 * 1000033;
+        let polynomial = Polynomial::new(n, next_polynomial_prime(n, 1000));
+        let a = polynomial.a;
+        let b = polynomial.b;
+        assert_eq!(0, (b * b - n as i128) % a);
+        for x in -5..5 {
+            let y = a * x + b;
+            assert_eq!(y * y - n as i128, a * polynomial.value(x));
+        }
+    }
+
+    #[test]
+    fn test_small() {
+        assert_eq!(None, quadratic_sieve(3));
+        assert_eq!(Some(2), quadratic_sieve(1000));
+        assert_eq!(Some(1000003), quadratic_sieve(1000003 * 1000003));
+        check_factor(1000003 * 1000033);
+    }
+
+    #[test]
+    fn test_semiprimes() {
+        // 60 and 80 bits
+        check_factor(1073741827 * 1073741831);
+        check_factor(1099511627791 * 1099511627803);
+    }
+}
@@ -0,0 +1,145 @@
+
+extern crate rand;
+
+use self::rand::Rng;
+
+use primality::is_prime;
+
+
+/// Returns a random prime with exactly the given number of bits,
+/// that is in the interval from 2^(bits - 1) to 2^bits - 1.
+///
+/// The candidates are drawn uniformly from the odd integers
+/// in the interval until one is prime, which takes about
+/// `bits * ln(2) / 2` tries on average.
+/// This is meant for generating test inputs, not for cryptography.
+///
+/// bits must be from 2 to 64.
+///
+/// This is only available with the `rand` feature.
+///
+/// ```
+/// extern crate jfactor;
+/// extern crate rand;
+///
+/// use jfactor::{is_prime, random_prime};
+///
+/// # fn main() {
+/// let p = random_prime(40, &mut rand::thread_rng());
+/// assert!(is_prime(p));
+/// assert_eq!(40, 64 - p.leading_zeros());
+/// # }
+/// ```
+///
+pub fn random_prime<R: Rng + ?Sized>(bits: u32, rng: &mut R) -> u64 {
+    assert!((2..=64).contains(&bits), "bits must be from 2 to 64");
+
+    if bits == 2 {
+        return rng.gen_range(2..4);
+    }
+    let top = 1 << (bits - 1);
+    let mask = top - 1;
+    loop {
+        let n = (rng.gen::<u64>() & mask) | top | 1;
+        if is_prime(n) {
+            return n;
+        }
+    }
+}
+
+
+/// Returns a random balanced semiprime n = p q with exactly
+/// the given number of bits, together with its prime factors p <= q.
+///
+/// Both factors have about half the bits of n,
+/// which is the hardest case for most factoring algorithms,
+/// so this is useful for benchmarks and fuzz tests.
+///
+/// bits must be from 4 to 128.
+///
+/// This is only available with the `rand` feature.
+///
+/// ```
+/// extern crate jfactor;
+/// extern crate rand;
+///
+/// use jfactor::{is_prime, random_semiprime};
+///
+/// # fn main() {
+/// let (n, p, q) = random_semiprime(80, &mut rand::thread_rng());
+/// assert_eq!(80, 128 - n.leading_zeros());
+/// assert_eq!(n, p as u128 * q as u128);
+/// assert!(is_prime(p) && is_prime(q));
+/// # }
+/// ```
+///
+pub fn random_semiprime<R: Rng + ?Sized>(bits: u32, rng: &mut R) -> (u128, u64, u64) {
+    assert!((4..=128).contains(&bits), "bits must be from 4 to 128");
+
+    // the product has either bits - 1 or bits bits
+    loop {
+        let p = random_prime(bits / 2, rng);
+        let q = random_prime(bits.div_ceil(2), rng);
+        let n = p as u128 * q as u128;
+        if 128 - n.leading_zeros() == bits {
+            return (n, p.min(q), p.max(q));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rand::rngs::StdRng;
+    use super::rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_random_prime() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for bits in 2..=64 {
+            for _ in 0..10 {
+                let p = random_prime(bits, &mut rng);
+                assert!(is_prime(p));
+                assert_eq!(bits, 64 - p.leading_zeros());
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_prime_covers_small() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut seen = (0..100).map(|_| random_prime(3, &mut rng)).collect::<Vec<_>>();
+        seen.sort();
+        seen.dedup();
+        assert_eq!(vec![5, 7], seen);
+        let mut seen = (0..100).map(|_| random_prime(2, &mut rng)).collect::<Vec<_>>();
+        seen.sort();
+        seen.dedup();
+        assert_eq!(vec![2, 3], seen);
+    }
+
+    #[test]
+    fn test_random_semiprime() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for bits in 4..=128 {
+            let (n, p, q) = random_semiprime(bits, &mut rng);
+            assert_eq!(bits, 128 - n.leading_zeros());
+            assert_eq!(n, p as u128 * q as u128);
+            assert!(p <= q && is_prime(p) && is_prime(q));
+            assert!(64 - q.leading_zeros() <= bits.div_ceil(2));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_random_semiprime_too_large() {
+        random_semiprime(129, &mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_random_prime_one_bit() {
+        random_prime(1, &mut StdRng::seed_from_u64(0));
+    }
+}
@@ -0,0 +1,175 @@
+
+use std::collections::VecDeque;
+
+use factor::factor;
+use factorization::Factorization;
+use sieve::prime_table;
+
+
+/// The number of integers sieved at once.
+const SEGMENT_SIZE: u64 = 1 << 15;
+
+/// The limit for the sieving primes.
+///
+/// Cofactors which may be composite after sieving with them
+/// are factored with `factor`, which only happens above 2^44.
+///
+const SIEVE_PRIME_LIMIT: u32 = 1 << 22;
+
+
+/// Factors all integers in the interval from start to start + len - 1.
+///
+/// This sieves the interval in segments with the primes up to
+/// the square root of its end, which is much faster than factoring
+/// each integer separately.
+/// The factorizations are returned in ascending order of n.
+///
+/// start must be positive and start + len must not overflow.
+///
+/// ```
+/// use jfactor::factor_range;
+///
+/// let exponents = factor_range(10, 3)
+///     .map(|(n, f)| (n, f.exponent(&2)))
+///     .collect::<Vec<_>>();
+/// assert_eq!(vec![(10, 1), (11, 0), (12, 2)], exponents);
+/// ```
+///
+pub fn factor_range(start: u64, len: u64) -> FactorRange {
+    assert!(start > 0);
+    let end = start.checked_add(len).expect("end of range overflows");
+
+    let sqrt_end = (end - 1).isqrt();
+    let limit = if sqrt_end > SIEVE_PRIME_LIMIT as u64 {
+        SIEVE_PRIME_LIMIT
+    } else {
+        sqrt_end as u32
+    };
+    FactorRange {
+        next: start,
+        end,
+        primes: prime_table(limit).into_owned(),
+        segment: VecDeque::new(),
+    }
+}
+
+
+/// An iterator over the factorizations of the integers in an interval.
+///
+/// This is returned by `factor_range`.
+///
+#[derive(Clone, Debug)]
+pub struct FactorRange {
+    next: u64,
+    end: u64,
+    primes: Vec<u32>,
+    segment: VecDeque<(u64, Factorization<u64>)>,
+}
+
+impl FactorRange {
+    /// Factors the next segment of the interval.
+    ///
+    fn sieve_segment(&mut self) {
+        let lo = self.next;
+        let hi = self.end.min(lo.saturating_add(SEGMENT_SIZE));
+        let size = (hi - lo) as usize;
+
+        let mut rest = (lo..hi).collect::<Vec<_>>();
+        let mut factorizations = vec![Factorization::new(); size];
+        for &p in &self.primes {
+            let p = p as u64;
+            if p * p >= hi {
+                break;
+            }
+            // the index of the first multiple of p
+            let mut i = ((p - lo % p) % p) as usize;
+            while i < size {
+                let mut exponent = 0;
+                while rest[i].is_multiple_of(p) {
+                    rest[i] /= p;
+                    exponent += 1;
+                }
+                factorizations[i].insert(p, exponent);
+                i += p as usize;
+            }
+        }
+
+        // the cofactors have no prime factors up to the square root of n
+        // or up to the largest sieving prime, so they are 1 or prime
+        // unless they exceed its square
+        let max_prime = self.primes.last().map_or(1, |&p| p as u64);
+        for ((n, r), mut factorization) in (lo..hi).zip(rest).zip(factorizations) {
+            if r / max_prime > max_prime {
+                for (p, e) in factor(r) {
+                    factorization.insert(p, e);
+                }
+            } else if r > 1 {
+                factorization.insert(r, 1);
+            }
+            self.segment.push_back((n, factorization));
+        }
+        self.next = hi;
+    }
+}
+
+impl Iterator for FactorRange {
+    type Item = (u64, Factorization<u64>);
+
+    fn next(&mut self) -> Option<(u64, Factorization<u64>)> {
+        if self.segment.is_empty() && self.next < self.end {
+            self.sieve_segment();
+        }
+        self.segment.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.segment.len() as u64 + (self.end - self.next);
+        if len <= usize::MAX as u64 {
+            (len as usize, Some(len as usize))
+        } else {
+            (usize::MAX, None)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_range(start: u64, len: u64) {
+        let mut count = 0;
+        for (n, factorization) in factor_range(start, len) {
+            assert_eq!(start + count, n);
+            assert_eq!(factor(n), factorization, "for {}", n);
+            count += 1;
+        }
+        assert_eq!(len, count);
+    }
+
+    #[test]
+    fn test_small() {
+        check_range(1, 2000);
+        check_range(1, 1);
+        check_range(5, 0);
+    }
+
+    #[test]
+    fn test_segments() {
+        check_range(1_000_000_000, 2 * SEGMENT_SIZE + 1000);
+    }
+
+    #[test]
+    fn test_large() {
+        // beyond the sieving primes
+        check_range(u64::MAX - 20, 20);
+    }
+
+    #[test]
+    fn test_size_hint() {
+        let mut range = factor_range(100, 10);
+        assert_eq!((10, Some(10)), range.size_hint());
+        range.next();
+        assert_eq!((9, Some(9)), range.size_hint());
+    }
+}
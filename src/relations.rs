@@ -0,0 +1,161 @@
+
+extern crate num;
+
+use self::num::integer::gcd;
+
+use arith::Word;
+use modular::legendre_symbol;
+use sieve::primes_up_to;
+
+
+/// The number of relations collected beyond the number of columns.
+///
+/// Each of the resulting dependencies finds a factor with probability
+/// at least 1/2, so failure is very unlikely.
+///
+pub(crate) const EXTRA_RELATIONS: usize = 16;
+
+
+/// Returns the first size odd primes p for which n is a nonzero square
+/// modulo p, the primes which can divide x^2 - n,
+/// or Err(p) for the first prime p dividing n.
+///
+pub(crate) fn factor_base(n: u128, size: usize) -> Result<Vec<u64>, u64> {
+    let mut fb = Vec::new();
+    let mut limit = 16 * size as u32;
+    while fb.len() < size {
+        fb.clear();
+        for p in primes_up_to(limit).into_iter().skip(1).map(u64::from) {
+            match legendre_symbol((n % p as u128) as u64, p) {
+                0 => return Err(p),
+                1 => fb.push(p),
+                _ => {}
+            }
+            if fb.len() == size {
+                break;
+            }
+        }
+        limit *= 2;
+    }
+    Ok(fb)
+}
+
+
+/// A relation x^2 = q^2 g mod n where g factors over the base.
+///
+pub(crate) struct Relation {
+    /// x mod n
+    pub(crate) square_root: u128,
+    /// a cofactor of x which is not in the base, or 1
+    pub(crate) q: u64,
+    /// the columns of the prime factors of g with their exponents
+    pub(crate) factors: Vec<(usize, u32)>,
+}
+
+
+/// Combines the relations into congruences of squares until one
+/// gives a nontrivial factor.
+///
+/// The primes of the columns are given, except for the sign column.
+///
+pub(crate) fn find_factor(n: u128, relations: &[Relation], column_primes: &[u64]) -> Option<u128> {
+    let columns = column_primes.len();
+    // Gaussian elimination over GF(2) on the exponent parities,
+    // tracking which relations were combined into each row
+    let words = |bits: usize| bits.div_ceil(64);
+    let mut rows = relations
+        .iter()
+        .enumerate()
+        .map(|(i, relation)| {
+            let mut vector = vec![0_u64; words(columns)];
+            for &(column, exponent) in &relation.factors {
+                if exponent & 1 != 0 {
+                    vector[column / 64] ^= 1 << (column % 64);
+                }
+            }
+            let mut history = vec![0_u64; words(relations.len())];
+            history[i / 64] |= 1 << (i % 64);
+            (vector, history)
+        })
+        .collect::<Vec<_>>();
+
+    let mut is_pivot = vec![false; rows.len()];
+    for column in 0..columns {
+        let (word, bit) = (column / 64, 1 << (column % 64));
+        if let Some(pivot) = (0..rows.len()).find(|&r| !is_pivot[r] && rows[r].0[word] & bit != 0) {
+            is_pivot[pivot] = true;
+            let pivot_row = rows[pivot].clone();
+            for (r, row) in rows.iter_mut().enumerate() {
+                if r != pivot && row.0[word] & bit != 0 {
+                    for (x, y) in row.0.iter_mut().zip(&pivot_row.0) {
+                        *x ^= y;
+                    }
+                    for (x, y) in row.1.iter_mut().zip(&pivot_row.1) {
+                        *x ^= y;
+                    }
+                }
+            }
+        }
+    }
+
+    // the rows which are not pivots are zero, so their histories
+    // are sets of relations whose product is a square
+    for (r, (_, history)) in rows.iter().enumerate() {
+        if is_pivot[r] {
+            continue;
+        }
+        let mut x = 1;
+        let mut y = 1;
+        let mut exponents = vec![0_u32; columns];
+        for (i, relation) in relations.iter().enumerate() {
+            if history[i / 64] & (1 << (i % 64)) != 0 {
+                x = u128::mul_mod(x, relation.square_root, n);
+                y = u128::mul_mod(y, relation.q as u128, n);
+                for &(column, exponent) in &relation.factors {
+                    exponents[column] += exponent;
+                }
+            }
+        }
+        // the exponent of the sign is even, so it can be ignored
+        for (&p, &exponent) in column_primes.iter().zip(&exponents).skip(1) {
+            for _ in 0..exponent / 2 {
+                y = u128::mul_mod(y, p as u128 % n, n);
+            }
+        }
+        let f = gcd(u128::sub_mod(x, y, n), n);
+        if f != 1 && f != n {
+            return Some(f);
+        }
+    }
+    None
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_factor_base() {
+        // 2 is a square modulo the primes p = ±1 mod 8
+        assert_eq!(Ok(vec![7, 17, 23, 31, 41]), factor_base(2, 5));
+        assert_eq!(Err(5), factor_base(35, 5));
+        let fb = factor_base(1000003 * 1000033, 1000).unwrap();
+        assert_eq!(1000, fb.len());
+        assert!(fb.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_find_factor() {
+        // 10^2 = 9 mod 91 and 3^2 = 9 give gcd(10 - 3, 91) = 7
+        let relations = vec![
+            Relation { square_root: 10, q: 1, factors: vec![(2, 2)] },
+            Relation { square_root: 3, q: 1, factors: vec![(2, 2)] },
+        ];
+        let f = find_factor(91, &relations, &[0, 2, 3]).unwrap();
+        assert!(f == 7 || f == 13);
+        // 10^2 = 9 on its own is a congruence of squares
+        assert!(find_factor(91, &relations[..1], &[0, 2, 3]).is_some());
+        assert_eq!(None, find_factor(91, &relations[1..], &[0, 2, 3]));
+    }
+}
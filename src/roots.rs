@@ -0,0 +1,87 @@
+
+extern crate num;
+
+use self::num::integer::Roots;
+use self::num::Unsigned;
+
+
+/// Returns the integer square root of n, the largest r with r^2 <= n.
+///
+/// This is exact for all values, unlike rounding the floating point
+/// square root, which is off for large integers.
+///
+/// ```
+/// use jfactor::isqrt;
+///
+/// assert_eq!(4, isqrt(24_u64));
+/// assert_eq!(5, isqrt(25_u64));
+/// assert_eq!(u64::MAX as u128, isqrt(u128::MAX));
+/// ```
+///
+pub fn isqrt<T: Unsigned + Roots>(n: T) -> T {
+    n.sqrt()
+}
+
+
+/// Returns the integer k-th root of n, the largest r with r^k <= n.
+///
+/// k must be positive.
+///
+/// ```
+/// use jfactor::nth_root;
+///
+/// assert_eq!(9, nth_root(999_u64, 3));
+/// assert_eq!(10, nth_root(1000_u64, 3));
+/// assert_eq!(2, nth_root(u128::MAX, 127));
+/// ```
+///
+pub fn nth_root<T: Unsigned + Roots>(n: T, k: u32) -> T {
+    assert!(k > 0, "the 0-th root is undefined");
+    n.nth_root(k)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_isqrt() {
+        let mut r = 0_u64;
+        for n in 0..100000_u64 {
+            if (r + 1) * (r + 1) <= n {
+                r += 1;
+            }
+            assert_eq!(r, isqrt(n));
+        }
+        assert_eq!(4294967295, isqrt(u64::MAX));
+        let s = 4294967291_u64;
+        assert_eq!(s - 1, isqrt(s * s - 1));
+        assert_eq!(s, isqrt(s * s));
+        let s = 18446744073709551557_u128;
+        assert_eq!(s - 1, isqrt(s * s - 1));
+        assert_eq!(s, isqrt(s * s));
+        assert_eq!(u64::MAX as u128, isqrt(u128::MAX));
+    }
+
+    #[test]
+    fn test_nth_root() {
+        for k in 1..=64 {
+            for &n in &[0, 1, 2, 1000, 1 << 40, u64::MAX - 1, u64::MAX] {
+                let r = nth_root(n, k);
+                assert!(r.checked_pow(k).is_some_and(|p| p <= n));
+                assert!(r.checked_add(1).and_then(|s| s.checked_pow(k)).is_none_or(|p| p > n));
+            }
+        }
+        assert_eq!(1 << 32, nth_root(1_u128 << 96, 3));
+        assert_eq!(17, nth_root(17_u128.pow(31), 31));
+        assert_eq!(16, nth_root(17_u128.pow(31) - 1, 31));
+        assert_eq!(1, nth_root(u128::MAX, 128));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_nth_root_zero() {
+        nth_root(8_u64, 0);
+    }
+}
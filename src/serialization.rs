@@ -0,0 +1,134 @@
+
+extern crate serde;
+
+use std::collections::BTreeMap;
+
+use self::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use factorization::Factorization;
+
+
+/// Serializes a factorization as a map from primes to exponents.
+///
+/// This is only available with the `serde` feature.
+///
+impl<T: Ord + Serialize> Serialize for Factorization<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_map().serialize(serializer)
+    }
+}
+
+/// Deserializes a factorization from a map from primes to exponents.
+///
+/// The keys are not checked for primality, zero exponents are dropped.
+///
+/// This is only available with the `serde` feature.
+///
+impl<'de, T: Ord + Deserialize<'de>> Deserialize<'de> for Factorization<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Factorization<T>, D::Error> {
+        BTreeMap::deserialize(deserializer).map(Factorization::from)
+    }
+}
+
+
+/// Serialization of a factorization in the compact form
+/// of a sequence of pairs of primes and exponents.
+///
+/// Use it with `#[serde(with = "jfactor::factorization_pairs")]`
+/// on a field of type `Factorization<T>`.
+/// For formats like JSON this avoids converting the primes
+/// to strings for the keys of a map.
+///
+/// This is only available with the `serde` feature.
+///
+/// ```
+/// extern crate jfactor;
+/// extern crate serde_json;
+///
+/// use jfactor::{factor, factorization_pairs};
+///
+/// # fn main() {
+/// let mut json = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut json);
+/// factorization_pairs::serialize(&factor(360_u32), &mut serializer).unwrap();
+/// assert_eq!(b"[[2,3],[3,2],[5,1]]", &json[..]);
+/// # }
+/// ```
+///
+pub mod factorization_pairs {
+    use super::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use factorization::Factorization;
+
+    /// Serializes a factorization as a sequence of pairs.
+    ///
+    pub fn serialize<T, S>(factorization: &Factorization<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Ord + Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(factorization.iter())
+    }
+
+    /// Deserializes a factorization from a sequence of pairs.
+    ///
+    /// The primes are not checked for primality,
+    /// exponents of repeated primes are added.
+    ///
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Factorization<T>, D::Error>
+    where
+        T: Ord + Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let pairs = Vec::<(T, u32)>::deserialize(deserializer)?;
+        let mut factorization = Factorization::new();
+        for (p, e) in pairs {
+            factorization.insert(p, e);
+        }
+        Ok(factorization)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use factor::factor;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Pairs {
+        #[serde(with = "factorization_pairs")]
+        f: Factorization<u64>,
+    }
+
+    #[test]
+    fn test_map() {
+        let f = factor(360_u64);
+        let json = serde_json::to_string(&f).unwrap();
+        assert_eq!(r#"{"2":3,"3":2,"5":1}"#, json);
+        assert_eq!(f, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn test_map_zero_exponent() {
+        let f: Factorization<u64> = serde_json::from_str(r#"{"2":3,"7":0}"#).unwrap();
+        assert_eq!(factor(8), f);
+    }
+
+    #[test]
+    fn test_pairs() {
+        let pairs = Pairs { f: factor(360) };
+        let json = serde_json::to_string(&pairs).unwrap();
+        assert_eq!(r#"{"f":[[2,3],[3,2],[5,1]]}"#, json);
+        assert_eq!(pairs, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn test_pairs_repeated() {
+        let pairs: Pairs = serde_json::from_str(r#"{"f":[[2,1],[3,1],[2,2],[5,0]]}"#).unwrap();
+        assert_eq!(factor(24), pairs.f);
+    }
+}
@@ -0,0 +1,959 @@
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+use factorization::Factorization;
+
+
+/// The limit of the shared table of primes.
+pub(crate) const PRIME_TABLE_LIMIT: u32 = 200000;
+
+/// The number of integers per segment of a segmented sieve.
+pub(crate) const SIEVE_SEGMENT_SIZE: u64 = 1 << 16;
+
+/// The residues modulo 210 = 2 * 3 * 5 * 7 which are coprime to it.
+const WHEEL_210: [u64; 48] = [
+    1, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97, 101,
+    103, 107, 109, 113, 121, 127, 131, 137, 139, 143, 149, 151, 157, 163, 167, 169, 173, 179,
+    181, 187, 191, 193, 197, 199, 209,
+];
+
+
+/// Returns the primes up to and including limit in ascending order.
+///
+/// Uses the sieve of Eratosthenes on odd numbers.
+///
+pub fn primes_up_to(limit: u32) -> Vec<u32> {
+    let mut primes = Vec::new();
+    if limit < 2 {
+        return primes;
+    }
+    primes.push(2);
+
+    // index i represents the odd number 2 * i + 1
+    let size = (limit as usize).div_ceil(2);
+    let mut composite = vec![false; size];
+    let mut i = 1;
+    while i < size {
+        if !composite[i] {
+            let p = 2 * i + 1;
+            primes.push(p as u32);
+            let mut j = p * p / 2;
+            while j < size {
+                composite[j] = true;
+                j += p;
+            }
+        }
+        i += 1;
+    }
+    primes
+}
+
+
+/// Returns the primes up to and including limit in ascending order,
+/// using a shared table for limits up to 200000.
+///
+/// The table is computed once on first use, so repeated calls
+/// are cheap. Larger limits compute the primes each time.
+///
+pub fn prime_table(limit: u32) -> Cow<'static, [u32]> {
+    static PRIMES: OnceLock<Vec<u32>> = OnceLock::new();
+
+    if limit <= PRIME_TABLE_LIMIT {
+        let primes = PRIMES.get_or_init(|| primes_up_to(PRIME_TABLE_LIMIT));
+        let len = primes.partition_point(|&p| p <= limit);
+        Cow::Borrowed(&primes[..len])
+    } else {
+        Cow::Owned(primes_up_to(limit))
+    }
+}
+
+
+/// Returns for each integer in lo..hi whether it is prime.
+///
+/// primes must contain at least the primes up to the square root of hi - 1.
+///
+pub(crate) fn sieve_segment(lo: u64, hi: u64, primes: &[u32]) -> Vec<bool> {
+    // start with the odd integers from 3 on, then cross out odd multiples
+    let mut is_prime = (lo..hi).map(|n| n & 1 == 1 && n > 1).collect::<Vec<_>>();
+    if lo <= 2 && 2 < hi {
+        is_prime[(2 - lo) as usize] = true;
+    }
+    for &p in primes.iter().skip_while(|&&p| p == 2) {
+        let p = p as u64;
+        if p * p >= hi {
+            break;
+        }
+        let mut m = (p * p).max(lo.saturating_add((p - lo % p) % p));
+        if m & 1 == 0 {
+            m += p;
+        }
+        while m < hi {
+            is_prime[(m - lo) as usize] = false;
+            m += 2 * p;
+        }
+    }
+    is_prime
+}
+
+
+/// Returns all primes in ascending order, without an upper bound.
+///
+/// The primes beyond the table of primes are found with a segmented sieve,
+/// which is extended one segment at a time as the iterator advances,
+/// so the memory needed grows only with the square root
+/// of the largest prime returned.
+///
+/// ```
+/// use jfactor::primes;
+///
+/// let first = primes().take(10).collect::<Vec<_>>();
+/// assert_eq!(vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29], first);
+/// assert_eq!(Some(1000003), primes().find(|&p| p > 1_000_000));
+/// ```
+///
+pub fn primes() -> impl Iterator<Item = u64> {
+    let table = prime_table(PRIME_TABLE_LIMIT).into_owned();
+    Primes {
+        segment: table.iter().map(|&p| p as u64).collect(),
+        next: PRIME_TABLE_LIMIT as u64 + 1,
+        primes: table,
+    }
+}
+
+
+/// An iterator over all primes, returned by `primes`.
+///
+struct Primes {
+    /// the start of the next segment
+    next: u64,
+    /// the sieving primes
+    primes: Vec<u32>,
+    /// the primes found but not returned yet
+    segment: VecDeque<u64>,
+}
+
+impl Primes {
+    /// Finds the primes in the next segment.
+    ///
+    fn sieve_next_segment(&mut self) {
+        let lo = self.next;
+        let hi = lo.saturating_add(SIEVE_SEGMENT_SIZE);
+
+        // extend the sieving primes up to the square root of hi,
+        // which is less than the square of the largest one
+        let limit = (hi - 1).isqrt();
+        let max_prime = *self.primes.last().unwrap() as u64;
+        if limit > max_prime {
+            let new_limit = limit.max(2 * max_prime).min(u32::MAX as u64);
+            let new_primes = sieve_segment(max_prime + 1, new_limit + 1, &self.primes);
+            for (i, &is_prime) in new_primes.iter().enumerate() {
+                if is_prime {
+                    self.primes.push((max_prime + 1 + i as u64) as u32);
+                }
+            }
+        }
+
+        let segment = sieve_segment(lo, hi, &self.primes);
+        for (i, &is_prime) in segment.iter().enumerate() {
+            if is_prime {
+                self.segment.push_back(lo + i as u64);
+            }
+        }
+        self.next = hi;
+    }
+}
+
+impl Iterator for Primes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        while self.segment.is_empty() && self.next < u64::MAX {
+            self.sieve_next_segment();
+        }
+        self.segment.pop_front()
+    }
+}
+
+
+/// Returns the k-th prime, counting from 1 for the prime 2.
+///
+/// Beyond the table of primes this sieves up to the k-th prime,
+/// which takes a few seconds for k = 10^8.
+/// k must be positive.
+///
+/// ```
+/// use jfactor::nth_prime;
+///
+/// assert_eq!(2, nth_prime(1));
+/// assert_eq!(29, nth_prime(10));
+/// assert_eq!(15485863, nth_prime(1_000_000));
+/// ```
+///
+pub fn nth_prime(k: u64) -> u64 {
+    assert!(k > 0);
+
+    let table = prime_table(PRIME_TABLE_LIMIT);
+    if k <= table.len() as u64 {
+        return table[k as usize - 1] as u64;
+    }
+
+    // Rosser's bound p_k < k (ln k + ln ln k) for k >= 6
+    let x = k as f64;
+    let bound = (x * (x.ln() + x.ln().ln())).ceil() as u64 + 1;
+    let primes = primes_up_to(bound.isqrt() as u32 + 1);
+    let mut count = table.len() as u64;
+    let mut lo = PRIME_TABLE_LIMIT as u64 + 1;
+    loop {
+        let hi = (lo + SIEVE_SEGMENT_SIZE).min(bound + 1);
+        let segment = sieve_segment(lo, hi, &primes);
+        let segment_count = segment.iter().filter(|&&is_prime| is_prime).count() as u64;
+        if count + segment_count >= k {
+            let i = segment
+                .iter()
+                .enumerate()
+                .filter(|&(_, &is_prime)| is_prime)
+                .nth((k - count - 1) as usize)
+                .unwrap()
+                .0;
+            return lo + i as u64;
+        }
+        count += segment_count;
+        lo = hi;
+    }
+}
+
+
+/// Returns the number of primes up to and including x.
+///
+/// This uses Lucy Hedgehog's method, which needs time O(x^(3/4))
+/// and memory O(x^(1/2)), a second or two for x = 10^12.
+///
+/// ```
+/// use jfactor::prime_pi;
+///
+/// assert_eq!(25, prime_pi(100));
+/// assert_eq!(50847534, prime_pi(1_000_000_000));
+/// ```
+///
+pub fn prime_pi(x: u64) -> u64 {
+    if x < 2 {
+        return 0;
+    }
+
+    // small[v] counts the integers 2..=v and large[i] those in 2..=x/i
+    // which are not crossed out yet, after crossing out
+    // the multiples of all primes up to p these count
+    // the primes and the integers with no prime factor up to p
+    let r = x.isqrt();
+    let mut small = (0..=r).map(|v| v.saturating_sub(1)).collect::<Vec<_>>();
+    let mut large = (0..=r).map(|i| x.checked_div(i).map_or(0, |q| q - 1)).collect::<Vec<_>>();
+    for p in 2..=r {
+        if small[p as usize] == small[p as usize - 1] {
+            // p is not prime
+            continue;
+        }
+        let count = small[p as usize - 1];
+        let p2 = p * p;
+        for i in 1..=r.min(x / p2) {
+            let d = i * p;
+            let crossed = if d <= r {
+                large[d as usize]
+            } else {
+                small[(x / d) as usize]
+            };
+            large[i as usize] -= crossed - count;
+        }
+        for v in (p2..=r).rev() {
+            small[v as usize] -= small[(v / p) as usize] - count;
+        }
+    }
+    large[1]
+}
+
+
+/// Returns the table of the Liouville function λ(n) for the integers
+/// up to and including limit.
+///
+/// This uses a linear sieve like `phi_sieve`.
+/// The entry for 0 is 0.
+///
+/// ```
+/// use jfactor::liouville_sieve;
+///
+/// assert_eq!(vec![0, 1, -1, -1, 1, -1, 1, -1, -1, 1, 1], liouville_sieve(10));
+/// ```
+///
+pub fn liouville_sieve(limit: u32) -> Vec<i8> {
+    let size = limit as usize + 1;
+    let mut lambda = vec![0_i8; size];
+    if limit >= 1 {
+        lambda[1] = 1;
+    }
+    let mut primes = Vec::new();
+    for i in 2..size {
+        if lambda[i] == 0 {
+            // i is prime
+            lambda[i] = -1;
+            primes.push(i);
+        }
+        for &p in &primes {
+            let m = i * p;
+            if m >= size {
+                break;
+            }
+            lambda[m] = -lambda[i];
+            if i % p == 0 {
+                break;
+            }
+        }
+    }
+    lambda
+}
+
+
+/// Returns the table of the number of distinct prime factors ω(n)
+/// for the integers up to and including limit.
+///
+/// This uses a linear sieve like `phi_sieve`.
+/// The entry for 0 is 0.
+///
+/// ```
+/// use jfactor::omega_sieve;
+///
+/// assert_eq!(vec![0, 0, 1, 1, 1, 1, 2, 1, 1, 1, 2], omega_sieve(10));
+/// ```
+///
+pub fn omega_sieve(limit: u32) -> Vec<u8> {
+    linear_sieve(limit, |omega_i, new_prime| omega_i + new_prime as u8)
+}
+
+
+/// Returns the table of the number of prime factors Ω(n)
+/// counted with multiplicity for the integers up to and including limit.
+///
+/// This uses a linear sieve like `phi_sieve`.
+/// The entry for 0 is 0.
+///
+/// ```
+/// use jfactor::big_omega_sieve;
+///
+/// assert_eq!(vec![0, 0, 1, 1, 2, 1, 2, 1, 3, 2, 2], big_omega_sieve(10));
+/// ```
+///
+pub fn big_omega_sieve(limit: u32) -> Vec<u8> {
+    linear_sieve(limit, |big_omega_i, _| big_omega_i + 1)
+}
+
+
+/// Computes an additive function f with f(p) = 1 for primes p
+/// with a linear sieve.
+///
+/// The value for i p with p the smallest prime factor of i p
+/// is computed as next(f(i), new_prime),
+/// where new_prime tells whether p does not divide i.
+///
+fn linear_sieve<F: Fn(u8, bool) -> u8>(limit: u32, next: F) -> Vec<u8> {
+    let size = limit as usize + 1;
+    let mut f = vec![0_u8; size];
+    let mut composite = vec![false; size];
+    let mut primes = Vec::new();
+    for i in 2..size {
+        if !composite[i] {
+            f[i] = 1;
+            primes.push(i);
+        }
+        for &p in &primes {
+            let m = i * p;
+            if m >= size {
+                break;
+            }
+            composite[m] = true;
+            let new_prime = i % p != 0;
+            f[m] = next(f[i], new_prime);
+            if !new_prime {
+                break;
+            }
+        }
+    }
+    f
+}
+
+
+/// Returns the Mertens function M(x), the sum of μ(n) for n from 1 to x.
+///
+/// This uses the recurrence M(x) = 1 - Σ M(x / n) for n from 2 to x,
+/// grouping the n with the same quotient x / n and looking up
+/// the values up to x^(2/3) in a table from `moebius_sieve`.
+/// It needs time and memory O(x^(2/3)), about a second for x = 10^11.
+/// Panics if x is 2^48 or more.
+///
+/// ```
+/// use jfactor::mertens;
+///
+/// assert_eq!(-23, mertens(10_000));
+/// assert_eq!(-33722, mertens(10_000_000_000));
+/// ```
+///
+pub fn mertens(x: u64) -> i64 {
+    let limit = small_table_limit(x);
+    let mu = moebius_sieve(limit as u32);
+    let mut small = vec![0_i32; mu.len()];
+    for i in 1..mu.len() {
+        small[i] = small[i - 1] + mu[i] as i32;
+    }
+    if x <= limit {
+        return small[x as usize] as i64;
+    }
+
+    // large[i] is M(x / i) for the i with x / i above the limit,
+    // computed from the largest i down, as the recurrence for x / i
+    // needs M(x / (i d)) only
+    let num_large = (x / (limit + 1)) as usize;
+    let mut large = vec![0_i64; num_large + 1];
+    for i in (1..=num_large).rev() {
+        let v = x / i as u64;
+        let mut m = 1;
+        let mut d = 2;
+        while d <= v {
+            let q = v / d;
+            let next_d = v / q + 1;
+            let mq = if q <= limit {
+                small[q as usize] as i64
+            } else {
+                large[i * d as usize]
+            };
+            m -= (next_d - d) as i64 * mq;
+            d = next_d;
+        }
+        large[i] = m;
+    }
+    large[1]
+}
+
+
+/// Returns the size of the table of small values
+/// for `mertens` and `totient_sum`,
+/// about x^(2/3) but at least √x.
+///
+/// Panics if x is 2^48 or more, where it would not fit into 32 bits.
+///
+fn small_table_limit(x: u64) -> u64 {
+    assert!(x < 1 << 48, "x must be less than 2^48");
+    ((x as f64).powf(2.0 / 3.0) as u64)
+        .max(x.isqrt())
+        .clamp(1, u32::MAX as u64)
+}
+
+
+/// Returns the summatory totient Φ(x), the sum of φ(n) for n from 1 to x.
+///
+/// This uses the recurrence Φ(x) = x (x + 1) / 2 - Σ Φ(x / n)
+/// for n from 2 to x, which follows from Σ φ(d) = n over the divisors d,
+/// in the same way as `mertens` with a table from `phi_sieve`.
+/// It needs time and memory O(x^(2/3)), about a second for x = 10^11.
+/// Panics if x is 2^48 or more.
+///
+/// ```
+/// use jfactor::totient_sum;
+///
+/// assert_eq!(3044, totient_sum(100));
+/// assert_eq!(30396355092886216366, totient_sum(10_000_000_000));
+/// ```
+///
+pub fn totient_sum(x: u64) -> u128 {
+    let limit = small_table_limit(x);
+    let phi = phi_sieve(limit as u32);
+    let mut small = vec![0_u64; phi.len()];
+    for i in 1..phi.len() {
+        small[i] = small[i - 1] + phi[i] as u64;
+    }
+    if x <= limit {
+        return small[x as usize] as u128;
+    }
+
+    // large[i] is Φ(x / i) for the i with x / i above the limit
+    let num_large = (x / (limit + 1)) as usize;
+    let mut large = vec![0_u128; num_large + 1];
+    for i in (1..=num_large).rev() {
+        let v = x / i as u64;
+        let mut sum = v as u128 * (v as u128 + 1) / 2;
+        let mut d = 2;
+        while d <= v {
+            let q = v / d;
+            let next_d = v / q + 1;
+            let sum_q = if q <= limit {
+                small[q as usize] as u128
+            } else {
+                large[i * d as usize]
+            };
+            sum -= (next_d - d) as u128 * sum_q;
+            d = next_d;
+        }
+        large[i] = sum;
+    }
+    large[1]
+}
+
+
+/// Returns the numbers from start on which are coprime to 210
+/// in ascending order.
+///
+/// This includes all primes above 7 and skips 77% of all numbers,
+/// for trial division beyond a table of primes.
+///
+pub(crate) fn wheel_candidates(start: u64) -> impl Iterator<Item = u64> {
+    (start / 210..)
+        .flat_map(|k| WHEEL_210.iter().map(move |&r| 210 * k + r))
+        .skip_while(move |&d| d < start)
+}
+
+
+/// Returns the table of smallest prime factors of the integers
+/// up to and including limit.
+///
+/// The entry for n is its smallest prime factor, or 0 for 0 and 1 for 1.
+/// With this table `factor_with_spf` factors any n up to limit
+/// in O(log n) steps, which makes it the basis for fast computations
+/// of multiplicative functions over a whole range.
+///
+/// This needs 4 bytes per integer.
+///
+pub fn spf_sieve(limit: u32) -> Vec<u32> {
+    let size = limit as usize + 1;
+    let mut spf = vec![0_u32; size];
+    if limit >= 1 {
+        spf[1] = 1;
+    }
+    for i in 2..size {
+        if spf[i] == 0 {
+            // i is prime
+            spf[i] = i as u32;
+            let mut j = i * i;
+            while j < size {
+                if spf[j] == 0 {
+                    spf[j] = i as u32;
+                }
+                j += i;
+            }
+        }
+    }
+    spf
+}
+
+
+/// Returns the table of Euler's totient φ(n) for the integers
+/// up to and including limit.
+///
+/// This uses a linear sieve, which sets each entry exactly once
+/// from its smallest prime factor, so it needs time O(limit).
+/// The entry for 0 is 0.
+///
+/// ```
+/// use jfactor::phi_sieve;
+///
+/// assert_eq!(vec![0, 1, 1, 2, 2, 4, 2, 6, 4, 6, 4], phi_sieve(10));
+/// ```
+///
+pub fn phi_sieve(limit: u32) -> Vec<u32> {
+    let size = limit as usize + 1;
+    let mut phi = vec![0_u32; size];
+    if limit >= 1 {
+        phi[1] = 1;
+    }
+    let mut primes = Vec::new();
+    for i in 2..size {
+        if phi[i] == 0 {
+            // i is prime
+            phi[i] = i as u32 - 1;
+            primes.push(i);
+        }
+        for &p in &primes {
+            let m = i * p;
+            if m >= size {
+                break;
+            }
+            if i % p == 0 {
+                phi[m] = phi[i] * p as u32;
+                break;
+            }
+            phi[m] = phi[i] * (p as u32 - 1);
+        }
+    }
+    phi
+}
+
+
+/// Returns the table of the Möbius function μ(n) for the integers
+/// up to and including limit.
+///
+/// μ(n) is 0 if n is divisible by a square above 1,
+/// and otherwise 1 or -1 for an even or odd number of prime factors.
+/// This uses a linear sieve like `phi_sieve`.
+/// The entry for 0 is 0.
+///
+/// ```
+/// use jfactor::moebius_sieve;
+///
+/// assert_eq!(vec![0, 1, -1, -1, 0, -1, 1, -1, 0, 0, 1], moebius_sieve(10));
+/// ```
+///
+pub fn moebius_sieve(limit: u32) -> Vec<i8> {
+    let size = limit as usize + 1;
+    let mut mu = vec![0_i8; size];
+    let mut composite = vec![false; size];
+    if limit >= 1 {
+        mu[1] = 1;
+    }
+    let mut primes = Vec::new();
+    for i in 2..size {
+        if !composite[i] {
+            mu[i] = -1;
+            primes.push(i);
+        }
+        for &p in &primes {
+            let m = i * p;
+            if m >= size {
+                break;
+            }
+            composite[m] = true;
+            if i % p == 0 {
+                mu[m] = 0;
+                break;
+            }
+            mu[m] = -mu[i];
+        }
+    }
+    mu
+}
+
+
+/// Factors n using a table of smallest prime factors from `spf_sieve`.
+///
+/// n must be positive and at most the limit of the table.
+///
+/// ```
+/// use jfactor::{factor_with_spf, spf_sieve};
+///
+/// let table = spf_sieve(1000);
+/// let f = factor_with_spf(360, &table);
+/// assert_eq!(vec![(&2, &3), (&3, &2), (&5, &1)], f.iter().collect::<Vec<_>>());
+/// ```
+///
+pub fn factor_with_spf(n: u32, table: &[u32]) -> Factorization<u32> {
+    assert!(n > 0);
+    assert!((n as usize) < table.len(), "n is beyond the table");
+
+    let mut factorization = Factorization::new();
+    let mut rest = n;
+    while rest > 1 {
+        let p = table[rest as usize];
+        let mut exponent = 0;
+        while rest.is_multiple_of(p) {
+            rest /= p;
+            exponent += 1;
+        }
+        factorization.insert(p, exponent);
+    }
+    factorization
+}
+
+
+/// Factors the factorial n! without computing it.
+///
+/// By Legendre's formula the exponent of a prime p in n!
+/// is the sum of n / p^i over i = 1, 2, ..., rounded down,
+/// so this needs only the primes up to n.
+///
+/// ```
+/// use jfactor::factor_factorial;
+///
+/// // 10! = 2^8 * 3^4 * 5^2 * 7
+/// let f = factor_factorial(10);
+/// assert_eq!(vec![(&2, &8), (&3, &4), (&5, &2), (&7, &1)], f.iter().collect::<Vec<_>>());
+/// assert!(factor_factorial(1).is_one());
+/// ```
+///
+pub fn factor_factorial(n: u32) -> Factorization<u32> {
+    let mut factorization = Factorization::new();
+    for p in primes_up_to(n) {
+        let mut exponent = 0;
+        let mut rest = n;
+        while rest > 0 {
+            rest /= p;
+            exponent += rest;
+        }
+        factorization.insert(p, exponent);
+    }
+    factorization
+}
+
+
+#[cfg(test)]
+mod tests {
+    use factor::factor;
+    use multiplicative_functions::liouville;
+    use primality::is_prime_word;
+
+    use super::*;
+
+    #[test]
+    fn test_small() {
+        assert_eq!(Vec::<u32>::new(), primes_up_to(0));
+        assert_eq!(Vec::<u32>::new(), primes_up_to(1));
+        assert_eq!(vec![2], primes_up_to(2));
+        assert_eq!(vec![2, 3], primes_up_to(3));
+        assert_eq!(vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29], primes_up_to(30));
+        assert_eq!(vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31], primes_up_to(31));
+    }
+
+    #[test]
+    fn test_prime_table() {
+        assert!(prime_table(1).is_empty());
+        assert_eq!(&[2, 3, 5, 7][..], &*prime_table(10));
+        assert_eq!(&[2, 3, 5, 7, 11][..], &*prime_table(11));
+        assert_eq!(primes_up_to(200000), &*prime_table(200000));
+        assert_eq!(primes_up_to(300000), &*prime_table(300000));
+    }
+
+    #[test]
+    fn test_wheel_candidates() {
+        let expected = (200..1000_u64)
+            .filter(|&d| [2, 3, 5, 7].iter().all(|&p| d % p != 0))
+            .collect::<Vec<_>>();
+        let actual = wheel_candidates(200).take_while(|&d| d < 1000).collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+        assert_eq!(Some(211), wheel_candidates(210).next());
+        assert_eq!(Some(1), wheel_candidates(0).next());
+    }
+
+    #[test]
+    fn test_spf_sieve() {
+        assert_eq!(vec![0], spf_sieve(0));
+        assert_eq!(vec![0, 1, 2, 3, 2, 5, 2, 7, 2, 3, 2, 11, 2], spf_sieve(12));
+        let spf = spf_sieve(10000);
+        for n in 2..10001_u32 {
+            let p = spf[n as usize];
+            assert_eq!(0, n % p);
+            assert!((2..p).all(|d| n % d != 0));
+        }
+    }
+
+    #[test]
+    fn test_phi_sieve() {
+        assert_eq!(vec![0], phi_sieve(0));
+        assert_eq!(vec![0, 1], phi_sieve(1));
+        let phi = phi_sieve(100000);
+        for n in 1..100001_u32 {
+            assert_eq!(factor(n).euler_phi(), phi[n as usize]);
+        }
+    }
+
+    #[test]
+    fn test_moebius_sieve() {
+        assert_eq!(vec![0], moebius_sieve(0));
+        assert_eq!(vec![0, 1], moebius_sieve(1));
+        let mu = moebius_sieve(100000);
+        for n in 1..100001_u32 {
+            let f = factor(n);
+            let expected = if f.iter().any(|(_, &e)| e > 1) {
+                0
+            } else if f.primes().count() % 2 == 0 {
+                1
+            } else {
+                -1
+            };
+            assert_eq!(expected, mu[n as usize]);
+        }
+    }
+
+    #[test]
+    fn test_liouville_sieve() {
+        assert_eq!(vec![0], liouville_sieve(0));
+        let lambda = liouville_sieve(100000);
+        for n in 1..100001_u32 {
+            assert_eq!(liouville(n), lambda[n as usize]);
+        }
+    }
+
+    #[test]
+    fn test_omega_sieve() {
+        assert_eq!(vec![0], omega_sieve(0));
+        assert_eq!(vec![0, 0], big_omega_sieve(1));
+        let omega = omega_sieve(100000);
+        let big_omega = big_omega_sieve(100000);
+        for n in 1..100001_u32 {
+            let f = factor(n);
+            assert_eq!(f.omega(), omega[n as usize] as u32);
+            assert_eq!(f.big_omega(), big_omega[n as usize] as u32);
+        }
+    }
+
+    #[test]
+    fn test_factor_with_spf() {
+        let table = spf_sieve(100000);
+        for n in 1..100001_u32 {
+            assert_eq!(factor(n), factor_with_spf(n, &table));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_factor_with_spf_beyond_table() {
+        factor_with_spf(101, &spf_sieve(100));
+    }
+
+    #[test]
+    fn test_factor_factorial() {
+        assert!(factor_factorial(0).is_one());
+        let mut factorial = 1_u64;
+        for n in 1..=20_u32 {
+            factorial *= n as u64;
+            let expected = factor(factorial)
+                .into_iter()
+                .map(|(p, e)| (p as u32, e))
+                .collect::<Vec<_>>();
+            assert_eq!(expected, factor_factorial(n).into_iter().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_factor_factorial_large() {
+        let n = 10_000_000;
+        let f = factor_factorial(n);
+        assert_eq!(664579, f.omega());
+        // the exponent of 2 is n minus the number of ones in binary
+        assert_eq!(n - n.count_ones(), f.exponent(&2));
+        assert_eq!(4999994, f.exponent(&3));
+        assert_eq!(1, f.exponent(&9999991));
+        assert_eq!(0, f.exponent(&10000019));
+    }
+
+    #[test]
+    fn test_sieve_segment() {
+        let primes = primes_up_to(1000);
+        let expected = (0..1000_u64).map(|n| primes.contains(&(n as u32))).collect::<Vec<_>>();
+        assert_eq!(expected, sieve_segment(0, 1000, &primes));
+        assert_eq!(expected[1..200].to_vec(), sieve_segment(1, 200, &primes));
+        assert_eq!(expected[100..200].to_vec(), sieve_segment(100, 200, &primes));
+        let lo = 1_000_000_000_000;
+        let segment = sieve_segment(lo, lo + 1000, &primes_up_to(1_000_000));
+        for (i, &is_prime) in segment.iter().enumerate() {
+            assert_eq!(is_prime_word(lo + i as u64), is_prime);
+        }
+    }
+
+    #[test]
+    fn test_primes() {
+        let expected = primes_up_to(1_000_000);
+        let actual = primes().take_while(|&p| p <= 1_000_000).collect::<Vec<_>>();
+        assert_eq!(expected.len(), actual.len());
+        assert!(expected.iter().zip(&actual).all(|(&p, &q)| p as u64 == q));
+        assert_eq!(Some(15485863), primes().nth(999_999));
+
+        // starting with few sieving primes, which must be extended
+        let lo = 1_000_000_000_000;
+        let mut large = Primes {
+            next: lo,
+            primes: primes_up_to(1000),
+            segment: VecDeque::new(),
+        };
+        let expected = (lo..lo + 1000).filter(|&n| is_prime_word(n)).collect::<Vec<_>>();
+        let actual = large.by_ref().take_while(|&p| p < lo + 1000).collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_nth_prime() {
+        let primes = primes_up_to(201000);
+        for (k, &p) in primes.iter().enumerate() {
+            assert_eq!(p as u64, nth_prime(k as u64 + 1));
+        }
+        assert_eq!(199999, nth_prime(17984));
+        assert_eq!(200003, nth_prime(17985));
+        assert_eq!(1299709, nth_prime(100000));
+        assert_eq!(15485863, nth_prime(1_000_000));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_nth_prime_zero() {
+        nth_prime(0);
+    }
+
+    #[test]
+    fn test_prime_pi() {
+        let primes = primes_up_to(10_000);
+        for x in 0..10_000 {
+            assert_eq!(primes.partition_point(|&p| p <= x) as u64, prime_pi(x as u64));
+        }
+        let expected = [4, 25, 168, 1229, 9592, 78498, 664579, 5761455, 50847534, 455052511];
+        for (k, &pi) in expected.iter().enumerate() {
+            assert_eq!(pi, prime_pi(10_u64.pow(k as u32 + 1)));
+        }
+        assert_eq!(203280221, prime_pi(u32::MAX as u64));
+    }
+
+    #[test]
+    fn test_mertens() {
+        let mu = moebius_sieve(10_000);
+        let mut m = 0;
+        for x in 0..10_000 {
+            m += mu[x as usize] as i64;
+            assert_eq!(m, mertens(x));
+        }
+        let expected = [-1, 1, 2, -23, -48, 212, 1037, 1928, -222];
+        for (k, &m) in expected.iter().enumerate() {
+            assert_eq!(m, mertens(10_u64.pow(k as u32 + 1)));
+        }
+    }
+
+    #[test]
+    fn test_small_table_limit() {
+        assert_eq!(1, small_table_limit(0));
+        assert_eq!(u32::MAX as u64, small_table_limit((1 << 48) - 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "x must be less than 2^48")]
+    fn test_mertens_too_large() {
+        mertens(1 << 48);
+    }
+
+    #[test]
+    #[should_panic(expected = "x must be less than 2^48")]
+    fn test_totient_sum_too_large() {
+        totient_sum(1 << 48);
+    }
+
+    #[test]
+    fn test_totient_sum() {
+        let phi = phi_sieve(10_000);
+        let mut sum = 0;
+        for x in 0..10_000 {
+            sum += phi[x as usize] as u128;
+            assert_eq!(sum, totient_sum(x));
+        }
+        let expected = [
+            32,
+            3044,
+            304192,
+            30397486,
+            3039650754,
+            303963552392,
+            30396356427242,
+            3039635516365908,
+            303963551173008414,
+        ];
+        for (k, &sum) in expected.iter().enumerate() {
+            assert_eq!(sum, totient_sum(10_u64.pow(k as u32 + 1)));
+        }
+    }
+
+    #[test]
+    fn test_count() {
+        assert_eq!(168, primes_up_to(1000).len());
+        assert_eq!(78498, primes_up_to(1_000_000).len());
+    }
+}
@@ -0,0 +1,287 @@
+
+extern crate num;
+extern crate num_bigint;
+
+use self::num::{Integer, One, Zero};
+use self::num_bigint::BigUint;
+
+use big::{factor_big, to_u128};
+use factor::factor;
+use factorization::Factorization;
+use modular::mod_pow;
+use primality::{bpsw_is_prime_big, is_prime};
+
+
+/// The number of candidates of the special form tried as factors
+/// before falling back to `factor_big`.
+const SPECIAL_TRIAL_LIMIT: u64 = 1 << 20;
+
+
+/// Tests whether the Mersenne number 2^p - 1 is prime
+/// with the Lucas-Lehmer test.
+///
+/// For an odd prime p, 2^p - 1 is prime if and only if s_(p - 2) = 0
+/// mod 2^p - 1, where s_0 = 4 and s_(i + 1) = s_i^2 - 2.
+/// Reducing modulo 2^p - 1 needs only shifts and additions,
+/// so this takes p - 2 squarings of p-bit integers.
+/// 2^2 - 1 = 3 is prime, for composite p the Mersenne number is composite.
+///
+/// ```
+/// use jfactor::lucas_lehmer;
+///
+/// assert!(lucas_lehmer(127));
+/// assert!(!lucas_lehmer(11));
+/// assert!(!lucas_lehmer(15));
+/// ```
+///
+pub fn lucas_lehmer(p: u32) -> bool {
+    if p == 2 {
+        return true;
+    }
+    if !is_prime(p) {
+        return false;
+    }
+
+    let m = mersenne(p);
+    let two = BigUint::from(2_u32);
+    let mut s = BigUint::from(4_u32);
+    for _ in 0..p - 2 {
+        s = &s * &s + &m - &two;
+        while s > m {
+            s = (&s & &m) + (&s >> p as usize);
+        }
+        if s == m {
+            s = BigUint::zero();
+        }
+    }
+    s.is_zero()
+}
+
+
+/// Factors the Mersenne number 2^p - 1 into its prime factors.
+///
+/// For odd prime p every prime factor q of 2^p - 1 is 1 mod 2 p,
+/// and as 2 is a square modulo q also 1 or 7 mod 8.
+/// Only these candidates up to about 2^21 p are tried by trial division,
+/// until the rest is prime or fits into 128 bits.
+/// Any remaining part is factored with `factor_big`.
+/// For composite p the algebraic factor 2^d - 1 for the largest
+/// proper divisor d of p is factored first, the new prime factors
+/// then satisfy a similar congruence.
+/// Panics if p is zero.
+///
+/// ```
+/// extern crate jfactor;
+/// extern crate num_bigint;
+///
+/// use num_bigint::BigUint;
+///
+/// use jfactor::factor_mersenne;
+///
+/// # fn main() {
+/// let big = |n: u64| BigUint::from(n);
+/// // 2^11 - 1 = 23 * 89
+/// let f = factor_mersenne(11);
+/// assert_eq!(vec![&big(23), &big(89)], f.primes().collect::<Vec<_>>());
+/// # }
+/// ```
+///
+pub fn factor_mersenne(p: u32) -> Factorization<BigUint> {
+    assert!(p > 0);
+    if p == 1 {
+        return Factorization::new();
+    }
+
+    // a prime factor q of 2^p - 1 not dividing 2^d - 1 with d = p / r
+    // has an order of 2 divisible by r^v, so q = 1 mod lcm(2, r^v)
+    let (r, v) = factor(p).into_iter().next().unwrap();
+    let t = r.pow(v) as u64;
+    let step = if t.is_multiple_of(2) { t } else { 2 * t };
+    let d = p / r;
+
+    let known = factor_mersenne(d);
+    let mut factorization = Factorization::new();
+    let mut rest = mersenne(p) / mersenne(d);
+    for q in known.primes() {
+        loop {
+            let (quotient, remainder) = rest.div_rem(q);
+            if !remainder.is_zero() {
+                break;
+            }
+            rest = quotient;
+            factorization.insert(q.clone(), 1);
+        }
+    }
+
+    let candidates = (1..=SPECIAL_TRIAL_LIMIT)
+        .map(|k| k * step + 1)
+        .filter(|&q| p.is_multiple_of(2) || q % 8 == 1 || q % 8 == 7)
+        .filter(|&q| mod_pow(2, p as u64, q) == 1);
+    known * factorization * factor_with_candidates(rest, candidates)
+}
+
+
+/// Factors the Fermat number 2^(2^k) + 1 into its prime factors.
+///
+/// By a theorem of Lucas every prime factor q of 2^(2^k) + 1
+/// with k at least 2 is 1 mod 2^(k + 2).
+/// Candidates of this form up to about 2^(k + 22) are tried
+/// by trial division, until the rest is prime or fits into 128 bits.
+/// Any remaining part is factored with `factor_big`,
+/// which is hopeless if it has two large prime factors,
+/// as for all Fermat numbers from k = 7 on.
+/// Panics if the number of bits 2^k does not fit into usize,
+/// that is for k of 64 or more on 64-bit targets.
+///
+/// ```
+/// extern crate jfactor;
+/// extern crate num_bigint;
+///
+/// use num_bigint::BigUint;
+///
+/// use jfactor::factor_fermat;
+///
+/// # fn main() {
+/// let big = |n: u64| BigUint::from(n);
+/// // Euler's factorization of 2^32 + 1
+/// let f = factor_fermat(5);
+/// assert_eq!(vec![&big(641), &big(6700417)], f.primes().collect::<Vec<_>>());
+/// # }
+/// ```
+///
+pub fn factor_fermat(k: u32) -> Factorization<BigUint> {
+    assert!(k < usize::BITS, "2^(2^k) + 1 is too large");
+    let n = (BigUint::one() << (1_usize << k)) + BigUint::one();
+    factor_with_candidates(n, fermat_candidates(k))
+}
+
+
+/// Returns the candidates m 2^(k + 2) + 1 for prime factors
+/// of the Fermat number 2^(2^k) + 1 with k at least 2,
+/// which pass the test 2^(2^k) = -1 mod q.
+///
+/// The candidates stop before they overflow 64 bits.
+///
+fn fermat_candidates(k: u32) -> impl Iterator<Item = u64> {
+    let step = 1_u64.checked_shl(k + 2).unwrap_or(0);
+    (1..=SPECIAL_TRIAL_LIMIT)
+        .map_while(move |m| m.checked_mul(step)?.checked_add(1))
+        .filter(move |&q| q > 1 && mod_pow(2, 1 << k, q) == q - 1)
+}
+
+
+/// Returns the Mersenne number 2^p - 1.
+///
+fn mersenne(p: u32) -> BigUint {
+    (BigUint::one() << p as usize) - BigUint::one()
+}
+
+
+/// Factors n by trial division with the candidates,
+/// until the rest is prime or fits into 128 bits,
+/// then finishes with `factor_big`.
+///
+fn factor_with_candidates<I>(n: BigUint, mut candidates: I) -> Factorization<BigUint>
+where
+    I: Iterator<Item = u64>,
+{
+    let mut factorization = Factorization::new();
+    let mut rest = n;
+    while to_u128(&rest).is_none() && !bpsw_is_prime_big(&rest) {
+        let q = match candidates.find(|&q| (&rest % BigUint::from(q)).is_zero()) {
+            Some(q) => BigUint::from(q),
+            None => break,
+        };
+        loop {
+            let (quotient, remainder) = rest.div_rem(&q);
+            if !remainder.is_zero() {
+                break;
+            }
+            rest = quotient;
+            factorization.insert(q.clone(), 1);
+        }
+    }
+    factorization * factor_big(&rest)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lucas_lehmer() {
+        // the Mersenne prime exponents below 1000
+        let expected = vec![2, 3, 5, 7, 13, 17, 19, 31, 61, 89, 107, 127, 521, 607];
+        let actual = (0..1000).filter(|&p| lucas_lehmer(p)).collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_lucas_lehmer_large() {
+        assert!(lucas_lehmer(4423));
+        assert!(!lucas_lehmer(4421));
+    }
+
+    #[test]
+    fn test_factor_mersenne_small() {
+        assert!(factor_mersenne(1).is_one());
+        for p in 2..90 {
+            assert_eq!(factor_big(&mersenne(p)), factor_mersenne(p));
+        }
+    }
+
+    #[test]
+    fn test_factor_mersenne() {
+        // 2^131 - 1 = 263 * q with a 123-bit prime q
+        let f = factor_mersenne(131);
+        let q = mersenne(131) / BigUint::from(263_u32);
+        assert_eq!(vec![&BigUint::from(263_u32), &q], f.primes().collect::<Vec<_>>());
+        assert!(f.iter().all(|(_, &e)| e == 1));
+
+        // a composite exponent with a repeated factor
+        let f = factor_mersenne(200);
+        assert_eq!(3, f.exponent(&BigUint::from(5_u32)));
+        assert_eq!(18, f.omega());
+        assert_eq!(Some(mersenne(200)), f.value());
+        assert!(f.primes().all(bpsw_is_prime_big));
+
+        let f = factor_mersenne(521);
+        assert_eq!(vec![&mersenne(521)], f.primes().collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_factor_mersenne_zero() {
+        factor_mersenne(0);
+    }
+
+    #[test]
+    fn test_factor_fermat() {
+        let big = |n: u64| BigUint::from(n);
+        let primes = |k| factor_fermat(k).into_map().into_iter().collect::<Vec<_>>();
+        assert_eq!(vec![(big(3), 1)], primes(0));
+        assert_eq!(vec![(big(5), 1)], primes(1));
+        assert_eq!(vec![(big(17), 1)], primes(2));
+        assert_eq!(vec![(big(257), 1)], primes(3));
+        assert_eq!(vec![(big(65537), 1)], primes(4));
+        assert_eq!(vec![(big(641), 1), (big(6700417), 1)], primes(5));
+        assert_eq!(vec![(big(274177), 1), (big(67280421310721), 1)], primes(6));
+    }
+
+    #[test]
+    #[should_panic(expected = "2^(2^k) + 1 is too large")]
+    fn test_factor_fermat_too_large() {
+        factor_fermat(usize::BITS);
+    }
+
+    #[test]
+    fn test_fermat_candidates() {
+        // the small prime factors of 2^512 + 1, 2^1024 + 1 and 2^2048 + 1
+        assert_eq!(vec![2424833], fermat_candidates(9).collect::<Vec<_>>());
+        // the second factor 6487031809 of 2^1024 + 1 is beyond the limit
+        assert_eq!(vec![45592577], fermat_candidates(10).collect::<Vec<_>>());
+        assert_eq!(vec![319489, 974849], fermat_candidates(11).take(2).collect::<Vec<_>>());
+        assert_eq!(0, fermat_candidates(62).count());
+    }
+}
@@ -0,0 +1,174 @@
+
+extern crate num;
+
+use self::num::integer::gcd;
+
+use arith::SQUARES_MOD_64;
+
+
+/// The multipliers tried in turn, the squarefree products of 3, 5, 7 and 11.
+const MULTIPLIERS: [u64; 16] = [
+    1,
+    3,
+    5,
+    7,
+    11,
+    3 * 5,
+    3 * 7,
+    3 * 11,
+    5 * 7,
+    5 * 11,
+    7 * 11,
+    3 * 5 * 7,
+    3 * 5 * 11,
+    3 * 7 * 11,
+    5 * 7 * 11,
+    3 * 5 * 7 * 11,
+];
+
+
+/// Finds a factor using Shanks' square forms factorization.
+///
+/// SQUFOF expands the square root of k n as a continued fraction
+/// until it finds a square form, and from that a factor.
+/// The runtime is on the order of the fourth root of n,
+/// but the arithmetic is on numbers of half the size of n,
+/// which makes it very fast for composites up to about 62 bits.
+///
+/// Returns None if no nontrivial factor was found,
+/// in particular if n is less than 4 or prime.
+/// This can also happen for composites, though rarely.
+/// The returned factor may be composite.
+///
+pub fn squfof(n: u64) -> Option<u64> {
+    if n < 4 {
+        return None;
+    }
+    if n & 1 == 0 {
+        return Some(2);
+    }
+    let s = n.isqrt();
+    if s * s == n {
+        return Some(s);
+    }
+
+    for &k in &MULTIPLIERS {
+        let g = gcd(k, n);
+        if g != 1 {
+            if g != n {
+                return Some(g);
+            }
+            continue;
+        }
+        if let Some(f) = squfof_multiplier(n, k) {
+            return Some(f);
+        }
+    }
+    None
+}
+
+
+/// Runs SQUFOF on k n.
+///
+fn squfof_multiplier(n: u64, k: u64) -> Option<u64> {
+    // the values P and Q are less than 2 sqrt(k n), which fits in 38 bits
+    let d = k as i128 * n as i128;
+    let p0 = d.isqrt() as i64;
+    let mut q = (d - p0 as i128 * p0 as i128) as i64;
+    if q == 0 {
+        // k n is a square
+        let g = gcd(p0 as u64, n);
+        return if g != 1 && g != n { Some(g) } else { None };
+    }
+    let limit = 3 * (2.0 * (2.0 * (n as f64).sqrt()).sqrt()) as u64;
+
+    // forward cycle: find a square Q at an even index
+    let mut p = p0;
+    let mut p_prev = p0;
+    let mut q_prev = 1;
+    let mut r = 0;
+    let mut found = false;
+    for i in 2..limit {
+        let b = (p0 + p) / q;
+        p = b * q - p;
+        let q_next = q_prev + b * (p_prev - p);
+        q_prev = q;
+        q = q_next;
+        p_prev = p;
+        if i & 1 == 0 && (SQUARES_MOD_64 >> (q & 63)) & 1 != 0 {
+            r = q.isqrt();
+            if r * r == q {
+                found = true;
+                break;
+            }
+        }
+    }
+    if !found {
+        return None;
+    }
+
+    // reverse cycle: start from the square root of the form
+    // and iterate until P repeats
+    let b = (p0 - p) / r;
+    p += b * r;
+    q_prev = r;
+    q = ((d - p as i128 * p as i128) / r as i128) as i64;
+    for _ in 0..limit {
+        let b = (p0 + p) / q;
+        p_prev = p;
+        p = b * q - p;
+        let q_next = q_prev + b * (p_prev - p);
+        q_prev = q;
+        q = q_next;
+        if p == p_prev {
+            let g = gcd(n, q_prev as u64);
+            return if g != 1 && g != n { Some(g) } else { None };
+        }
+    }
+    None
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_factor(n: u64) {
+        let f = squfof(n).unwrap();
+        assert!(f != 1 && f != n);
+        assert_eq!(0, n % f);
+    }
+
+    #[test]
+    fn test_small() {
+        assert_eq!(None, squfof(3));
+        assert_eq!(Some(2), squfof(4));
+        assert_eq!(Some(2), squfof(1000));
+        check_factor(11 * 13);
+    }
+
+    #[test]
+    fn test_square() {
+        assert_eq!(Some(1000003), squfof(1000003 * 1000003));
+    }
+
+    #[test]
+    fn test_semiprimes() {
+        check_factor(1000003 * 1000033);
+        check_factor(1000003 * 2147483647);
+        check_factor(2147483647 * 2147483629);
+        // 63 bits
+        check_factor(2147483647 * 2147483659);
+    }
+
+    #[test]
+    fn test_multiplier_shares_factor() {
+        assert_eq!(Some(3), squfof(3 * 1000003));
+    }
+
+    #[test]
+    fn test_prime() {
+        assert_eq!(None, squfof(1000003));
+        assert_eq!(None, squfof(4611686018427387847));
+    }
+}
@@ -0,0 +1,96 @@
+//! Bindings for JavaScript via wasm-bindgen.
+//!
+//! This is only available with the `wasm` feature.
+//!
+//! Integers are passed as `BigInt` on the JavaScript side,
+//! errors are thrown as JavaScript `Error`s.
+
+extern crate wasm_bindgen;
+
+use self::wasm_bindgen::prelude::*;
+
+use divisors::divisors_of;
+use factor::{try_factor, FactorError};
+
+
+/// Returns the prime factors of n in ascending order,
+/// repeated according to their multiplicity.
+///
+/// Throws an error if n is zero.
+///
+#[wasm_bindgen]
+pub fn factor(n: u64) -> Result<Vec<u64>, JsError> {
+    factor_flat(n).map_err(to_js_error)
+}
+
+
+/// Returns the divisors of n in ascending order.
+///
+/// Throws an error if n is zero.
+///
+#[wasm_bindgen]
+pub fn divisors(n: u64) -> Result<Vec<u64>, JsError> {
+    divisors_sorted(n).map_err(to_js_error)
+}
+
+
+/// Returns Euler's totient of n,
+/// the number of integers from 1 to n which are coprime to n.
+///
+/// Throws an error if n is zero.
+///
+#[wasm_bindgen(js_name = eulerPhi)]
+pub fn euler_phi(n: u64) -> Result<u64, JsError> {
+    totient(n).map_err(to_js_error)
+}
+
+
+fn to_js_error(e: FactorError) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+
+fn factor_flat(n: u64) -> Result<Vec<u64>, FactorError> {
+    let factorization = try_factor(n)?;
+    Ok(factorization
+        .iter()
+        .flat_map(|(&p, &e)| (0..e).map(move |_| p))
+        .collect())
+}
+
+
+fn divisors_sorted(n: u64) -> Result<Vec<u64>, FactorError> {
+    Ok(divisors_of(&try_factor(n)?))
+}
+
+
+fn totient(n: u64) -> Result<u64, FactorError> {
+    Ok(try_factor(n)?.euler_phi())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_factor_flat() {
+        assert_eq!(Ok(vec![]), factor_flat(1));
+        assert_eq!(Ok(vec![2, 2, 2, 3, 3, 5]), factor_flat(360));
+        assert_eq!(Err(FactorError::Zero), factor_flat(0));
+    }
+
+    #[test]
+    fn test_divisors_sorted() {
+        assert_eq!(Ok(vec![1]), divisors_sorted(1));
+        assert_eq!(Ok(vec![1, 2, 3, 4, 6, 12]), divisors_sorted(12));
+        assert_eq!(Err(FactorError::Zero), divisors_sorted(0));
+    }
+
+    #[test]
+    fn test_totient() {
+        assert_eq!(Ok(1), totient(1));
+        assert_eq!(Ok(96), totient(360));
+        assert_eq!(Ok(1000002), totient(1000003));
+    }
+}